@@ -0,0 +1,209 @@
+use actix_web::{web, HttpResponse};
+use utoipa::OpenApi;
+
+use crate::config::DocsConfig;
+use crate::models::{
+    AffordabilityInput,
+    AffordabilityResult,
+    AnalyticsRequest,
+    BatchItemResult,
+    BatchOutput,
+    ComparisonRequest,
+    ComparisonResult,
+    ComparisonScenario,
+    CostCurvePoint,
+    DayCountConvention,
+    DefaultAssumptions,
+    DelinquencyStatus,
+    DiscountCurve,
+    EscrowAnalysis,
+    EscrowInput,
+    ForbearanceInterest,
+    InstallmentStatus,
+    InterestStatement,
+    InterestStatementRequest,
+    LateFeePolicy,
+    LimitingRatio,
+    LoanAnalytics,
+    LoanModification,
+    LoanPricing,
+    MortgageInput,
+    MortgageInsuranceInput,
+    MortgageInsuranceType,
+    MortgageSchedule,
+    MortgageSummary,
+    ParRatePoint,
+    PaymentApplication,
+    PaymentHistory,
+    PaymentHistoryRequest,
+    PaymentScheduleEntry,
+    PayoffQuote,
+    PayoffRequest,
+    PoolCashFlow,
+    PoolProjection,
+    PoolRequest,
+    PrepaymentPenalty,
+    PrepaymentSpeed,
+    PresentValue,
+    ReceivedPayment,
+    ReconciledInstallment,
+    RefinanceAnalysis,
+    RefinanceInput,
+    RefinanceOffer,
+    RefinanceOfferResult,
+    RentVsBuyAnalysis,
+    RentVsBuyInput,
+    RentVsBuyYear,
+    RepaymentType,
+    RepaymentTypeInfo,
+    ScenarioDifference,
+    ScenarioOverrides,
+    ScenarioResult,
+    ScheduleAggregation,
+    ScheduleYear,
+    SolveRequest,
+    SolveResult,
+    SolveTarget,
+    SolveVariable,
+    StatementSource,
+    ValuationRequest,
+    ZeroRatePoint,
+};
+use super::errors::{FieldError, ProblemDetails};
+use super::handlers;
+
+/// OpenAPI description of the API
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "mortgagekit-rs",
+        description = "A high-precision mortgage engine powered by Rust",
+    ),
+    paths(
+        handlers::calculate_mortgage,
+        handlers::calculate_mortgage_summary,
+        handlers::calculate_batch,
+        handlers::calculate_affordability,
+        handlers::solve,
+        handlers::analyze_refinance,
+        handlers::compare_mortgages,
+        handlers::analyze_rent_vs_buy,
+        handlers::quote_payoff,
+        handlers::reconcile_payment_history,
+        handlers::generate_interest_statement,
+        handlers::project_pool,
+        handlers::analyze_loan,
+        handlers::value_loan,
+        handlers::get_repayment_types,
+        handlers::health_check,
+    ),
+    components(schemas(
+        MortgageInput,
+        RepaymentType,
+        EscrowInput,
+        MortgageInsuranceInput,
+        MortgageInsuranceType,
+        PrepaymentPenalty,
+        LoanModification,
+        ForbearanceInterest,
+        MortgageSchedule,
+        PaymentScheduleEntry,
+        EscrowAnalysis,
+        MortgageSummary,
+        RepaymentTypeInfo,
+        ScheduleAggregation,
+        ScheduleYear,
+        BatchOutput,
+        BatchItemResult,
+        AffordabilityInput,
+        AffordabilityResult,
+        LimitingRatio,
+        SolveRequest,
+        SolveTarget,
+        SolveVariable,
+        SolveResult,
+        RefinanceInput,
+        RefinanceOffer,
+        RefinanceAnalysis,
+        RefinanceOfferResult,
+        ComparisonRequest,
+        ComparisonScenario,
+        ScenarioOverrides,
+        ComparisonResult,
+        ScenarioResult,
+        ScenarioDifference,
+        CostCurvePoint,
+        RentVsBuyInput,
+        RentVsBuyAnalysis,
+        RentVsBuyYear,
+        DayCountConvention,
+        PayoffRequest,
+        PayoffQuote,
+        PaymentHistoryRequest,
+        ReceivedPayment,
+        LateFeePolicy,
+        PaymentHistory,
+        ReconciledInstallment,
+        PaymentApplication,
+        InstallmentStatus,
+        DelinquencyStatus,
+        InterestStatementRequest,
+        InterestStatement,
+        StatementSource,
+        PoolRequest,
+        PrepaymentSpeed,
+        DefaultAssumptions,
+        PoolProjection,
+        PoolCashFlow,
+        AnalyticsRequest,
+        LoanPricing,
+        LoanAnalytics,
+        ValuationRequest,
+        DiscountCurve,
+        ParRatePoint,
+        ZeroRatePoint,
+        PresentValue,
+        ProblemDetails,
+        FieldError,
+    )),
+    tags(
+        (name = "mortgages", description = "Schedules and summaries for a single loan or a batch of loans"),
+        (name = "planning", description = "Affordability, goal seeking and comparisons for borrowers"),
+        (name = "servicing", description = "Payoffs, payment histories and tax statements for existing loans"),
+        (name = "investors", description = "Pool projections, pricing and valuation of loan cash flows"),
+        (name = "information", description = "Service and reference information"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Swagger UI page rendering the spec served next to it, with its assets loaded from `assets_url`
+fn swagger_ui_page(assets_url: &str) -> String {
+    format!(r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>mortgagekit-rs API</title>
+  <link rel="stylesheet" href="{assets_url}/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="{assets_url}/swagger-ui-bundle.js" crossorigin></script>
+  <script>
+    window.onload = () => {{
+      window.ui = SwaggerUIBundle({{ url: "/api/docs/openapi.json", dom_id: "#swagger-ui" }});
+    }};
+  </script>
+</body>
+</html>
+"##, assets_url = assets_url.trim_end_matches('/'))
+}
+
+pub async fn openapi_json() -> HttpResponse {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+pub async fn swagger_ui(config: web::Data<DocsConfig>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(swagger_ui_page(&config.swagger_ui_url))
+}
@@ -1,15 +1,30 @@
-use actix_web::{HttpResponse, ResponseError};
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use actix_web::error::{JsonPayloadError, QueryPayloadError};
+use actix_web::http::StatusCode;
+use serde::Serialize;
 use thiserror::Error;
-use validator::ValidationErrors;
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
 use log::error;
 
+/// Media type of every error body
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
 /// Custom error types for the API
 #[derive(Error, Debug)]
 pub enum ApiError {
     /// Validation error for input parameters
-    #[error("validation_error: {0}")]
-    ValidationError(#[from] ValidationErrors),
-    
+    #[error("validation_error: {} invalid field(s)", .0.len())]
+    ValidationError(Vec<FieldError>),
+
+    /// Request body or query string that cannot be deserialized
+    #[error("malformed_request: {message}")]
+    MalformedRequest {
+        status: StatusCode,
+        message: String,
+        field: Option<String>,
+    },
+
     /// Internal server error
     #[error("internal_error: {0}")]
     InternalError(String),
@@ -17,40 +32,300 @@ pub enum ApiError {
     /// Calculation error
     #[error("calculation_error: {0}")]
     CalculationError(String),
+
+    /// Request body that cannot be read
+    #[error("bad_request: {0}")]
+    BadRequest(String),
+
+    /// `Accept` header naming no format the endpoint can produce
+    #[error("not_acceptable: {0}")]
+    NotAcceptable(String),
+}
+
+/// One rejected field of a request
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    /// Path to the field in the request, e.g. `input.principal` or `payments[2].amount`;
+    /// absent for rules spanning several fields of the request itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+
+    /// Stable identifier of the rule that failed, e.g. `invalid_principal` or `range`
+    pub code: String,
+
+    pub message: String,
+
+    /// Value the request supplied for the field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub rejected_value: Option<serde_json::Value>,
+}
+
+/// RFC 7807 problem details, the body of every error response
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemDetails {
+    /// Always `about:blank`; `code` identifies the problem
+    #[serde(rename = "type")]
+    pub problem_type: String,
+
+    /// HTTP status phrase
+    pub title: String,
+
+    pub status: u16,
+
+    /// Description of this occurrence of the problem
+    pub detail: String,
+
+    /// Stable identifier of the problem, e.g. `validation_error`
+    pub code: String,
+
+    /// Rejected fields of validation and malformed request errors
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+impl ApiError {
+    /// Stable identifier of the error kind
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::ValidationError(_) => "validation_error",
+            ApiError::MalformedRequest { .. } => "malformed_request",
+            ApiError::InternalError(_) => "internal_error",
+            ApiError::CalculationError(_) => "calculation_error",
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::NotAcceptable(_) => "not_acceptable",
+        }
+    }
+
+    /// Prefixes the field paths of a validation error with `path`
+    pub fn at(self, path: &str) -> Self {
+        match self {
+            ApiError::ValidationError(errors) => ApiError::ValidationError(errors.into_iter()
+                .map(|error| FieldError {
+                    field: Some(join_path(path, error.field.as_deref())),
+                    ..error
+                })
+                .collect()),
+            other => other,
+        }
+    }
+
+    pub fn problem(&self) -> ProblemDetails {
+        let status = self.status_code();
+        let (detail, errors) = match self {
+            ApiError::ValidationError(errors) => (
+                "The request contains invalid fields".to_string(),
+                errors.clone(),
+            ),
+            ApiError::MalformedRequest { message, field, .. } => (
+                message.clone(),
+                field.iter()
+                    .map(|field| FieldError {
+                        field: Some(field.clone()),
+                        code: "malformed_field".to_string(),
+                        message: message.clone(),
+                        rejected_value: None,
+                    })
+                    .collect(),
+            ),
+            ApiError::InternalError(message)
+            | ApiError::CalculationError(message)
+            | ApiError::BadRequest(message)
+            | ApiError::NotAcceptable(message) => (message.clone(), Vec::new()),
+        };
+
+        ProblemDetails {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail,
+            code: self.code().to_string(),
+            errors,
+        }
+    }
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        ApiError::ValidationError(field_errors(&errors, ""))
+    }
 }
 
 impl ResponseError for ApiError {
     fn error_response(&self) -> HttpResponse {
         match self {
-            ApiError::ValidationError(errors) => {
-                error!("Validation error: {:?}", errors);
-                HttpResponse::BadRequest().json(serde_json::json!({
-                    "error": "validation_error",
-                    "details": errors
-                }))
+            ApiError::InternalError(message) => error!("Internal server error: {}", message),
+            other => error!("Request failed: {}", other),
+        }
+
+        HttpResponse::build(self.status_code())
+            .content_type(PROBLEM_JSON)
+            .json(self.problem())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            ApiError::MalformedRequest { status, .. } => *status,
+            ApiError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::CalculationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+        }
+    }
+}
+
+/// Validates a request, reporting each rejected field with the value supplied for it
+pub fn validate_request<T: Validate + Serialize>(request: &T) -> Result<(), ApiError> {
+    validate_request_with(request, T::validate)
+}
+
+/// Like [`validate_request`], checking the request with `validate` instead of its `Validate` rules
+pub fn validate_request_with<T: Serialize>(
+    request: &T,
+    validate: impl FnOnce(&T) -> Result<(), ValidationErrors>,
+) -> Result<(), ApiError> {
+    validate(request).map_err(|errors| {
+        let body = serde_json::to_value(request).unwrap_or_default();
+        let mut rejected = field_errors(&errors, "");
+        for error in &mut rejected {
+            if error.rejected_value.is_none() {
+                error.rejected_value = error.field.as_deref()
+                    .and_then(|field| lookup(&body, field))
+                    .cloned();
             }
-            ApiError::InternalError(message) => {
-                error!("Internal server error: {}", message);
-                HttpResponse::InternalServerError().json(serde_json::json!({
-                    "error": "internal_error",
-                    "message": message
-                }))
+        }
+        ApiError::ValidationError(rejected)
+    })
+}
+
+/// Error handler for the JSON extractor
+pub fn json_error(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let (status, field) = match &err {
+        JsonPayloadError::OverflowKnownLength { .. } | JsonPayloadError::Overflow { .. } => {
+            (StatusCode::PAYLOAD_TOO_LARGE, None)
+        }
+        JsonPayloadError::ContentType => (StatusCode::UNSUPPORTED_MEDIA_TYPE, None),
+        JsonPayloadError::Deserialize(inner) => (StatusCode::BAD_REQUEST, named_field(&inner.to_string())),
+        _ => (StatusCode::BAD_REQUEST, None),
+    };
+
+    ApiError::MalformedRequest {
+        status,
+        message: err.to_string(),
+        field,
+    }.into()
+}
+
+/// Error handler for the query string extractor
+pub fn query_error(err: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let message = err.to_string();
+    ApiError::MalformedRequest {
+        status: StatusCode::BAD_REQUEST,
+        field: named_field(&message),
+        message,
+    }.into()
+}
+
+/// Field named by a serde error such as "missing field `principal`"
+fn named_field(message: &str) -> Option<String> {
+    let (_, rest) = message.split_once("field `")?;
+    let (field, _) = rest.split_once('`')?;
+    Some(field.to_string())
+}
+
+/// Flattens nested validation errors into paths over the request body
+fn field_errors(errors: &ValidationErrors, path: &str) -> Vec<FieldError> {
+    let mut flattened = Vec::new();
+    for (field, kind) in errors.errors() {
+        // Struct-level rules are reported against the struct they validate
+        let field_path = if *field == "__all__" {
+            path.to_string()
+        } else {
+            join_path(path, Some(&camel_case(field)))
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                flattened.extend(errors.iter().map(|error| FieldError {
+                    field: Some(field_path.clone()).filter(|path| !path.is_empty()),
+                    code: error.code.to_string(),
+                    message: describe(error),
+                    rejected_value: error.params.get("value").cloned(),
+                }));
+            }
+            ValidationErrorsKind::Struct(errors) => {
+                flattened.extend(field_errors(errors, &field_path));
             }
-            ApiError::CalculationError(message) => {
-                error!("Calculation error: {}", message);
-                HttpResponse::UnprocessableEntity().json(serde_json::json!({
-                    "error": "calculation_error",
-                    "message": message
-                }))
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    flattened.extend(field_errors(errors, &format!("{}[{}]", field_path, index)));
+                }
             }
         }
     }
 
-    fn status_code(&self) -> actix_web::http::StatusCode {
-        match self {
-            ApiError::ValidationError(_) => actix_web::http::StatusCode::BAD_REQUEST,
-            ApiError::InternalError(_) => actix_web::http::StatusCode::INTERNAL_SERVER_ERROR,
-            ApiError::CalculationError(_) => actix_web::http::StatusCode::UNPROCESSABLE_ENTITY,
+    flattened.sort_by(|a, b| a.field.cmp(&b.field).then_with(|| a.code.cmp(&b.code)));
+    flattened
+}
+
+fn join_path(path: &str, field: Option<&str>) -> String {
+    match field {
+        Some(field) if path.is_empty() || field.starts_with('[') => format!("{}{}", path, field),
+        Some(field) => format!("{}.{}", path, field),
+        None => path.to_string(),
+    }
+}
+
+/// Request bodies name their fields in camelCase
+fn camel_case(field: &str) -> String {
+    let mut name = String::with_capacity(field.len());
+    let mut upper = false;
+    for character in field.chars() {
+        if character == '_' {
+            upper = true;
+        } else if upper {
+            name.extend(character.to_uppercase());
+            upper = false;
+        } else {
+            name.push(character);
+        }
+    }
+    name
+}
+
+/// Value at a path such as `payments[2].amount` in a JSON body
+fn lookup<'a>(body: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.').try_fold(body, |value, segment| {
+        let mut parts = segment.split('[');
+        let name = parts.next().unwrap_or_default();
+        let value = if name.is_empty() { value } else { value.get(name)? };
+        parts.try_fold(value, |value, index| value.get(index.trim_end_matches(']').parse::<usize>().ok()?))
+    })
+}
+
+/// Human-readable message for a failed rule
+fn describe(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+
+    let param = |name: &str| error.params.get(name).map(|value| value.to_string());
+    match (error.code.as_ref(), param("min"), param("max")) {
+        ("range", Some(min), Some(max)) => format!("must be between {} and {}", min, max),
+        ("range", Some(min), None) => format!("must be at least {}", min),
+        ("range", None, Some(max)) => format!("must be at most {}", max),
+        ("length", Some(min), Some(max)) => format!("must have between {} and {} items", min, max),
+        ("length", Some(min), None) => format!("must have at least {} items", min),
+        ("length", None, Some(max)) => format!("must have at most {} items", max),
+        (code, _, _) => {
+            let words = code.replace('_', " ");
+            let mut characters = words.chars();
+            characters.next()
+                .map(|first| first.to_uppercase().chain(characters).collect())
+                .unwrap_or_default()
         }
     }
 }
@@ -58,34 +333,110 @@ impl ResponseError for ApiError {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use validator::ValidationError;
+    use actix_web::body::to_bytes;
 
-    #[test]
-    fn test_validation_error_response() {
-        let mut errors = ValidationErrors::new();
-        let mut error = ValidationError::new("test");
-        error.message = Some("test message".into());
-        errors.add("field", error);
+    #[derive(Serialize, Validate)]
+    #[serde(rename_all = "camelCase")]
+    struct Loan {
+        #[validate(range(min = 1, max = 50))]
+        term_years: u32,
+
+        #[validate]
+        payments: Vec<Payment>,
+    }
+
+    #[derive(Serialize, Validate)]
+    #[serde(rename_all = "camelCase")]
+    struct Payment {
+        #[validate(custom = "positive")]
+        received_amount: i64,
+    }
+
+    fn positive(amount: i64) -> Result<(), ValidationError> {
+        if amount > 0 { Ok(()) } else { Err(ValidationError::new("invalid_amount")) }
+    }
 
-        let api_error = ApiError::ValidationError(errors);
+    #[actix_web::test]
+    async fn test_validation_error_response() {
+        let loan = Loan {
+            term_years: 80,
+            payments: vec![Payment { received_amount: 5 }, Payment { received_amount: -5 }],
+        };
+        let api_error = validate_request(&loan).unwrap_err();
         let response = api_error.error_response();
-        
-        assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers().get("content-type").unwrap(), PROBLEM_JSON);
+
+        let body: serde_json::Value = serde_json::from_slice(&to_bytes(response.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body["type"], "about:blank");
+        assert_eq!(body["status"], 400);
+        assert_eq!(body["code"], "validation_error");
+        assert_eq!(body["errors"][0]["field"], "payments[1].receivedAmount");
+        assert_eq!(body["errors"][0]["code"], "invalid_amount");
+        assert_eq!(body["errors"][0]["message"], "Invalid amount");
+        assert_eq!(body["errors"][0]["rejectedValue"], -5);
+        assert_eq!(body["errors"][1]["field"], "termYears");
+        assert_eq!(body["errors"][1]["code"], "range");
+        assert_eq!(body["errors"][1]["rejectedValue"], 80);
+    }
+
+    #[test]
+    fn test_nested_paths_are_prefixed() {
+        let error = ApiError::ValidationError(vec![FieldError {
+            field: Some("principal".to_string()),
+            code: "invalid_principal".to_string(),
+            message: "Invalid principal".to_string(),
+            rejected_value: None,
+        }]).at("scenarios[2]");
+
+        match error {
+            ApiError::ValidationError(errors) => {
+                assert_eq!(errors[0].field.as_deref(), Some("scenarios[2].principal"));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
     }
 
     #[test]
     fn test_internal_error_response() {
         let api_error = ApiError::InternalError("test error".to_string());
         let response = api_error.error_response();
-        
-        assert_eq!(response.status(), actix_web::http::StatusCode::INTERNAL_SERVER_ERROR);
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
 
     #[test]
     fn test_calculation_error_response() {
         let api_error = ApiError::CalculationError("division by zero".to_string());
+        let problem = api_error.problem();
+
+        assert_eq!(api_error.error_response().status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(problem.title, "Unprocessable Entity");
+        assert_eq!(problem.code, "calculation_error");
+        assert_eq!(problem.detail, "division by zero");
+        assert!(problem.errors.is_empty());
+    }
+
+    #[test]
+    fn test_bad_request_response() {
+        let api_error = ApiError::BadRequest("unexpected end of input".to_string());
         let response = api_error.error_response();
-        
-        assert_eq!(response.status(), actix_web::http::StatusCode::UNPROCESSABLE_ENTITY);
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_not_acceptable_response() {
+        let api_error = ApiError::NotAcceptable("image/png".to_string());
+
+        assert_eq!(api_error.error_response().status(), StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(api_error.problem().code, "not_acceptable");
+    }
+
+    #[test]
+    fn test_named_field() {
+        assert_eq!(named_field("missing field `principal` at line 1 column 2"), Some("principal".to_string()));
+        assert_eq!(named_field("expected value at line 1 column 1"), None);
     }
 }
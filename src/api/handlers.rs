@@ -1,65 +1,591 @@
-use actix_web::{web, HttpResponse};
-use validator::Validate;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
-use crate::models::{MortgageInput, RepaymentType, RepaymentTypeInfo};
+use actix_web::{web, HttpRequest, HttpResponse, HttpResponseBuilder};
+use actix_web::http::header;
+use actix_web::body::{BodySize, MessageBody};
+use tokio::sync::mpsc;
+
+use crate::models::{
+    AffordabilityInput,
+    AnalyticsRequest,
+    BatchItemResult,
+    BatchOptions,
+    ComparisonRequest,
+    InterestStatementRequest,
+    MortgageInput,
+    PaymentHistoryRequest,
+    PayoffRequest,
+    PoolRequest,
+    RefinanceInput,
+    RentVsBuyInput,
+    RepaymentTypeInfo,
+    ScheduleQuery,
+    SolveRequest,
+    ValuationRequest,
+};
 use crate::calculators::{
-    MortgageCalculator,
-    StandardCalculator,
-    InterestOnlyCalculator,
-    AcceleratedCalculator,
-    BalloonCalculator,
-    FloatingRateCalculator,
+    self,
+    AffordabilityCalculator,
+    AnalyticsCalculator,
+    BatchCalculator,
+    ComparisonCalculator,
+    GoalSeekSolver,
+    InterestStatementCalculator,
+    PaymentHistoryReconciler,
+    PayoffCalculator,
+    PoolCalculator,
+    RefinanceCalculator,
+    RentVsBuyCalculator,
+    ScheduleAggregator,
+    ValuationCalculator,
 };
-use super::errors::ApiError;
+use crate::export::{ExportFormat, Report};
+use super::errors::{validate_request, validate_request_with, ApiError};
+
+/// Header carrying the number of rows matching a schedule query before paging
+const TOTAL_COUNT_HEADER: &str = "X-Total-Count";
 
+/// Picks the response format from the request's `Accept` header
+fn export_format(req: &HttpRequest) -> Result<ExportFormat, ApiError> {
+    let accept = req.headers().get(header::ACCEPT).and_then(|value| value.to_str().ok());
+    ExportFormat::negotiate(accept).ok_or_else(|| {
+        let available = ExportFormat::ALL.iter()
+            .map(|format| format.media_type())
+            .collect::<Vec<_>>()
+            .join(", ");
+        ApiError::NotAcceptable(format!(
+            "Cannot respond with {}; available formats are {}",
+            accept.unwrap_or_default(), available
+        ))
+    })
+}
+
+/// Successful response in `format`, downloaded as `name` with the format's extension unless JSON
+fn export_response(format: ExportFormat, name: &str) -> HttpResponseBuilder {
+    let mut response = HttpResponse::Ok();
+    response.content_type(format.content_type());
+    if format != ExportFormat::Json {
+        // Reports open in the browser to print; spreadsheets download
+        let disposition = if format == ExportFormat::Html { "inline" } else { "attachment" };
+        response.insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("{}; filename=\"{}.{}\"", disposition, name, format.extension()),
+        ));
+    }
+    response
+}
+
+/// Calculate the payment schedule for a loan, or a page, date range or yearly roll-up of it
+#[utoipa::path(
+    post,
+    path = "/api/v1/calculate",
+    tag = "mortgages",
+    request_body = MortgageInput,
+    params(ScheduleQuery),
+    responses(
+        (status = 200, description = "Payment schedule with the selected payments, or yearly totals ([ScheduleYear]) when `aggregate` is set; \
+            CSV, XLSX or printable HTML of the same rows when the `Accept` header asks for them",
+            content(
+                ("application/json" = MortgageSchedule),
+                ("text/csv" = String),
+                ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" = String),
+                ("text/html" = String),
+            ),
+            headers(("X-Total-Count" = usize, description = "Payments or years matching the date range, before paging"))),
+        (status = 400, description = "Invalid loan terms or query", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable format", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "The loan cannot be calculated", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn calculate_mortgage(
-    input: web::Json<MortgageInput>
+    req: HttpRequest,
+    input: web::Json<MortgageInput>,
+    query: web::Query<ScheduleQuery>
 ) -> Result<HttpResponse, ApiError> {
+    let format = export_format(&req)?;
     let input_data = input.into_inner();
-    input_data.validate()?;
-
-    let schedule = match input_data.repayment_type {
-        RepaymentType::StandardPrincipalAndInterest => 
-            StandardCalculator::calculate_schedule(&input_data),
-        RepaymentType::InterestOnly => 
-            InterestOnlyCalculator::calculate_schedule(&input_data),
-        RepaymentType::AcceleratedBiweekly => 
-            AcceleratedCalculator::calculate_schedule(&input_data),
-        RepaymentType::BalloonPayment => 
-            BalloonCalculator::calculate_schedule(&input_data),
-        RepaymentType::FloatingRate =>
-            FloatingRateCalculator::calculate_schedule(&input_data),
+    let query = query.into_inner();
+    validate_request(&query)?;
+    validate_loan(&input_data)?;
+
+    if let Some(aggregation) = query.aggregate {
+        let payments_per_year = input_data.repayment_type.payments_per_year();
+        let mut response = export_response(format, "schedule");
+
+        if format == ExportFormat::Json {
+            let entries = calculators::schedule_entries(&input_data)
+                .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+            let (years, total) = ScheduleAggregator::yearly(entries, &query, aggregation, payments_per_year);
+            response.insert_header((TOTAL_COUNT_HEADER, total));
+            return Ok(response.json(years));
+        }
+
+        // Floating rates are redrawn on every calculation, so the summary and years must share one schedule
+        let schedule = calculators::calculate_schedule(&input_data)
+            .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+        let summary = calculators::summarize_schedule(&input_data, &schedule)
+            .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+        let (years, total) = ScheduleAggregator::yearly(schedule.schedule, &query, aggregation, payments_per_year);
+        response.insert_header((TOTAL_COUNT_HEADER, total));
+        return Ok(match Report::years(&summary, &years).render(format) {
+            Some(body) => response.body(body),
+            None => response.json(years),
+        });
+    }
+
+    let mut schedule = calculators::calculate_schedule(&input_data)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+    // Exports summarize the whole loan, so take the summary before selecting rows
+    let summary = match format {
+        ExportFormat::Json => None,
+        _ => Some(calculators::summarize_schedule(&input_data, &schedule)
+            .map_err(|e| ApiError::CalculationError(e.to_string()))?),
     };
+    let (entries, total) = ScheduleAggregator::select(std::mem::take(&mut schedule.schedule), &query);
+    schedule.schedule = entries;
 
-    Ok(HttpResponse::Ok().json(schedule))
+    let mut response = export_response(format, "schedule");
+    response.insert_header((TOTAL_COUNT_HEADER, total));
+    let body = summary.and_then(|summary| Report::schedule(&summary, &schedule).render(format));
+    Ok(match body {
+        Some(body) => response.body(body),
+        None => response.json(schedule),
+    })
 }
 
+/// Calculate the totals for a loan without the payment-by-payment schedule
+#[utoipa::path(
+    post,
+    path = "/api/v1/calculate/summary",
+    tag = "mortgages",
+    request_body = MortgageInput,
+    responses(
+        (status = 200, description = "Loan summary, or CSV, XLSX or printable HTML of it when the `Accept` header asks for them",
+            content(
+                ("application/json" = MortgageSummary),
+                ("text/csv" = String),
+                ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" = String),
+                ("text/html" = String),
+            )),
+        (status = 400, description = "Invalid loan terms", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable format", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "The loan cannot be calculated", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn calculate_mortgage_summary(
+    req: HttpRequest,
     input: web::Json<MortgageInput>
 ) -> Result<HttpResponse, ApiError> {
+    let format = export_format(&req)?;
     let input_data = input.into_inner();
-    input_data.validate()?;
-
-    let summary = match input_data.repayment_type {
-        RepaymentType::StandardPrincipalAndInterest => 
-            StandardCalculator::calculate_summary(&input_data),
-        RepaymentType::InterestOnly => 
-            InterestOnlyCalculator::calculate_summary(&input_data),
-        RepaymentType::AcceleratedBiweekly => 
-            AcceleratedCalculator::calculate_summary(&input_data),
-        RepaymentType::BalloonPayment => 
-            BalloonCalculator::calculate_summary(&input_data),
-        RepaymentType::FloatingRate =>
-            FloatingRateCalculator::calculate_summary(&input_data),
-    };
+    validate_loan(&input_data)?;
+
+    let summary = calculators::calculate_summary(&input_data)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+
+    let mut response = export_response(format, "summary");
+    Ok(match Report::summary(&summary).render(format) {
+        Some(body) => response.body(body),
+        None => response.json(summary),
+    })
+}
+
+/// Find the largest loan and purchase price a borrower's income and debts support
+#[utoipa::path(
+    post,
+    path = "/api/v1/affordability",
+    tag = "planning",
+    request_body = AffordabilityInput,
+    responses(
+        (status = 200, description = "Largest affordable loan and the ratio limiting it", body = AffordabilityResult),
+        (status = 400, description = "Invalid borrower finances or loan terms", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Payment factor overflowed for the rate and term", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn calculate_affordability(
+    input: web::Json<AffordabilityInput>
+) -> Result<HttpResponse, ApiError> {
+    let input_data = input.into_inner();
+    validate_request(&input_data)?;
+
+    let result = AffordabilityCalculator::calculate(&input_data)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// Solve for the loan term that meets a target payment, total interest, payoff date or balloon amount
+#[utoipa::path(
+    post,
+    path = "/api/v1/solve",
+    tag = "planning",
+    request_body = SolveRequest,
+    responses(
+        (status = 200, description = "Value of the solved term and the loan it produces", body = SolveResult),
+        (status = 400, description = "Invalid loan terms or target", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "No value of the term meets the target", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn solve(
+    request: web::Json<SolveRequest>
+) -> Result<HttpResponse, ApiError> {
+    let request_data = request.into_inner();
+    validate_request(&request_data)?;
+    validate_loan(&request_data.input).map_err(|e| e.at("input"))?;
+
+    let result = GoalSeekSolver::solve(&request_data)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// Compare refinance offers against keeping the existing loan
+#[utoipa::path(
+    post,
+    path = "/api/v1/refinance",
+    tag = "planning",
+    request_body = RefinanceInput,
+    responses(
+        (status = 200, description = "Savings and break-even month of each offer", body = RefinanceAnalysis),
+        (status = 400, description = "Invalid existing loan or offers", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "An offer or the existing loan cannot be calculated", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn analyze_refinance(
+    input: web::Json<RefinanceInput>
+) -> Result<HttpResponse, ApiError> {
+    let input_data = input.into_inner();
+    validate_request(&input_data)?;
+    validate_loan(&input_data.existing_loan).map_err(|e| e.at("existingLoan"))?;
+
+    let analysis = RefinanceCalculator::analyze(&input_data)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(analysis))
+}
+
+/// Compare loans side by side, in full or as overrides of a base loan
+#[utoipa::path(
+    post,
+    path = "/api/v1/compare",
+    tag = "planning",
+    request_body = ComparisonRequest,
+    responses(
+        (status = 200, description = "Summary of each scenario and its differences from the first", body = ComparisonResult),
+        (status = 400, description = "Invalid scenarios", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "A scenario cannot be calculated", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn compare_mortgages(
+    request: web::Json<ComparisonRequest>
+) -> Result<HttpResponse, ApiError> {
+    let request_data = request.into_inner();
+    validate_request(&request_data)?;
+
+    // Overrides can push a valid base loan out of range, so check each resolved loan too
+    let scenarios = request_data.resolve();
+    for (index, (_, input)) in scenarios.iter().enumerate() {
+        validate_loan(input).map_err(|e| e.at(&format!("scenarios[{}]", index)))?;
+    }
+
+    let result = ComparisonCalculator::compare(&scenarios)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// Compare the net worth of buying a home against renting and investing the difference
+#[utoipa::path(
+    post,
+    path = "/api/v1/rent-vs-buy",
+    tag = "planning",
+    request_body = RentVsBuyInput,
+    responses(
+        (status = 200, description = "Year-by-year costs and net worth of owning and renting", body = RentVsBuyAnalysis),
+        (status = 400, description = "Invalid purchase, rent or growth assumptions", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "The purchase loan cannot be calculated", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn analyze_rent_vs_buy(
+    input: web::Json<RentVsBuyInput>
+) -> Result<HttpResponse, ApiError> {
+    let input_data = input.into_inner();
+    validate_request(&input_data)?;
+
+    let analysis = RentVsBuyCalculator::analyze(&input_data)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(analysis))
+}
+
+/// Quote the amount needed to pay a loan off on a given date
+#[utoipa::path(
+    post,
+    path = "/api/v1/payoff",
+    tag = "servicing",
+    request_body = PayoffRequest,
+    responses(
+        (status = 200, description = "Payoff amount with accrued interest and fees", body = PayoffQuote),
+        (status = 400, description = "Invalid loan terms or payoff date", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "The loan cannot be calculated", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn quote_payoff(
+    request: web::Json<PayoffRequest>
+) -> Result<HttpResponse, ApiError> {
+    let request_data = request.into_inner();
+    validate_request(&request_data)?;
+    validate_loan(&request_data.input).map_err(|e| e.at("input"))?;
+
+    let quote = PayoffCalculator::quote(&request_data)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(quote))
+}
+
+/// Apply received payments to a loan's installments and report its delinquency
+#[utoipa::path(
+    post,
+    path = "/api/v1/payment-history",
+    tag = "servicing",
+    request_body = PaymentHistoryRequest,
+    responses(
+        (status = 200, description = "Status of each installment and how each payment was applied", body = PaymentHistory),
+        (status = 400, description = "Invalid loan terms or payments", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "The loan cannot be calculated", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn reconcile_payment_history(
+    request: web::Json<PaymentHistoryRequest>
+) -> Result<HttpResponse, ApiError> {
+    let request_data = request.into_inner();
+    validate_request(&request_data)?;
+    validate_loan(&request_data.input).map_err(|e| e.at("input"))?;
+
+    let history = PaymentHistoryReconciler::reconcile(&request_data)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(history))
+}
+
+/// Report a tax year's mortgage interest in the shape of Form 1098
+#[utoipa::path(
+    post,
+    path = "/api/v1/interest-statement",
+    tag = "servicing",
+    request_body = InterestStatementRequest,
+    responses(
+        (status = 200, description = "Year-end interest statement", body = InterestStatement),
+        (status = 400, description = "Invalid loan terms, tax year or payments", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "The loan cannot be calculated", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn generate_interest_statement(
+    request: web::Json<InterestStatementRequest>
+) -> Result<HttpResponse, ApiError> {
+    let request_data = request.into_inner();
+    validate_request(&request_data)?;
+    validate_loan(&request_data.input).map_err(|e| e.at("input"))?;
+
+    let statement = InterestStatementCalculator::statement(&request_data)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(statement))
+}
+
+/// Project the cash flows of a pool of loans under prepayment and default assumptions
+#[utoipa::path(
+    post,
+    path = "/api/v1/pool",
+    tag = "investors",
+    request_body = PoolRequest,
+    responses(
+        (status = 200, description = "Monthly pool cash flows and their totals", body = PoolProjection),
+        (status = 400, description = "Invalid loans or assumptions", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "A loan in the pool cannot be calculated", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn project_pool(
+    request: web::Json<PoolRequest>
+) -> Result<HttpResponse, ApiError> {
+    let request_data = request.into_inner();
+    validate_request(&request_data)?;
+    for (index, loan) in request_data.loans.iter().enumerate() {
+        validate_loan(loan).map_err(|e| e.at(&format!("loans[{}]", index)))?;
+    }
+
+    let projection = PoolCalculator::project(&request_data)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(projection))
+}
+
+/// Loans calculated together before their results are streamed back
+const BATCH_CHUNK_SIZE: usize = 256;
+
+/// Calculate many loans in parallel, streaming an NDJSON result line back for each
+///
+/// The whole body is read into memory before the first loan is calculated, so
+/// `limits.batch_bytes` (64 MiB by default) bounds the memory a request can take.
+#[utoipa::path(
+    post,
+    path = "/api/v1/batch",
+    tag = "mortgages",
+    params(BatchOptions),
+    request_body(
+        content = String,
+        content_type = "application/x-ndjson",
+        description = "A JSON array or NDJSON stream of loans ([MortgageInput]). The body is buffered in full, \
+            so it may be at most `limits.batch_bytes` long (64 MiB by default)"
+    ),
+    responses(
+        (status = 200, description = "One result per loan, in request order; loans that fail carry an error instead. \
+            A final line with only an `error` means the remaining results could not be written",
+            body = BatchItemResult, content_type = "application/x-ndjson"),
+        (status = 400, description = "The body is neither a JSON array nor NDJSON", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 413, description = "The body exceeds `limits.batch_bytes`"),
+    )
+)]
+pub async fn calculate_batch(
+    body: web::Bytes,
+    options: web::Query<BatchOptions>
+) -> Result<HttpResponse, ApiError> {
+    let items = BatchCalculator::parse(&body)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let output = options.output;
+
+    // A small channel keeps a slow client from buffering the whole batch in memory
+    let (sender, receiver) = mpsc::channel(4);
+    actix_web::rt::task::spawn_blocking(move || {
+        for (chunk, slice) in items.chunks(BATCH_CHUNK_SIZE).enumerate() {
+            let results = BatchCalculator::calculate_parallel(chunk * BATCH_CHUNK_SIZE, slice, output);
+            let (lines, complete) = ndjson_lines(&results);
+            if sender.blocking_send(web::Bytes::from(lines)).is_err() || !complete {
+                // The client has gone away, or the stream already ended with an error line
+                break;
+            }
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .body(NdjsonBody(receiver)))
+}
+
+/// Price a loan's cash flows to a yield, or a yield to a price, with duration and convexity
+#[utoipa::path(
+    post,
+    path = "/api/v1/analytics",
+    tag = "investors",
+    request_body = AnalyticsRequest,
+    responses(
+        (status = 200, description = "Price, yield and risk measures of the loan", body = LoanAnalytics),
+        (status = 400, description = "Invalid loan terms or pricing", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "The loan cannot be calculated or priced", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn analyze_loan(
+    request: web::Json<AnalyticsRequest>
+) -> Result<HttpResponse, ApiError> {
+    let request_data = request.into_inner();
+    validate_request(&request_data)?;
+    validate_loan(&request_data.input).map_err(|e| e.at("input"))?;
+
+    let analytics = AnalyticsCalculator::analyze(&request_data)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(analytics))
+}
+
+/// Discount a loan's cash flows along a flat rate or bootstrapped par curve
+#[utoipa::path(
+    post,
+    path = "/api/v1/present-value",
+    tag = "investors",
+    request_body = ValuationRequest,
+    responses(
+        (status = 200, description = "Present value of the loan's cash flows", body = PresentValue),
+        (status = 400, description = "Invalid loan terms or curve", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "The loan cannot be calculated or the curve bootstrapped", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn value_loan(
+    request: web::Json<ValuationRequest>
+) -> Result<HttpResponse, ApiError> {
+    let request_data = request.into_inner();
+    validate_request(&request_data)?;
+    validate_loan(&request_data.input).map_err(|e| e.at("input"))?;
+
+    let value = ValuationCalculator::value(&request_data)
+        .map_err(|e| ApiError::CalculationError(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(value))
+}
+
+/// Validates a loan against the rules of its repayment type
+fn validate_loan(input: &MortgageInput) -> Result<(), ApiError> {
+    validate_request_with(input, |input| input.validate_for(input.repayment_type))
+}
+
+/// One NDJSON line per result, and whether every result could be written
+///
+/// The status is sent before the first result, so a result that fails to
+/// serialize ends the stream with an `{"error": ...}` line instead.
+fn ndjson_lines(results: &[BatchItemResult]) -> (Vec<u8>, bool) {
+    let mut lines = Vec::new();
+    for result in results {
+        let start = lines.len();
+        if let Err(error) = serde_json::to_writer(&mut lines, result) {
+            lines.truncate(start);
+            let message = format!("result {} could not be serialized: {}", result.index, error);
+            lines.extend_from_slice(serde_json::json!({ "error": message }).to_string().as_bytes());
+            lines.push(b'\n');
+            return (lines, false);
+        }
+        lines.push(b'\n');
+    }
+    (lines, true)
+}
+
+/// Response body streaming NDJSON lines as the batch produces them
+struct NdjsonBody(mpsc::Receiver<web::Bytes>);
+
+impl MessageBody for NdjsonBody {
+    type Error = Infallible;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
 
-    Ok(HttpResponse::Ok().json(summary))
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<web::Bytes, Self::Error>>> {
+        self.get_mut().0.poll_recv(cx).map(|lines| lines.map(Ok))
+    }
 }
 
+/// List the supported repayment types
+#[utoipa::path(
+    get,
+    path = "/api/v1/repayment-types",
+    tag = "information",
+    responses(
+        (status = 200, description = "Supported repayment types", body = [RepaymentTypeInfo]),
+    )
+)]
 pub async fn get_repayment_types() -> Result<HttpResponse, ApiError> {
     Ok(HttpResponse::Ok().json(RepaymentTypeInfo::all()))
 }
 
+/// Report that the service is up
+#[utoipa::path(
+    get,
+    path = "/api/v1/health",
+    tag = "information",
+    responses(
+        (status = 200, description = "Service is healthy"),
+    )
+)]
 pub async fn health_check() -> HttpResponse {
     HttpResponse::Ok().json(serde_json::json!({
         "status": "healthy",
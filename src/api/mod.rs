@@ -6,9 +6,11 @@
 mod handlers;
 mod routes;
 mod errors;
+mod docs;
 
-pub use routes::configure_routes;
-pub use errors::ApiError;
+pub use routes::{configure_routes, configure_routes_with};
+pub use errors::{ApiError, FieldError, ProblemDetails, PROBLEM_JSON};
+pub use docs::ApiDoc;
 
 // Re-export handlers if needed for testing
 #[cfg(test)]
@@ -1,10 +1,21 @@
 use actix_web::web;
-use super::handlers;
+use super::{docs, errors, handlers};
+use crate::config::ServerConfig;
 
-/// Configure all routes for the mortgage calculator API
+/// Configure all routes for the mortgage calculator API with the default configuration
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
-    cfg.service(
-        web::scope("/api/v1")
+    configure_routes_with(cfg, &ServerConfig::default());
+}
+
+/// Configure the routes enabled in `config`
+pub fn configure_routes_with(cfg: &mut web::ServiceConfig, config: &ServerConfig) {
+    // Bodies and query strings that fail to deserialize are reported as problem details too
+    cfg.app_data(web::JsonConfig::default()
+            .limit(config.limits.json_bytes)
+            .error_handler(errors::json_error))
+        .app_data(web::QueryConfig::default().error_handler(errors::query_error));
+
+    let mut api = web::scope("/api/v1")
             // Mortgage calculation endpoints
             .service(
                 web::resource("/calculate")
@@ -14,6 +25,50 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                 web::resource("/calculate/summary")
                     .route(web::post().to(handlers::calculate_mortgage_summary))
             )
+            .service(
+                web::resource("/affordability")
+                    .route(web::post().to(handlers::calculate_affordability))
+            )
+            .service(
+                web::resource("/solve")
+                    .route(web::post().to(handlers::solve))
+            )
+            .service(
+                web::resource("/refinance")
+                    .route(web::post().to(handlers::analyze_refinance))
+            )
+            .service(
+                web::resource("/compare")
+                    .route(web::post().to(handlers::compare_mortgages))
+            )
+            .service(
+                web::resource("/rent-vs-buy")
+                    .route(web::post().to(handlers::analyze_rent_vs_buy))
+            )
+            .service(
+                web::resource("/payoff")
+                    .route(web::post().to(handlers::quote_payoff))
+            )
+            .service(
+                web::resource("/payment-history")
+                    .route(web::post().to(handlers::reconcile_payment_history))
+            )
+            .service(
+                web::resource("/interest-statement")
+                    .route(web::post().to(handlers::generate_interest_statement))
+            )
+            .service(
+                web::resource("/pool")
+                    .route(web::post().to(handlers::project_pool))
+            )
+            .service(
+                web::resource("/analytics")
+                    .route(web::post().to(handlers::analyze_loan))
+            )
+            .service(
+                web::resource("/present-value")
+                    .route(web::post().to(handlers::value_loan))
+            )
             // Information endpoints
             .service(
                 web::resource("/repayment-types")
@@ -23,6 +78,25 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
             .service(
                 web::resource("/health")
                     .route(web::get().to(handlers::health_check))
-            )
-    );
+            );
+
+    if config.features.batch {
+        api = api.service(
+            web::resource("/batch")
+                .app_data(web::PayloadConfig::new(config.limits.batch_bytes))
+                .route(web::post().to(handlers::calculate_batch))
+        );
+    }
+    cfg.service(api);
+
+    // Interactive documentation and the OpenAPI spec behind it
+    if config.features.docs {
+        cfg.service(
+            web::scope("/api/docs")
+                .app_data(web::Data::new(config.docs.clone()))
+                .route("", web::get().to(docs::swagger_ui))
+                .route("/", web::get().to(docs::swagger_ui))
+                .route("/openapi.json", web::get().to(docs::openapi_json))
+        );
+    }
 }
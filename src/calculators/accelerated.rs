@@ -1,69 +1,74 @@
-use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use chrono::Duration;
 
-use crate::models::{MortgageInput, MortgageSchedule, PaymentScheduleEntry, MortgageSummary, RepaymentType};
-use crate::utils::DecimalUtils;
-use super::MortgageCalculator;
+use crate::models::{MortgageInput, MortgageSchedule, MortgageSummary, RepaymentType};
+use super::amortization::{is_plain_amortization, Amortization};
+use super::{
+    check_input, payment_factor, CalculationError, MortgageCalculator, ScheduleEntries,
+    ModificationCalculator, EscrowCalculator, MortgageInsuranceCalculator, PrepaymentPenaltyCalculator,
+};
 
 pub struct AcceleratedCalculator;
 
 impl MortgageCalculator for AcceleratedCalculator {
-    fn calculate_schedule(input: &MortgageInput) -> MortgageSchedule {
-        let monthly_rate = input.annual_interest_rate / dec!(12);
-        let biweekly_rate = monthly_rate / dec!(2);
-        let num_payments = input.term_years * 26;
-        
-        let rate_factor = DecimalUtils::power(dec!(1) + monthly_rate, (input.term_years * 12) as i64);
-        let monthly_equivalent = input.principal * 
-            (monthly_rate * rate_factor) /
-            (rate_factor - dec!(1));
-        let biweekly_payment = monthly_equivalent / dec!(2);
+    fn repayment_type(&self) -> RepaymentType {
+        RepaymentType::AcceleratedBiweekly
+    }
 
-        let mut schedule = Vec::with_capacity(num_payments as usize);
-        let mut remaining_principal = input.principal;
-        let mut total_interest = Decimal::ZERO;
+    fn description(&self) -> &str {
+        "Payments every two weeks, resulting in one extra monthly payment per year."
+    }
 
-        for payment_number in 1..=num_payments {
-            let interest_component = remaining_principal * biweekly_rate;
-            let principal_component = biweekly_payment - interest_component;
-            
-            total_interest += interest_component;
-            remaining_principal -= principal_component;
+    fn calculate_schedule(&self, input: &MortgageInput) -> Result<MortgageSchedule, CalculationError> {
+        let amortization = Self::amortization(input)?;
+        let monthly_payment = amortization.scheduled_payment() * dec!(2);
+        let mut schedule = amortization.into_schedule(monthly_payment);
 
-            let payment_date = input.start_date + 
-                Duration::days(((payment_number - 1) * 14) as i64);
+        ModificationCalculator::apply(input, &mut schedule)?;
+        EscrowCalculator::apply(input, &mut schedule)?;
+        MortgageInsuranceCalculator::apply(input, &mut schedule)?;
+        PrepaymentPenaltyCalculator::apply(input, &mut schedule)?;
+        Ok(schedule)
+    }
 
-            schedule.push(PaymentScheduleEntry {
-                payment_date,
-                payment_number,
-                payment_amount: biweekly_payment,
-                principal_component,
-                interest_component,
-                remaining_principal,
-                current_rate: Some(input.annual_interest_rate),
-            });
+    fn schedule_entries(&self, input: &MortgageInput) -> Result<ScheduleEntries, CalculationError> {
+        if !is_plain_amortization(input) {
+            return Ok(Box::new(self.calculate_schedule(input)?.schedule.into_iter()));
         }
+        Ok(Box::new(Self::amortization(input)?))
+    }
 
-        MortgageSchedule {
-            monthly_payment: biweekly_payment * dec!(2),
-            total_payments: biweekly_payment * Decimal::from(num_payments),
-            total_interest,
-            schedule,
+    fn calculate_summary(&self, input: &MortgageInput) -> Result<MortgageSummary, CalculationError> {
+        if !is_plain_amortization(input) {
+            return Ok(self.summarize(input, &self.calculate_schedule(input)?));
         }
-    }
+        let amortization = Self::amortization(input)?;
+        let monthly_payment = amortization.scheduled_payment() * dec!(2);
+        let totals = amortization.totals()?;
 
-    fn calculate_summary(input: &MortgageInput) -> MortgageSummary {
-        let schedule = Self::calculate_schedule(input);
-        
-        MortgageSummary::new(
-            RepaymentType::AcceleratedBiweekly,
-            schedule.monthly_payment,
-            schedule.total_payments,
-            schedule.total_interest,
+        Ok(MortgageSummary::new(
+            self.repayment_type(),
+            monthly_payment,
+            totals.total_payments,
+            totals.total_interest,
             input.principal,
             input.annual_interest_rate,
-            input.term_years * 26,
-        )
+            totals.payment_count,
+        ))
+    }
+}
+
+impl AcceleratedCalculator {
+    fn amortization(input: &MortgageInput) -> Result<Amortization, CalculationError> {
+        check_input(input)?;
+        let monthly_rate = input.annual_interest_rate / dec!(12);
+        let biweekly_rate = monthly_rate / dec!(2);
+        let num_payments = input.term_years * 26;
+
+        // Half the monthly payment every two weeks adds a thirteenth monthly payment each year
+        let monthly_equivalent = input.principal *
+            payment_factor(monthly_rate, input.term_years * 12)?;
+        let biweekly_payment = monthly_equivalent / dec!(2);
+
+        Ok(Amortization::new(input, biweekly_rate, num_payments, 14).with_level_payment(biweekly_payment))
     }
 }
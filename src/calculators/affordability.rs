@@ -0,0 +1,131 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::models::{AffordabilityInput, AffordabilityResult, LimitingRatio};
+use crate::utils::DecimalUtils;
+use super::{payment_factor, CalculationError};
+
+/// Solves the payment formula backwards for the largest affordable loan
+pub struct AffordabilityCalculator;
+
+impl AffordabilityCalculator {
+    pub fn calculate(input: &AffordabilityInput) -> Result<AffordabilityResult, CalculationError> {
+        let monthly_income = input.gross_annual_income / dec!(12);
+        let front_end_payment = monthly_income * input.front_end_dti_limit;
+        let back_end_payment = monthly_income * input.back_end_dti_limit - input.monthly_debts;
+
+        let (max_housing_payment, limiting_ratio) = if back_end_payment < front_end_payment {
+            (back_end_payment.max(Decimal::ZERO), LimitingRatio::BackEnd)
+        } else {
+            (front_end_payment, LimitingRatio::FrontEnd)
+        };
+
+        let monthly_rate = DecimalUtils::annual_to_monthly_rate(input.annual_interest_rate);
+        let payment_factor = payment_factor(monthly_rate, input.term_years * 12)?;
+        let monthly_tax_rate = input.property_tax_rate / dec!(12);
+        let monthly_insurance = input.annual_insurance / dec!(12);
+
+        // housing = loan * factor + (loan + down payment) * tax rate + insurance + HOA
+        let available_for_loan = max_housing_payment
+            - monthly_insurance
+            - input.monthly_hoa
+            - input.down_payment * monthly_tax_rate;
+        let max_loan_amount = DecimalUtils::round_currency(
+            (available_for_loan / (payment_factor + monthly_tax_rate)).max(Decimal::ZERO)
+        );
+        let max_purchase_price = max_loan_amount + input.down_payment;
+
+        let monthly_principal_and_interest = DecimalUtils::round_currency(max_loan_amount * payment_factor);
+        let monthly_property_tax = DecimalUtils::round_currency(max_purchase_price * monthly_tax_rate);
+        let housing_payment = monthly_principal_and_interest
+            + monthly_property_tax
+            + DecimalUtils::round_currency(monthly_insurance)
+            + input.monthly_hoa;
+
+        Ok(AffordabilityResult {
+            max_purchase_price,
+            max_loan_amount,
+            max_housing_payment: DecimalUtils::round_currency(max_housing_payment),
+            monthly_principal_and_interest,
+            monthly_property_tax,
+            monthly_insurance: DecimalUtils::round_currency(monthly_insurance),
+            monthly_hoa: input.monthly_hoa,
+            front_end_ratio: (housing_payment / monthly_income).round_dp(4),
+            back_end_ratio: ((housing_payment + input.monthly_debts) / monthly_income).round_dp(4),
+            limiting_ratio,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_input() -> AffordabilityInput {
+        AffordabilityInput {
+            gross_annual_income: dec!(120000),
+            monthly_debts: dec!(500),
+            front_end_dti_limit: dec!(0.28),
+            back_end_dti_limit: dec!(0.36),
+            annual_interest_rate: dec!(0.06),
+            term_years: 30,
+            property_tax_rate: dec!(0.012),
+            annual_insurance: dec!(1200),
+            monthly_hoa: dec!(0),
+            down_payment: dec!(60000),
+        }
+    }
+
+    #[test]
+    fn test_front_end_limited() {
+        let result = AffordabilityCalculator::calculate(&sample_input()).unwrap();
+
+        assert_eq!(result.limiting_ratio, LimitingRatio::FrontEnd);
+        assert_eq!(result.max_housing_payment, dec!(2800));
+        assert!((result.front_end_ratio - dec!(0.28)).abs() <= dec!(0.0001));
+        assert_eq!(result.max_purchase_price, result.max_loan_amount + dec!(60000));
+    }
+
+    #[test]
+    fn test_back_end_limited() {
+        let mut input = sample_input();
+        input.monthly_debts = dec!(1500);
+        let result = AffordabilityCalculator::calculate(&input).unwrap();
+
+        assert_eq!(result.limiting_ratio, LimitingRatio::BackEnd);
+        assert_eq!(result.max_housing_payment, dec!(2100));
+        assert!((result.back_end_ratio - dec!(0.36)).abs() <= dec!(0.0001));
+    }
+
+    #[test]
+    fn test_debts_exceeding_limit_yield_zero_loan() {
+        let mut input = sample_input();
+        input.monthly_debts = dec!(5000);
+        let result = AffordabilityCalculator::calculate(&input).unwrap();
+
+        assert_eq!(result.max_loan_amount, Decimal::ZERO);
+        assert_eq!(result.max_purchase_price, dec!(60000));
+    }
+
+    #[test]
+    fn test_zero_rate() {
+        let mut input = sample_input();
+        input.annual_interest_rate = Decimal::ZERO;
+        input.property_tax_rate = Decimal::ZERO;
+        input.annual_insurance = Decimal::ZERO;
+        let result = AffordabilityCalculator::calculate(&input).unwrap();
+
+        assert_eq!(result.max_loan_amount, dec!(1008000));
+    }
+
+    #[test]
+    fn test_extreme_rate_and_term_do_not_panic() {
+        let mut input = sample_input();
+        input.annual_interest_rate = dec!(1);
+        input.term_years = 50;
+        let result = AffordabilityCalculator::calculate(&input).unwrap();
+
+        assert!(result.max_loan_amount > Decimal::ZERO);
+        assert_eq!(result.limiting_ratio, LimitingRatio::FrontEnd);
+    }
+}
@@ -0,0 +1,241 @@
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+
+use crate::models::{MortgageInput, MortgageSchedule, PaymentScheduleEntry};
+use crate::utils::DecimalUtils;
+use super::CalculationError;
+
+/// Level-payment amortization yielding one schedule entry at a time
+///
+/// Built from inputs that passed `check_input`, so every payment date is representable.
+#[derive(Debug, Clone)]
+pub(crate) struct Amortization {
+    start_date: NaiveDate,
+    interval_days: i64,
+    annual_rate: Decimal,
+    periodic_rate: Decimal,
+    principal: Decimal,
+    /// Scheduled payment before extra principal; `None` pays interest only
+    level_payment: Option<Decimal>,
+    extra_payment: Decimal,
+    num_payments: u32,
+    /// Whether the last payment clears whatever balance remains
+    balance_due_at_maturity: bool,
+    payment_number: u32,
+    remaining_principal: Decimal,
+}
+
+/// Running totals over amortized payments
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct AmortizationTotals {
+    pub total_payments: Decimal,
+    pub total_interest: Decimal,
+    pub payment_count: u32,
+    pub final_payment: Decimal,
+}
+
+impl Amortization {
+    /// Interest-only amortization of the input's principal, paying extra principal each period
+    pub fn new(input: &MortgageInput, periodic_rate: Decimal, num_payments: u32, interval_days: i64) -> Self {
+        Self {
+            start_date: input.start_date,
+            interval_days,
+            annual_rate: input.annual_interest_rate,
+            periodic_rate,
+            principal: input.principal,
+            level_payment: None,
+            extra_payment: input.extra_payment,
+            num_payments,
+            balance_due_at_maturity: false,
+            payment_number: 0,
+            remaining_principal: input.principal,
+        }
+    }
+
+    /// Pays down principal with a level payment of principal and interest
+    pub fn with_level_payment(mut self, payment: Decimal) -> Self {
+        self.level_payment = Some(payment);
+        self
+    }
+
+    /// Clears the remaining balance with the final payment
+    pub fn with_balance_due_at_maturity(mut self) -> Self {
+        self.balance_due_at_maturity = true;
+        self
+    }
+
+    /// Regular payment before extra principal
+    pub fn scheduled_payment(&self) -> Decimal {
+        self.level_payment.unwrap_or(self.principal * self.periodic_rate)
+    }
+
+    /// Collects the payments into a schedule without escrow, insurance or modifications
+    pub fn into_schedule(self, monthly_payment: Decimal) -> MortgageSchedule {
+        let mut schedule = MortgageSchedule::new(monthly_payment, Decimal::ZERO, Decimal::ZERO);
+        schedule.schedule.reserve(self.num_payments as usize);
+        for entry in self {
+            schedule.total_interest += entry.interest_component;
+            schedule.total_payments += entry.payment_amount;
+            schedule.add_payment(entry);
+        }
+        schedule
+    }
+
+    /// Totals over every payment, in closed form when no extra principal is paid
+    pub fn totals(self) -> Result<AmortizationTotals, CalculationError> {
+        match self.closed_form_totals()? {
+            Some(totals) => Ok(totals),
+            None => Ok(self.fold(AmortizationTotals::default(), |totals, entry| AmortizationTotals {
+                total_payments: totals.total_payments + entry.payment_amount,
+                total_interest: totals.total_interest + entry.interest_component,
+                payment_count: totals.payment_count + 1,
+                final_payment: entry.payment_amount,
+            })),
+        }
+    }
+
+    /// Totals without iterating, where every payment but the last is the scheduled one
+    fn closed_form_totals(&self) -> Result<Option<AmortizationTotals>, CalculationError> {
+        if !self.extra_payment.is_zero() || self.payment_number > 0 || self.num_payments == 0 {
+            return Ok(None);
+        }
+        let payments = Decimal::from(self.num_payments);
+        let interest_only = self.principal * self.periodic_rate;
+
+        let final_payment = match self.level_payment {
+            None => interest_only + self.principal,
+            Some(payment) if self.balance_due_at_maturity => {
+                // Balance after all but the last payment, which also pays its interest
+                let growth = DecimalUtils::checked_power(Decimal::ONE + self.periodic_rate, i64::from(self.num_payments) - 1)
+                    .ok_or(CalculationError::Overflow("payment factor"))?;
+                let paid = if self.periodic_rate.is_zero() {
+                    payment * (payments - Decimal::ONE)
+                } else {
+                    payment * (growth - Decimal::ONE) / self.periodic_rate
+                };
+                (self.principal * growth - paid) * (Decimal::ONE + self.periodic_rate)
+            }
+            // Only a payment amortizing exactly over the term runs the full term
+            Some(payment) => {
+                let factor = DecimalUtils::checked_payment_factor(self.periodic_rate, self.num_payments)
+                    .ok_or(CalculationError::Overflow("payment factor"))?;
+                if payment != self.principal * factor {
+                    return Ok(None);
+                }
+                payment
+            }
+        };
+
+        let total_payments = self.scheduled_payment() * (payments - Decimal::ONE) + final_payment;
+        Ok(Some(AmortizationTotals {
+            total_payments,
+            total_interest: total_payments - self.principal,
+            payment_count: self.num_payments,
+            final_payment,
+        }))
+    }
+}
+
+impl Iterator for Amortization {
+    type Item = PaymentScheduleEntry;
+
+    fn next(&mut self) -> Option<PaymentScheduleEntry> {
+        if self.payment_number >= self.num_payments || self.remaining_principal <= Decimal::ZERO {
+            return None;
+        }
+        self.payment_number += 1;
+
+        let interest_component = self.remaining_principal * self.periodic_rate;
+        let principal_component = if self.balance_due_at_maturity && self.payment_number == self.num_payments {
+            self.remaining_principal
+        } else {
+            (self.level_payment.map_or(Decimal::ZERO, |payment| payment - interest_component) + self.extra_payment)
+                .min(self.remaining_principal)
+        };
+        self.remaining_principal -= principal_component;
+
+        Some(PaymentScheduleEntry {
+            payment_date: self.start_date + Duration::days(i64::from(self.payment_number - 1) * self.interval_days),
+            payment_number: self.payment_number,
+            payment_amount: principal_component + interest_component,
+            principal_component,
+            interest_component,
+            remaining_principal: self.remaining_principal,
+            current_rate: Some(self.annual_rate),
+            escrow_deposit: None,
+            escrow_balance: None,
+            mortgage_insurance: None,
+            prepayment_penalty: None,
+            deferred_balance: None,
+            modifications: Vec::new(),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.num_payments.saturating_sub(self.payment_number) as usize))
+    }
+}
+
+/// Whether a schedule is plain amortization, with nothing layered on by other calculators
+pub(crate) fn is_plain_amortization(input: &MortgageInput) -> bool {
+    input.modifications.is_empty()
+        && input.escrow.is_none()
+        && input.mortgage_insurance.is_none()
+        && input.prepayment_penalty.is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+    use crate::models::RepaymentType;
+
+    fn loan(extra_payment: Decimal) -> MortgageInput {
+        MortgageInput {
+            extra_payment,
+            ..MortgageInput::new(
+                dec!(250000),
+                dec!(0.06),
+                30,
+                RepaymentType::StandardPrincipalAndInterest,
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            )
+        }
+    }
+
+    fn amortizations(input: &MortgageInput) -> Vec<Amortization> {
+        let rate = input.annual_interest_rate / dec!(12);
+        let payment = input.principal * DecimalUtils::checked_payment_factor(rate, 360).unwrap();
+        let balloon_payment = payment / dec!(2);
+        vec![
+            Amortization::new(input, rate, 360, 30).with_balance_due_at_maturity(),
+            Amortization::new(input, rate, 360, 30).with_level_payment(payment),
+            Amortization::new(input, rate, 360, 30).with_level_payment(balloon_payment).with_balance_due_at_maturity(),
+        ]
+    }
+
+    #[test]
+    fn test_closed_form_totals_match_iterated_schedule() {
+        for amortization in amortizations(&loan(dec!(0))) {
+            let schedule = amortization.clone().into_schedule(amortization.scheduled_payment());
+            let totals = amortization.totals().unwrap();
+
+            assert_eq!(totals.payment_count as usize, schedule.payment_count());
+            assert!((totals.total_payments - schedule.total_payments).abs() < dec!(0.000001));
+            assert!((totals.total_interest - schedule.total_interest).abs() < dec!(0.000001));
+        }
+    }
+
+    #[test]
+    fn test_extra_payments_are_totalled_exactly() {
+        for amortization in amortizations(&loan(dec!(150))) {
+            let schedule = amortization.clone().into_schedule(amortization.scheduled_payment());
+            let totals = amortization.totals().unwrap();
+
+            assert_eq!(totals.payment_count as usize, schedule.payment_count());
+            assert_eq!(totals.total_payments, schedule.total_payments);
+            assert_eq!(totals.total_interest, schedule.total_interest);
+        }
+    }
+}
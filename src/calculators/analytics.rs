@@ -0,0 +1,198 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use thiserror::Error;
+
+use crate::models::{AnalyticsRequest, LoanAnalytics, LoanPricing, MortgageSchedule};
+use crate::utils::DecimalUtils;
+use super::{calculate_schedule, CalculationError};
+
+/// Upper bound on Newton iterations when solving for yield
+const MAX_ITERATIONS: u32 = 100;
+
+/// Yields are solved until the price is within a millionth of a cent
+const PRICE_TOLERANCE: Decimal = dec!(0.00000001);
+
+/// Errors raised while valuing a loan
+#[derive(Debug, Error, PartialEq)]
+pub enum AnalyticsError {
+    #[error("the schedule has no cash flows to value")]
+    NoCashFlows,
+
+    #[error("no yield reproduces a price of {price}")]
+    YieldNotFound { price: Decimal },
+
+    #[error(transparent)]
+    Calculation(#[from] CalculationError),
+}
+
+/// Values a loan's scheduled cash flows and measures their rate sensitivity
+pub struct AnalyticsCalculator;
+
+impl AnalyticsCalculator {
+    pub fn analyze(request: &AnalyticsRequest) -> Result<LoanAnalytics, AnalyticsError> {
+        let schedule = calculate_schedule(&request.input)?;
+        Self::analyze_schedule(&schedule, request.input.repayment_type.payments_per_year(), request.pricing)
+    }
+
+    /// Analytics for the principal and interest cash flows of `schedule`
+    ///
+    /// Cash flows fall at whole payment periods from the start of the schedule,
+    /// and prices are quoted against its original principal.
+    pub fn analyze_schedule(
+        schedule: &MortgageSchedule,
+        payments_per_year: u32,
+        pricing: LoanPricing,
+    ) -> Result<LoanAnalytics, AnalyticsError> {
+        let first = schedule.schedule.first().ok_or(AnalyticsError::NoCashFlows)?;
+        let par = first.remaining_principal + first.principal_component;
+        let cash_flows: Vec<Decimal> = schedule.schedule.iter().map(|entry| entry.payment_amount).collect();
+        if par <= Decimal::ZERO || cash_flows.iter().all(|amount| amount.is_zero()) {
+            return Err(AnalyticsError::NoCashFlows);
+        }
+        let periods_per_year = Decimal::from(payments_per_year);
+
+        let periodic_yield = match pricing {
+            LoanPricing::Yield { annual_yield } => annual_yield / periods_per_year,
+            LoanPricing::Price { price } => {
+                let target = par * price / dec!(100);
+                Self::solve_yield(&cash_flows, target)
+                    .ok_or(AnalyticsError::YieldNotFound { price })?
+            }
+        };
+
+        let discount = dec!(1) / (dec!(1) + periodic_yield);
+        let mut factor = dec!(1);
+        let (mut present_value, mut weighted_time, mut weighted_convexity) = (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO);
+        for (period, amount) in (1u32..).zip(&cash_flows) {
+            factor *= discount;
+            let value = amount * factor;
+            let period = Decimal::from(period);
+            present_value += value;
+            weighted_time += period * value;
+            weighted_convexity += period * (period + dec!(1)) * value;
+        }
+
+        let macaulay_periods = weighted_time / present_value;
+        let macaulay_duration = macaulay_periods / periods_per_year;
+        let modified_duration = macaulay_duration / (dec!(1) + periodic_yield);
+        let convexity = weighted_convexity * discount * discount / present_value / (periods_per_year * periods_per_year);
+
+        // Principal includes any deferred balance repaid and interest capitalized along the way
+        let (principal_total, principal_time) = (1u32..).zip(&schedule.schedule)
+            .fold((Decimal::ZERO, Decimal::ZERO), |(total, time), (period, entry)| {
+                let principal = entry.payment_amount - entry.interest_component;
+                (total + principal, time + Decimal::from(period) * principal)
+            });
+        let weighted_average_life = principal_time / principal_total / periods_per_year;
+
+        Ok(LoanAnalytics {
+            price: (present_value / par * dec!(100)).round_dp(6),
+            dollar_price: DecimalUtils::round_currency(present_value),
+            yield_to_maturity: (periodic_yield * periods_per_year).round_dp(8),
+            macaulay_duration: macaulay_duration.round_dp(6),
+            modified_duration: modified_duration.round_dp(6),
+            convexity: convexity.round_dp(6),
+            weighted_average_life: weighted_average_life.round_dp(6),
+            dv01: DecimalUtils::round_currency(modified_duration * present_value * dec!(0.0001)),
+        })
+    }
+
+    /// Periodic yield discounting `cash_flows` to `target`, by Newton's method
+    fn solve_yield(cash_flows: &[Decimal], target: Decimal) -> Option<Decimal> {
+        let total: Decimal = cash_flows.iter().sum();
+        // Start from the yield that would return the undiscounted gain evenly over the life
+        let mut rate = ((total - target) / target / Decimal::from(cash_flows.len() as u32)).max(dec!(-0.5));
+
+        for _ in 0..MAX_ITERATIONS {
+            let discount = dec!(1) / (dec!(1) + rate);
+            let mut factor = dec!(1);
+            let (mut value, mut slope) = (Decimal::ZERO, Decimal::ZERO);
+            for (period, amount) in (1u32..).zip(cash_flows) {
+                factor *= discount;
+                value += amount * factor;
+                slope -= Decimal::from(period) * amount * factor * discount;
+            }
+
+            let error = value - target;
+            if error.abs() <= PRICE_TOLERANCE {
+                return Some(rate);
+            }
+            if slope.is_zero() {
+                return None;
+            }
+            rate = (rate - error / slope).max(dec!(-0.99));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::models::{MortgageInput, RepaymentType};
+
+    fn loan(repayment_type: RepaymentType) -> MortgageInput {
+        MortgageInput::new(
+            dec!(200000),
+            dec!(0.06),
+            30,
+            repayment_type,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        )
+    }
+
+    fn analyze(pricing: LoanPricing) -> LoanAnalytics {
+        AnalyticsCalculator::analyze(&AnalyticsRequest {
+            input: loan(RepaymentType::StandardPrincipalAndInterest),
+            pricing,
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_note_rate_prices_at_par() {
+        let at_note_rate = analyze(LoanPricing::Yield { annual_yield: dec!(0.06) });
+        let at_par = analyze(LoanPricing::Price { price: dec!(100) });
+
+        assert_eq!(at_note_rate.price.round_dp(4), dec!(100));
+        assert_eq!(at_note_rate.dollar_price, dec!(200000));
+        assert_eq!(at_par.yield_to_maturity.round_dp(6), dec!(0.06));
+    }
+
+    #[test]
+    fn test_premium_yields_less_than_note_rate() {
+        let premium = analyze(LoanPricing::Price { price: dec!(103) });
+        let discount = analyze(LoanPricing::Price { price: dec!(97) });
+
+        assert!(premium.yield_to_maturity < dec!(0.06));
+        assert!(discount.yield_to_maturity > dec!(0.06));
+        assert_eq!(premium.price, dec!(103));
+    }
+
+    #[test]
+    fn test_duration_measures() {
+        let analytics = analyze(LoanPricing::Yield { annual_yield: dec!(0.06) });
+
+        // A 30-year level-pay loan at 6% returns principal on average after about 19 years
+        assert!(analytics.weighted_average_life > dec!(19) && analytics.weighted_average_life < dec!(20));
+        assert!(analytics.macaulay_duration < analytics.weighted_average_life);
+        assert!(analytics.modified_duration < analytics.macaulay_duration);
+        assert!(analytics.convexity > analytics.modified_duration * analytics.modified_duration);
+        assert_eq!(analytics.dv01, DecimalUtils::round_currency(analytics.modified_duration * dec!(20)));
+
+        // Duration predicts the price move for a small change in yield
+        let bumped = analyze(LoanPricing::Yield { annual_yield: dec!(0.0601) });
+        assert!((analytics.dollar_price - bumped.dollar_price - analytics.dv01).abs() < dec!(0.5));
+    }
+
+    #[test]
+    fn test_interest_only_life_is_the_term() {
+        let analytics = AnalyticsCalculator::analyze(&AnalyticsRequest {
+            input: loan(RepaymentType::InterestOnly),
+            pricing: LoanPricing::Price { price: dec!(100) },
+        }).unwrap();
+
+        assert_eq!(analytics.weighted_average_life, dec!(30));
+    }
+}
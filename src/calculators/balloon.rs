@@ -1,75 +1,48 @@
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use chrono::Duration;
 
-use crate::models::{MortgageInput, MortgageSchedule, PaymentScheduleEntry, MortgageSummary, RepaymentType};
+use crate::models::{MortgageInput, MortgageSchedule, MortgageSummary, RepaymentType};
 use crate::utils::DecimalUtils;
-use super::MortgageCalculator;
+use super::amortization::{is_plain_amortization, Amortization};
+use super::{
+    check_input, payment_factor, CalculationError, MortgageCalculator, ScheduleEntries,
+    ModificationCalculator, EscrowCalculator, MortgageInsuranceCalculator, PrepaymentPenaltyCalculator,
+};
 
 pub struct BalloonCalculator;
 
 impl MortgageCalculator for BalloonCalculator {
-    fn calculate_schedule(input: &MortgageInput) -> MortgageSchedule {
-        let monthly_rate = input.annual_interest_rate / dec!(12);
-        let num_payments = input.term_years * 12;
-        
-        let balloon_amount = input.principal * input.balloon_payment_percentage / dec!(100);
-        let amortizing_amount = input.principal - balloon_amount;
-        
-        let rate_factor = DecimalUtils::power(dec!(1) + monthly_rate, num_payments as i64);
-        let monthly_payment = amortizing_amount * 
-            (monthly_rate * rate_factor) /
-            (rate_factor - dec!(1));
-
-        let mut schedule = Vec::with_capacity(num_payments as usize);
-        let mut remaining_principal = input.principal;
-        let mut total_interest = Decimal::ZERO;
-
-        for payment_number in 1..=num_payments {
-            let is_final_payment = payment_number == num_payments;
-            let interest_component = remaining_principal * monthly_rate;
-            let mut principal_component = monthly_payment - interest_component;
-            
-            if is_final_payment {
-                principal_component += balloon_amount;
-            }
-            
-            total_interest += interest_component;
-            remaining_principal -= principal_component;
-
-            let payment_amount = if is_final_payment {
-                monthly_payment + balloon_amount
-            } else {
-                monthly_payment
-            };
-
-            let payment_date = input.start_date + 
-                Duration::days(((payment_number - 1) * 30) as i64);
-
-            schedule.push(PaymentScheduleEntry {
-                payment_date,
-                payment_number,
-                payment_amount,
-                principal_component,
-                interest_component,
-                remaining_principal,
-                current_rate: Some(input.annual_interest_rate),
-            });
-        }
+    fn repayment_type(&self) -> RepaymentType {
+        RepaymentType::BalloonPayment
+    }
 
-        MortgageSchedule {
-            monthly_payment,
-            total_payments: (monthly_payment * Decimal::from(num_payments - 1)) + 
-                          (monthly_payment + balloon_amount),
-            total_interest,
-            schedule,
+    fn description(&self) -> &str {
+        "Regular payments with a large final balloon payment at the end of the term."
+    }
+
+    fn calculate_schedule(&self, input: &MortgageInput) -> Result<MortgageSchedule, CalculationError> {
+        let amortization = Self::amortization(input)?;
+        let monthly_payment = amortization.scheduled_payment();
+        let mut schedule = amortization.into_schedule(monthly_payment);
+
+        ModificationCalculator::apply(input, &mut schedule)?;
+        EscrowCalculator::apply(input, &mut schedule)?;
+        MortgageInsuranceCalculator::apply(input, &mut schedule)?;
+        PrepaymentPenaltyCalculator::apply(input, &mut schedule)?;
+        Ok(schedule)
+    }
+
+    fn schedule_entries(&self, input: &MortgageInput) -> Result<ScheduleEntries, CalculationError> {
+        if !is_plain_amortization(input) {
+            return Ok(Box::new(self.calculate_schedule(input)?.schedule.into_iter()));
         }
+        Ok(Box::new(Self::amortization(input)?))
     }
 
-    fn calculate_summary(input: &MortgageInput) -> MortgageSummary {
-        let schedule = Self::calculate_schedule(input);
-        let balloon_amount = input.principal * input.balloon_payment_percentage / dec!(100);
-        
+    fn summarize(&self, input: &MortgageInput, schedule: &MortgageSchedule) -> MortgageSummary {
+        let final_payment = schedule.schedule.last()
+            .map(|entry| (entry.payment_number, entry.payment_amount));
+
         MortgageSummary::new(
             RepaymentType::BalloonPayment,
             schedule.monthly_payment,
@@ -77,7 +50,118 @@ impl MortgageCalculator for BalloonCalculator {
             schedule.total_interest,
             input.principal,
             input.annual_interest_rate,
-            input.term_years * 12,
-        ).with_balloon_payment(balloon_amount)
+            schedule.payment_count() as u32,
+        )
+        .with_balloon_payment(Self::balloon_amount(input, schedule.monthly_payment, final_payment))
+        .with_escrow_from(schedule)
+        .with_mortgage_insurance_from(schedule)
+        .with_prepayment_penalty_from(schedule)
+        .with_modifications_from(schedule)
+    }
+
+    fn calculate_summary(&self, input: &MortgageInput) -> Result<MortgageSummary, CalculationError> {
+        if !is_plain_amortization(input) {
+            return Ok(self.summarize(input, &self.calculate_schedule(input)?));
+        }
+        let amortization = Self::amortization(input)?;
+        let monthly_payment = amortization.scheduled_payment();
+        let totals = amortization.totals()?;
+        let final_payment = Some((totals.payment_count, totals.final_payment));
+
+        Ok(MortgageSummary::new(
+            RepaymentType::BalloonPayment,
+            monthly_payment,
+            totals.total_payments,
+            totals.total_interest,
+            input.principal,
+            input.annual_interest_rate,
+            totals.payment_count,
+        )
+        .with_balloon_payment(Self::balloon_amount(input, monthly_payment, final_payment)))
+    }
+}
+
+impl BalloonCalculator {
+    fn amortization(input: &MortgageInput) -> Result<Amortization, CalculationError> {
+        check_input(input)?;
+        let monthly_rate = input.annual_interest_rate / dec!(12);
+        let num_payments = input.term_years * 12;
+
+        let balloon_amount = input.principal * input.balloon_payment_percentage / dec!(100);
+
+        // Amortize everything except the present value of the balloon
+        let rate_factor = DecimalUtils::checked_power(dec!(1) + monthly_rate, i64::from(num_payments))
+            .ok_or(CalculationError::Overflow("payment factor"))?;
+        let monthly_payment = (input.principal - balloon_amount / rate_factor) *
+            payment_factor(monthly_rate, num_payments)?;
+
+        Ok(Amortization::new(input, monthly_rate, num_payments, 30)
+            .with_level_payment(monthly_payment)
+            .with_balance_due_at_maturity())
+    }
+
+    /// Balloon paid with the final installment, given its number and amount
+    fn balloon_amount(input: &MortgageInput, monthly_payment: Decimal, final_payment: Option<(u32, Decimal)>) -> Decimal {
+        // Extra payments can retire the loan before the balloon falls due
+        final_payment
+            .filter(|(payment_number, _)| *payment_number == input.term_years * 12)
+            .map(|(_, amount)| DecimalUtils::round_currency(amount - monthly_payment))
+            .unwrap_or(Decimal::ZERO)
+            .max(Decimal::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn balloon_loan() -> MortgageInput {
+        MortgageInput {
+            balloon_payment_percentage: dec!(50),
+            ..MortgageInput::new(
+                dec!(100000),
+                dec!(0.06),
+                5,
+                RepaymentType::BalloonPayment,
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_payment_amortizes_principal_less_present_value_of_balloon() {
+        let input = balloon_loan();
+        let schedule = BalloonCalculator.calculate_schedule(&input).unwrap();
+        let summary = BalloonCalculator.calculate_summary(&input).unwrap();
+
+        assert_eq!(DecimalUtils::round_currency(schedule.monthly_payment), dec!(1216.64));
+        assert_eq!(summary.balloon_payment, Some(dec!(50000)));
+        let final_payment = schedule.schedule.last().unwrap();
+        assert_eq!(DecimalUtils::round_currency(final_payment.payment_amount), dec!(51216.64));
+        assert_eq!(final_payment.remaining_principal, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_balloon_is_owed_before_the_final_installment() {
+        let schedule = BalloonCalculator.calculate_schedule(&balloon_loan()).unwrap();
+
+        assert_eq!(schedule.schedule.len(), 60);
+        assert_eq!(DecimalUtils::round_currency(schedule.schedule[58].remaining_principal), dec!(50961.83));
+        let final_payment = &schedule.schedule[59];
+        assert_eq!(DecimalUtils::round_currency(final_payment.interest_component), dec!(254.81));
+        assert_eq!(DecimalUtils::round_currency(final_payment.principal_component), dec!(50961.83));
+    }
+
+    #[test]
+    fn test_extra_payments_retire_the_loan_before_the_balloon() {
+        let mut input = balloon_loan();
+        input.extra_payment = dec!(2000);
+        let schedule = BalloonCalculator.calculate_schedule(&input).unwrap();
+        let summary = BalloonCalculator.calculate_summary(&input).unwrap();
+
+        assert!(schedule.schedule.len() < 60);
+        assert_eq!(schedule.schedule.last().unwrap().remaining_principal, Decimal::ZERO);
+        assert_eq!(summary.balloon_payment, Some(Decimal::ZERO));
     }
 }
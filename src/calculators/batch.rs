@@ -0,0 +1,198 @@
+use std::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+
+use thiserror::Error;
+
+use crate::models::{BatchItemResult, BatchOutput, MortgageInput, MortgageSchedule, MortgageSummary};
+use super::{calculate_schedule, calculate_summary, summarize_schedule, CalculationError};
+
+/// Loan as read from a batch, or why it could not be read
+pub type BatchItem = Result<MortgageInput, String>;
+
+/// Reasons a batch body cannot be split into loans
+#[derive(Debug, Error)]
+pub enum BatchError {
+    #[error("batch body is not a JSON array of loans: {0}")]
+    InvalidArray(#[from] serde_json::Error),
+
+    #[error("batch body is not valid UTF-8")]
+    InvalidEncoding,
+}
+
+/// Calculates many loans at once, reporting failures per loan
+pub struct BatchCalculator;
+
+impl BatchCalculator {
+    /// Splits a JSON array or newline-delimited JSON body into loans
+    ///
+    /// Only a malformed array fails the whole batch; a loan that cannot be
+    /// deserialized becomes an error for that item alone.
+    pub fn parse(body: &[u8]) -> Result<Vec<BatchItem>, BatchError> {
+        let text = std::str::from_utf8(body).map_err(|_| BatchError::InvalidEncoding)?;
+
+        if text.trim_start().starts_with('[') {
+            let values: Vec<serde_json::Value> = serde_json::from_str(text)?;
+            Ok(values.into_iter()
+                .map(|value| serde_json::from_value(value).map_err(|e| e.to_string()))
+                .collect())
+        } else {
+            Ok(text.lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| serde_json::from_str(line).map_err(|e| e.to_string()))
+                .collect())
+        }
+    }
+
+    /// Validates and calculates a single loan
+    pub fn calculate(index: usize, item: &BatchItem, output: BatchOutput) -> BatchItemResult {
+        let input = match item {
+            Ok(input) => input,
+            Err(error) => return Self::failure(index, error.clone()),
+        };
+        if let Err(errors) = input.validate_for(input.repayment_type) {
+            return Self::failure(index, errors.to_string());
+        }
+
+        match Self::outputs(input, output) {
+            Ok((summary, schedule)) => BatchItemResult { index, summary, schedule, error: None },
+            Err(error) => Self::failure(index, error.to_string()),
+        }
+    }
+
+    /// Summary and schedule of a loan, each only when `output` asks for it
+    fn outputs(
+        input: &MortgageInput,
+        output: BatchOutput,
+    ) -> Result<(Option<MortgageSummary>, Option<MortgageSchedule>), CalculationError> {
+        Ok(match output {
+            BatchOutput::Summary => (Some(calculate_summary(input)?), None),
+            BatchOutput::Schedule => (None, Some(calculate_schedule(input)?)),
+            BatchOutput::Both => {
+                let schedule = calculate_schedule(input)?;
+                (Some(summarize_schedule(input, &schedule)?), Some(schedule))
+            }
+        })
+    }
+
+    /// Calculates `items` across the available cores, keeping request order
+    ///
+    /// `first_index` is the position of the first item in the whole batch. A
+    /// loan whose calculation panics fails alone, like any other bad loan.
+    pub fn calculate_parallel(first_index: usize, items: &[BatchItem], output: BatchOutput) -> Vec<BatchItemResult> {
+        let workers = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let per_worker = items.len().div_ceil(workers).max(1);
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = items.chunks(per_worker)
+                .enumerate()
+                .map(|(chunk, slice)| {
+                    let offset = first_index + chunk * per_worker;
+                    let handle = scope.spawn(move || slice.iter()
+                        .enumerate()
+                        .map(|(position, item)| Self::calculate_isolated(offset + position, item, output))
+                        .collect::<Vec<_>>());
+                    (offset..offset + slice.len(), handle)
+                })
+                .collect();
+
+            handles.into_iter()
+                .flat_map(|(indices, handle)| handle.join().unwrap_or_else(|_| indices
+                    .map(|index| Self::failure(index, Self::PANICKED.to_string()))
+                    .collect()))
+                .collect()
+        })
+    }
+
+    const PANICKED: &'static str = "calculation failed unexpectedly";
+
+    /// Calculates a single loan, turning a panic into an error for that loan
+    fn calculate_isolated(index: usize, item: &BatchItem, output: BatchOutput) -> BatchItemResult {
+        panic::catch_unwind(AssertUnwindSafe(|| Self::calculate(index, item, output)))
+            .unwrap_or_else(|_| Self::failure(index, Self::PANICKED.to_string()))
+    }
+
+    fn failure(index: usize, error: String) -> BatchItemResult {
+        BatchItemResult {
+            index,
+            summary: None,
+            schedule: None,
+            error: Some(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculators::{register_calculator, MortgageCalculator};
+    use crate::models::{CustomRepaymentType, RepaymentType};
+
+    const LOAN: &str = r#"{"principal":250000,"annualInterestRate":0.05,"termYears":30,"repaymentType":"standardPrincipalAndInterest","startDate":"2024-01-01"}"#;
+
+    #[test]
+    fn test_parse_array_and_ndjson() {
+        let array = BatchCalculator::parse(format!("[{LOAN}, {{\"principal\": \"abc\"}}]").as_bytes()).unwrap();
+        let ndjson = BatchCalculator::parse(format!("{LOAN}\n\n{LOAN}\nnot json\n").as_bytes()).unwrap();
+
+        assert_eq!(array.len(), 2);
+        assert!(array[0].is_ok() && array[1].is_err());
+        assert_eq!(ndjson.len(), 3);
+        assert!(ndjson[2].is_err());
+        assert!(BatchCalculator::parse(b"[{").is_err());
+    }
+
+    #[test]
+    fn test_parallel_results_keep_order_and_errors() {
+        let mut items: Vec<BatchItem> = (0..50)
+            .map(|_| Ok(serde_json::from_str(LOAN).unwrap()))
+            .collect();
+        if let Ok(input) = &mut items[7] {
+            input.term_years = 0;
+        }
+        items[20] = Err("expected value".to_string());
+
+        let results = BatchCalculator::calculate_parallel(100, &items, BatchOutput::Summary);
+
+        assert_eq!(results.len(), 50);
+        assert!(results.iter().enumerate().all(|(position, result)| result.index == 100 + position));
+        assert!(results[7].error.is_some() && results[20].error.is_some());
+        assert_eq!(results.iter().filter(|result| result.summary.is_some()).count(), 48);
+        assert!(results[0].schedule.is_none());
+    }
+
+    static PANICKING: CustomRepaymentType = CustomRepaymentType::new("batchTestPanicking", "Panicking");
+
+    struct PanickingCalculator;
+
+    impl MortgageCalculator for PanickingCalculator {
+        fn repayment_type(&self) -> RepaymentType {
+            RepaymentType::Custom(&PANICKING)
+        }
+
+        fn description(&self) -> &str {
+            "Panics on every calculation."
+        }
+
+        fn calculate_schedule(&self, _input: &MortgageInput) -> Result<MortgageSchedule, CalculationError> {
+            panic!("calculator bug")
+        }
+    }
+
+    #[test]
+    fn test_panicking_calculation_fails_only_its_loan() {
+        register_calculator(PanickingCalculator);
+        let mut items: Vec<BatchItem> = (0..10)
+            .map(|_| Ok(serde_json::from_str(LOAN).unwrap()))
+            .collect();
+        if let Ok(input) = &mut items[3] {
+            input.repayment_type = RepaymentType::Custom(&PANICKING);
+        }
+
+        let results = BatchCalculator::calculate_parallel(0, &items, BatchOutput::Summary);
+
+        assert_eq!(results.len(), 10);
+        assert_eq!(results[3].error.as_deref(), Some("calculation failed unexpectedly"));
+        assert_eq!(results.iter().filter(|result| result.summary.is_some()).count(), 9);
+    }
+}
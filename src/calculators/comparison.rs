@@ -0,0 +1,160 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use crate::models::{
+    ComparisonResult,
+    CostCurvePoint,
+    MortgageInput,
+    MortgageSchedule,
+    MortgageSummary,
+    PaymentScheduleEntry,
+    ScenarioDifference,
+    ScenarioResult,
+};
+use super::{calculate_schedule, summarize_schedule, CalculationError};
+
+/// Length of a loan month when aligning schedules, matching the calculators' date stepping
+pub(crate) const DAYS_PER_MONTH: i64 = 30;
+
+/// Runs several loans through the calculators and lines up their results
+pub struct ComparisonCalculator;
+
+impl ComparisonCalculator {
+    pub fn compare(scenarios: &[(String, MortgageInput)]) -> Result<ComparisonResult, CalculationError> {
+        let schedules: Vec<MortgageSchedule> = scenarios.iter()
+            .map(|(_, input)| calculate_schedule(input))
+            .collect::<Result<_, _>>()?;
+        let results: Vec<ScenarioResult> = scenarios.iter()
+            .zip(&schedules)
+            .map(|((name, input), schedule)| Ok(ScenarioResult {
+                name: name.clone(),
+                input: input.clone(),
+                summary: summarize_schedule(input, schedule)?,
+            }))
+            .collect::<Result<_, CalculationError>>()?;
+
+        let differences = results.iter()
+            .map(|from| results.iter()
+                .map(|to| Self::difference(&from.summary, &to.summary))
+                .collect())
+            .collect();
+
+        let monthly_costs: Vec<Vec<(Decimal, Decimal)>> = scenarios.iter()
+            .zip(&schedules)
+            .map(|((_, input), schedule)| Self::monthly_costs(input.start_date, &schedule.schedule))
+            .collect();
+
+        Ok(ComparisonResult {
+            scenarios: results,
+            differences,
+            cost_curves: Self::cost_curves(&monthly_costs),
+        })
+    }
+
+    /// Subtracts `from` from `to`
+    fn difference(from: &MortgageSummary, to: &MortgageSummary) -> ScenarioDifference {
+        ScenarioDifference {
+            monthly_payment: to.monthly_payment - from.monthly_payment,
+            total_monthly_payment: to.total_monthly_payment - from.total_monthly_payment,
+            total_payments: to.total_payments - from.total_payments,
+            total_interest: to.total_interest - from.total_interest,
+            number_of_payments: i64::from(to.number_of_payments) - i64::from(from.number_of_payments),
+        }
+    }
+
+    /// Payments and interest falling in each month from `start_date`, indexed from month one
+    pub(crate) fn monthly_costs(start_date: NaiveDate, entries: &[PaymentScheduleEntry]) -> Vec<(Decimal, Decimal)> {
+        let mut months: Vec<(Decimal, Decimal)> = Vec::new();
+        for entry in entries {
+            let month = ((entry.payment_date - start_date).num_days() / DAYS_PER_MONTH).max(0) as usize;
+            if months.len() <= month {
+                months.resize(month + 1, (Decimal::ZERO, Decimal::ZERO));
+            }
+            months[month].0 += entry.payment_amount;
+            months[month].1 += entry.interest_component;
+        }
+        months
+    }
+
+    /// Accumulates monthly costs, carrying each total forward once a loan is repaid
+    fn cost_curves(monthly_costs: &[Vec<(Decimal, Decimal)>]) -> Vec<CostCurvePoint> {
+        let months = monthly_costs.iter().map(Vec::len).max().unwrap_or(0);
+        let mut cumulative_payments = vec![Decimal::ZERO; monthly_costs.len()];
+        let mut cumulative_interest = vec![Decimal::ZERO; monthly_costs.len()];
+
+        (0..months)
+            .map(|month| {
+                for (index, costs) in monthly_costs.iter().enumerate() {
+                    if let Some((payments, interest)) = costs.get(month) {
+                        cumulative_payments[index] += payments;
+                        cumulative_interest[index] += interest;
+                    }
+                }
+                CostCurvePoint {
+                    month: month as u32 + 1,
+                    cumulative_payments: cumulative_payments.clone(),
+                    cumulative_interest: cumulative_interest.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+    use crate::models::RepaymentType;
+
+    fn scenario(name: &str, term_years: u32, repayment_type: RepaymentType) -> (String, MortgageInput) {
+        (name.to_string(), MortgageInput::new(
+            dec!(300000),
+            dec!(0.06),
+            term_years,
+            repayment_type,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn test_difference_matrix_is_antisymmetric() {
+        let result = ComparisonCalculator::compare(&[
+            scenario("30 year", 30, RepaymentType::StandardPrincipalAndInterest),
+            scenario("15 year", 15, RepaymentType::StandardPrincipalAndInterest),
+        ]).unwrap();
+
+        assert_eq!(result.differences[0][0].total_interest, Decimal::ZERO);
+        assert_eq!(result.differences[0][1].total_interest, -result.differences[1][0].total_interest);
+        assert!(result.differences[0][1].total_interest < Decimal::ZERO);
+        assert_eq!(result.differences[0][1].number_of_payments, -180);
+    }
+
+    #[test]
+    fn test_cost_curves_cover_longest_loan() {
+        let result = ComparisonCalculator::compare(&[
+            scenario("30 year", 30, RepaymentType::StandardPrincipalAndInterest),
+            scenario("15 year", 15, RepaymentType::StandardPrincipalAndInterest),
+        ]).unwrap();
+
+        assert_eq!(result.cost_curves.len(), 360);
+        let last = result.cost_curves.last().unwrap();
+        assert_eq!(last.cumulative_payments[0], result.scenarios[0].summary.total_payments);
+        assert_eq!(last.cumulative_payments[1], result.scenarios[1].summary.total_payments);
+        assert_eq!(last.cumulative_interest[1], result.scenarios[1].summary.total_interest);
+    }
+
+    #[test]
+    fn test_biweekly_payments_grouped_by_month() {
+        let result = ComparisonCalculator::compare(&[
+            scenario("monthly", 30, RepaymentType::StandardPrincipalAndInterest),
+            scenario("biweekly", 30, RepaymentType::AcceleratedBiweekly),
+        ]).unwrap();
+
+        // Days 0, 14 and 28 all fall in the first 30-day month
+        let first = &result.cost_curves[0];
+        let biweekly_payment = result.scenarios[1].summary.monthly_payment / dec!(2);
+        assert_eq!(first.cumulative_payments[1], biweekly_payment * dec!(3));
+        assert!(result.cost_curves.len() <= 360);
+    }
+}
@@ -0,0 +1,246 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use chrono::{Datelike, Months, NaiveDate};
+
+use crate::models::{EscrowAnalysis, EscrowInput, MortgageInput, MortgageSchedule};
+use crate::utils::{DateUtils, DecimalUtils};
+use super::CalculationError;
+
+/// Surplus at or above this amount is refunded instead of credited (RESPA)
+const REFUND_THRESHOLD: Decimal = dec!(50);
+
+/// Largest grown annual amount of one component, leaving headroom for the balances it feeds
+const MAX_ANNUAL_DISBURSEMENT: Decimal = dec!(1_000_000_000_000_000_000_000_000);
+
+/// Layers escrow deposits, disbursements and annual analyses onto a P&I schedule
+pub struct EscrowCalculator;
+
+impl EscrowCalculator {
+    /// Fills the escrow columns of `schedule` when `input` carries an escrow component
+    pub fn apply(input: &MortgageInput, schedule: &mut MortgageSchedule) -> Result<(), CalculationError> {
+        let escrow = match &input.escrow {
+            Some(escrow) => escrow,
+            None => return Ok(()),
+        };
+        if schedule.schedule.is_empty() {
+            return Ok(());
+        }
+
+        let disbursements = Self::disbursements_per_payment(input.start_date, escrow, schedule)?;
+        let payments_per_year = input.repayment_type.payments_per_year() as usize;
+        let periods = Decimal::from(payments_per_year);
+
+        let mut balance = Decimal::ZERO;
+        let mut initial_deposit = Decimal::ZERO;
+        let mut analyses = Vec::new();
+
+        for (year_index, block_start) in (0..schedule.schedule.len()).step_by(payments_per_year).enumerate() {
+            let block_end = (block_start + payments_per_year).min(schedule.schedule.len());
+            let block = &disbursements[block_start..block_end];
+
+            let projected_disbursements: Decimal = block.iter().sum();
+            let required_cushion = DecimalUtils::round_currency(
+                projected_disbursements * Decimal::from(escrow.cushion_months) / dec!(12)
+            );
+            let base_deposit = DecimalUtils::round_currency(projected_disbursements / periods);
+            let beginning_balance = balance;
+
+            let mut lowest_projected_balance = Self::lowest_balance(balance, base_deposit, block);
+            if year_index == 0 {
+                initial_deposit = (required_cushion - lowest_projected_balance).max(Decimal::ZERO);
+                initial_deposit = DecimalUtils::round_currency(initial_deposit);
+                balance += initial_deposit;
+                lowest_projected_balance += initial_deposit;
+            }
+
+            let shortage = (required_cushion - lowest_projected_balance).max(Decimal::ZERO);
+            let surplus = (lowest_projected_balance - required_cushion).max(Decimal::ZERO);
+            let refund = if surplus >= REFUND_THRESHOLD { surplus } else { Decimal::ZERO };
+            let credit = surplus - refund;
+            balance -= refund;
+
+            let periodic_deposit = DecimalUtils::round_currency(
+                base_deposit + (shortage - credit) / periods
+            ).max(Decimal::ZERO);
+
+            for (entry, disbursement) in schedule.schedule[block_start..block_end].iter_mut().zip(block) {
+                balance += periodic_deposit - disbursement;
+                entry.escrow_deposit = Some(periodic_deposit);
+                entry.escrow_balance = Some(balance);
+            }
+
+            analyses.push(EscrowAnalysis {
+                escrow_year: year_index as u32 + 1,
+                analysis_date: schedule.schedule[block_start].payment_date,
+                beginning_balance,
+                projected_disbursements,
+                required_cushion,
+                lowest_projected_balance,
+                shortage,
+                surplus,
+                refund,
+                periodic_deposit,
+                monthly_deposit: periodic_deposit * periods / dec!(12),
+            });
+        }
+
+        schedule.initial_escrow_deposit = Some(initial_deposit);
+        schedule.escrow_analysis = analyses;
+        Ok(())
+    }
+
+    /// Returns the escrow disbursements paid out alongside each scheduled payment
+    fn disbursements_per_payment(
+        start_date: NaiveDate,
+        escrow: &EscrowInput,
+        schedule: &MortgageSchedule,
+    ) -> Result<Vec<Decimal>, CalculationError> {
+        let components = [
+            (escrow.annual_property_tax, escrow.property_tax_growth_rate, &escrow.property_tax_disbursement_months),
+            (escrow.annual_insurance, escrow.insurance_growth_rate, &escrow.insurance_disbursement_months),
+            (escrow.annual_hoa, escrow.hoa_growth_rate, &escrow.hoa_disbursement_months),
+        ];
+
+        let mut previous_date = start_date;
+        schedule.schedule.iter()
+            .map(|entry| {
+                let mut total = Decimal::ZERO;
+                for month_start in Self::month_starts_between(previous_date, entry.payment_date) {
+                    for (annual_amount, growth_rate, months) in &components {
+                        total += Self::disbursement_on(start_date, month_start, *annual_amount, *growth_rate, months)?;
+                    }
+                }
+                previous_date = previous_date.max(entry.payment_date);
+                Ok(total)
+            })
+            .collect()
+    }
+
+    /// Returns the first-of-month dates in the half-open interval `(after, until]`
+    fn month_starts_between(after: NaiveDate, until: NaiveDate) -> Vec<NaiveDate> {
+        let mut dates = Vec::new();
+        let mut month_start = after.with_day(1).unwrap_or(after);
+        while month_start <= until {
+            if month_start > after {
+                dates.push(month_start);
+            }
+            month_start = match month_start.checked_add_months(Months::new(1)) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        dates
+    }
+
+    /// Amount of one component disbursed on the given month start
+    fn disbursement_on(
+        start_date: NaiveDate,
+        month_start: NaiveDate,
+        annual_amount: Decimal,
+        growth_rate: Decimal,
+        months: &[u32],
+    ) -> Result<Decimal, CalculationError> {
+        if annual_amount.is_zero() {
+            return Ok(Decimal::ZERO);
+        }
+        let installments = if months.is_empty() {
+            12
+        } else if months.contains(&month_start.month()) {
+            months.len()
+        } else {
+            return Ok(Decimal::ZERO);
+        };
+
+        let years = DateUtils::years_between(start_date, month_start);
+        let grown = DecimalUtils::checked_power(dec!(1) + growth_rate, years as i64)
+            .and_then(|growth| annual_amount.checked_mul(growth))
+            .filter(|grown| *grown <= MAX_ANNUAL_DISBURSEMENT)
+            .ok_or(CalculationError::Overflow("escrow disbursement"))?;
+        Ok(DecimalUtils::round_currency(grown / Decimal::from(installments)))
+    }
+
+    /// Lowest period-end balance over a block when collecting `deposit` each period
+    fn lowest_balance(opening_balance: Decimal, deposit: Decimal, disbursements: &[Decimal]) -> Decimal {
+        let mut balance = opening_balance;
+        let mut lowest: Option<Decimal> = None;
+        for disbursement in disbursements {
+            balance += deposit - disbursement;
+            lowest = Some(lowest.map_or(balance, |low| low.min(balance)));
+        }
+        lowest.unwrap_or(opening_balance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculators::{MortgageCalculator, StandardCalculator};
+    use crate::models::RepaymentType;
+
+    fn escrow_input(escrow: EscrowInput) -> MortgageInput {
+        MortgageInput {
+            escrow: Some(escrow),
+            ..MortgageInput::new(
+                dec!(300000),
+                dec!(0.06),
+                30,
+                RepaymentType::StandardPrincipalAndInterest,
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            )
+        }
+    }
+
+    fn sample_escrow() -> EscrowInput {
+        EscrowInput {
+            annual_property_tax: dec!(3600),
+            annual_insurance: dec!(1200),
+            annual_hoa: dec!(0),
+            property_tax_growth_rate: dec!(0.03),
+            insurance_growth_rate: dec!(0),
+            hoa_growth_rate: dec!(0),
+            property_tax_disbursement_months: vec![4, 10],
+            insurance_disbursement_months: vec![6],
+            hoa_disbursement_months: vec![],
+            cushion_months: 2,
+        }
+    }
+
+    #[test]
+    fn test_escrow_columns_populated() {
+        let schedule = StandardCalculator.calculate_schedule(&escrow_input(sample_escrow())).unwrap();
+
+        assert_eq!(schedule.escrow_analysis.len(), 30);
+        assert!(schedule.schedule.iter().all(|entry| entry.escrow_deposit.is_some()));
+        assert_eq!(schedule.monthly_escrow_payment(), Some(dec!(400)));
+    }
+
+    #[test]
+    fn test_balance_never_below_zero_in_first_year() {
+        let schedule = StandardCalculator.calculate_schedule(&escrow_input(sample_escrow())).unwrap();
+
+        assert!(schedule.initial_escrow_deposit.unwrap() > Decimal::ZERO);
+        for entry in &schedule.schedule[..12] {
+            assert!(entry.escrow_balance.unwrap() >= Decimal::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_tax_growth_raises_deposit() {
+        let schedule = StandardCalculator.calculate_schedule(&escrow_input(sample_escrow())).unwrap();
+        let first = &schedule.escrow_analysis[0];
+        let fifth = &schedule.escrow_analysis[4];
+
+        assert!(fifth.projected_disbursements > first.projected_disbursements);
+        assert!(fifth.periodic_deposit > first.periodic_deposit);
+    }
+
+    #[test]
+    fn test_no_escrow_leaves_schedule_untouched() {
+        let mut input = escrow_input(sample_escrow());
+        input.escrow = None;
+        let schedule = StandardCalculator.calculate_schedule(&input).unwrap();
+
+        assert!(schedule.escrow_analysis.is_empty());
+        assert!(schedule.schedule[0].escrow_deposit.is_none());
+    }
+}
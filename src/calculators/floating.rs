@@ -4,33 +4,85 @@ use rand::Rng;
 use chrono::Duration;
 
 use crate::models::{MortgageInput, MortgageSchedule, PaymentScheduleEntry, MortgageSummary, RepaymentType};
-use super::MortgageCalculator;
+use super::{
+    check_input, payment_factor, CalculationError, MortgageCalculator,
+    ModificationCalculator, EscrowCalculator, MortgageInsuranceCalculator, PrepaymentPenaltyCalculator,
+};
 
 pub struct FloatingRateCalculator;
 
 impl MortgageCalculator for FloatingRateCalculator {
-    fn calculate_schedule(input: &MortgageInput) -> MortgageSchedule {
-        let num_payments = input.term_years * 12;
+    fn repayment_type(&self) -> RepaymentType {
+        RepaymentType::FloatingRate
+    }
+
+    fn description(&self) -> &str {
+        "Variable interest rate that changes monthly between 1-10% APR."
+    }
+
+    fn calculate_schedule(&self, input: &MortgageInput) -> Result<MortgageSchedule, CalculationError> {
+        check_input(input)?;
+        Self::schedule_with_rates(input, &Self::draw_rates(input.term_years * 12))
+    }
+
+    fn summarize(&self, input: &MortgageInput, schedule: &MortgageSchedule) -> MortgageSummary {
+        MortgageSummary::new(
+            RepaymentType::FloatingRate,
+            schedule.monthly_payment,
+            schedule.total_payments,
+            schedule.total_interest,
+            input.principal,
+            dec!(5.5),
+            schedule.payment_count() as u32,
+        )
+        .with_escrow_from(schedule)
+        .with_mortgage_insurance_from(schedule)
+        .with_prepayment_penalty_from(schedule)
+        .with_modifications_from(schedule)
+    }
+}
+
+impl FloatingRateCalculator {
+    /// Draws a fresh annual percentage rate for each of `payments` months
+    pub(crate) fn draw_rates(payments: u32) -> Vec<Decimal> {
         let mut rng = rand::thread_rng();
-        
+        (0..payments).map(|_| Decimal::from(rng.gen_range(1..=10))).collect()
+    }
+
+    /// Schedule following `rates`, one annual percentage rate per payment
+    pub(crate) fn schedule_with_rates(input: &MortgageInput, rates: &[Decimal]) -> Result<MortgageSchedule, CalculationError> {
+        check_input(input)?;
+        let num_payments = input.term_years * 12;
+        if rates.len() < num_payments as usize {
+            return Err(CalculationError::InvalidInput(format!(
+                "{} floating rates drawn for {} payments", rates.len(), num_payments
+            )));
+        }
+
         let mut schedule = Vec::with_capacity(num_payments as usize);
         let mut remaining_principal = input.principal;
         let mut total_interest = Decimal::ZERO;
         let mut total_payments = Decimal::ZERO;
 
         for payment_number in 1..=num_payments {
-            let annual_rate = Decimal::from(rng.gen_range(1..=10));
+            if remaining_principal <= Decimal::ZERO {
+                break;
+            }
+            let annual_rate = rates[(payment_number - 1) as usize];
             let monthly_rate = annual_rate / dec!(100) / dec!(12);
             
-            let monthly_payment = remaining_principal * 
-                (monthly_rate * (dec!(1) + monthly_rate)) /
-                (dec!(1) + monthly_rate - dec!(1));
+            // Re-amortize the outstanding balance over the remaining term at the new rate
+            let remaining_payments = num_payments - payment_number + 1;
+            let monthly_payment = remaining_principal *
+                payment_factor(monthly_rate, remaining_payments)?;
             
             let interest_component = remaining_principal * monthly_rate;
-            let principal_component = monthly_payment - interest_component;
+            let principal_component = (monthly_payment - interest_component + input.extra_payment)
+                .min(remaining_principal);
+            let payment_amount = principal_component + interest_component;
             
             total_interest += interest_component;
-            total_payments += monthly_payment;
+            total_payments += payment_amount;
             remaining_principal -= principal_component;
 
             let payment_date = input.start_date + Duration::days(((payment_number - 1) * 30) as i64);
@@ -38,35 +90,105 @@ impl MortgageCalculator for FloatingRateCalculator {
             schedule.push(PaymentScheduleEntry {
                 payment_date,
                 payment_number,
-                payment_amount: monthly_payment,
+                payment_amount,
                 principal_component,
                 interest_component,
                 remaining_principal,
                 current_rate: Some(annual_rate),
+                escrow_deposit: None,
+                escrow_balance: None,
+                mortgage_insurance: None,
+                prepayment_penalty: None,
+                deferred_balance: None,
+                modifications: Vec::new(),
             });
         }
 
         let avg_monthly_payment = total_payments / Decimal::from(schedule.len());
 
-        MortgageSchedule {
+        let mut schedule = MortgageSchedule {
             monthly_payment: avg_monthly_payment,
             total_payments,
             total_interest,
             schedule,
-        }
+            initial_escrow_deposit: None,
+            escrow_analysis: Vec::new(),
+            monthly_mortgage_insurance: None,
+            upfront_mortgage_insurance: None,
+            mortgage_insurance_end_date: None,
+            deferred_balance: None,
+        };
+
+        ModificationCalculator::apply(input, &mut schedule)?;
+        EscrowCalculator::apply(input, &mut schedule)?;
+        MortgageInsuranceCalculator::apply(input, &mut schedule)?;
+        PrepaymentPenaltyCalculator::apply(input, &mut schedule)?;
+        Ok(schedule)
     }
+}
 
-    fn calculate_summary(input: &MortgageInput) -> MortgageSummary {
-        let schedule = Self::calculate_schedule(input);
-        
-        MortgageSummary::new(
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::utils::DecimalUtils;
+
+    fn floating_loan() -> MortgageInput {
+        MortgageInput::new(
+            dec!(200000),
+            dec!(0.05),
+            30,
             RepaymentType::FloatingRate,
-            schedule.monthly_payment,
-            schedule.total_payments,
-            schedule.total_interest,
-            input.principal,
-            dec!(5.5),
-            input.term_years * 12,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
         )
     }
+
+    #[test]
+    fn test_each_rate_change_reamortizes_over_remaining_term() {
+        let input = floating_loan();
+        let schedule = FloatingRateCalculator.calculate_schedule(&input).unwrap();
+
+        assert_eq!(schedule.schedule.len(), 360);
+        assert!(schedule.schedule.last().unwrap().remaining_principal.abs() < dec!(0.01));
+        let mut balance = input.principal;
+        for entry in &schedule.schedule {
+            let monthly_rate = entry.current_rate.unwrap() / dec!(100) / dec!(12);
+            let remaining_payments = 360 - entry.payment_number + 1;
+            let expected = balance * payment_factor(monthly_rate, remaining_payments).unwrap();
+            assert_eq!(DecimalUtils::round_currency(entry.payment_amount), DecimalUtils::round_currency(expected));
+            balance = entry.remaining_principal;
+        }
+    }
+
+    #[test]
+    fn test_payments_stay_within_the_rate_band() {
+        let input = floating_loan();
+        let schedule = FloatingRateCalculator.calculate_schedule(&input).unwrap();
+
+        // A 30-year installment on 200,000 costs 643.28 at 1% and 1,755.14 at 10%
+        let first = &schedule.schedule[0];
+        assert!(first.payment_amount > dec!(643.27) && first.payment_amount < dec!(1755.15));
+        assert!(schedule.schedule.windows(2).all(|pair| pair[1].remaining_principal < pair[0].remaining_principal));
+    }
+
+    #[test]
+    fn test_same_rates_give_the_same_schedule() {
+        let input = floating_loan();
+        let rates = FloatingRateCalculator::draw_rates(360);
+        let first = FloatingRateCalculator::schedule_with_rates(&input, &rates).unwrap();
+        let second = FloatingRateCalculator::schedule_with_rates(&input, &rates).unwrap();
+
+        assert_eq!(serde_json::to_value(first).unwrap(), serde_json::to_value(second).unwrap());
+        assert!(FloatingRateCalculator::schedule_with_rates(&input, &rates[..359]).is_err());
+    }
+
+    #[test]
+    fn test_extra_payments_shorten_the_term() {
+        let mut input = floating_loan();
+        input.extra_payment = dec!(1000);
+        let schedule = FloatingRateCalculator.calculate_schedule(&input).unwrap();
+
+        assert!(schedule.schedule.len() < 360);
+        assert_eq!(schedule.schedule.last().unwrap().remaining_principal, Decimal::ZERO);
+    }
 }
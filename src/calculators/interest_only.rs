@@ -1,71 +1,109 @@
-use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use chrono::Duration;
 
-use crate::models::{MortgageInput, MortgageSchedule, PaymentScheduleEntry, MortgageSummary, RepaymentType};
-use super::MortgageCalculator;
+use crate::models::{MortgageInput, MortgageSchedule, MortgageSummary, RepaymentType};
+use super::amortization::{is_plain_amortization, Amortization};
+use super::{
+    check_input, CalculationError, MortgageCalculator, ScheduleEntries,
+    ModificationCalculator, EscrowCalculator, MortgageInsuranceCalculator, PrepaymentPenaltyCalculator,
+};
 
 pub struct InterestOnlyCalculator;
 
 impl MortgageCalculator for InterestOnlyCalculator {
-    fn calculate_schedule(input: &MortgageInput) -> MortgageSchedule {
-        let monthly_rate = input.annual_interest_rate / dec!(12);
-        let num_payments = input.term_years * 12;
-        let monthly_payment = input.principal * monthly_rate;
-        
-        let mut schedule = Vec::with_capacity(num_payments as usize);
-        let mut total_interest = Decimal::ZERO;
-
-        for payment_number in 1..=num_payments {
-            let is_final_payment = payment_number == num_payments;
-            let interest_component = monthly_payment;
-            let principal_component = if is_final_payment {
-                input.principal
-            } else {
-                Decimal::ZERO
-            };
-            
-            total_interest += interest_component;
-
-            let payment_amount = if is_final_payment {
-                monthly_payment + input.principal
-            } else {
-                monthly_payment
-            };
-
-            let payment_date = input.start_date + 
-                Duration::days(((payment_number - 1) * 30) as i64);
-
-            schedule.push(PaymentScheduleEntry {
-                payment_date,
-                payment_number,
-                payment_amount,
-                principal_component,
-                interest_component,
-                remaining_principal: input.principal,
-                current_rate: Some(input.annual_interest_rate),
-            });
-        }
+    fn repayment_type(&self) -> RepaymentType {
+        RepaymentType::InterestOnly
+    }
 
-        MortgageSchedule {
-            monthly_payment,
-            total_payments: (monthly_payment * Decimal::from(num_payments)) + input.principal,
-            total_interest,
-            schedule,
+    fn description(&self) -> &str {
+        "Pay only interest during the loan term with full principal due at the end."
+    }
+
+    fn calculate_schedule(&self, input: &MortgageInput) -> Result<MortgageSchedule, CalculationError> {
+        let amortization = Self::amortization(input)?;
+        let monthly_payment = amortization.scheduled_payment();
+        let mut schedule = amortization.into_schedule(monthly_payment);
+
+        ModificationCalculator::apply(input, &mut schedule)?;
+        EscrowCalculator::apply(input, &mut schedule)?;
+        MortgageInsuranceCalculator::apply(input, &mut schedule)?;
+        PrepaymentPenaltyCalculator::apply(input, &mut schedule)?;
+        Ok(schedule)
+    }
+
+    fn schedule_entries(&self, input: &MortgageInput) -> Result<ScheduleEntries, CalculationError> {
+        if !is_plain_amortization(input) {
+            return Ok(Box::new(self.calculate_schedule(input)?.schedule.into_iter()));
         }
+        Ok(Box::new(Self::amortization(input)?))
     }
 
-    fn calculate_summary(input: &MortgageInput) -> MortgageSummary {
-        let schedule = Self::calculate_schedule(input);
-        
-        MortgageSummary::new(
-            RepaymentType::InterestOnly,
-            schedule.monthly_payment,
-            schedule.total_payments,
-            schedule.total_interest,
+    fn calculate_summary(&self, input: &MortgageInput) -> Result<MortgageSummary, CalculationError> {
+        if !is_plain_amortization(input) {
+            return Ok(self.summarize(input, &self.calculate_schedule(input)?));
+        }
+        let amortization = Self::amortization(input)?;
+        let monthly_payment = amortization.scheduled_payment();
+        let totals = amortization.totals()?;
+
+        Ok(MortgageSummary::new(
+            self.repayment_type(),
+            monthly_payment,
+            totals.total_payments,
+            totals.total_interest,
             input.principal,
             input.annual_interest_rate,
-            input.term_years * 12,
-        )
+            totals.payment_count,
+        ))
+    }
+}
+
+impl InterestOnlyCalculator {
+    fn amortization(input: &MortgageInput) -> Result<Amortization, CalculationError> {
+        check_input(input)?;
+        let monthly_rate = input.annual_interest_rate / dec!(12);
+        let num_payments = input.term_years * 12;
+
+        Ok(Amortization::new(input, monthly_rate, num_payments, 30).with_balance_due_at_maturity())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    fn interest_only_loan(extra_payment: Decimal) -> MortgageInput {
+        MortgageInput {
+            extra_payment,
+            ..MortgageInput::new(
+                dec!(100000),
+                dec!(0.06),
+                1,
+                RepaymentType::InterestOnly,
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_interest_is_level_without_extra_payments() {
+        let schedule = InterestOnlyCalculator.calculate_schedule(&interest_only_loan(dec!(0))).unwrap();
+
+        assert!(schedule.schedule.iter().all(|entry| entry.interest_component == dec!(500)));
+        assert_eq!(schedule.total_interest, dec!(6000));
+        assert_eq!(schedule.schedule.last().unwrap().payment_amount, dec!(100500));
+    }
+
+    #[test]
+    fn test_interest_accrues_on_balance_reduced_by_extra_payments() {
+        let schedule = InterestOnlyCalculator.calculate_schedule(&interest_only_loan(dec!(1000))).unwrap();
+
+        assert_eq!(schedule.schedule[0].interest_component, dec!(500));
+        assert_eq!(schedule.schedule[1].interest_component, dec!(495));
+        assert_eq!(schedule.total_interest, dec!(5670));
+        let final_payment = schedule.schedule.last().unwrap();
+        assert_eq!(final_payment.payment_amount, dec!(89445));
+        assert_eq!(final_payment.remaining_principal, Decimal::ZERO);
     }
 }
@@ -0,0 +1,241 @@
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+
+use crate::models::{
+    InterestStatement,
+    InterestStatementRequest,
+    MortgageInput,
+    MortgageSchedule,
+    PaymentHistoryRequest,
+    ReceivedPayment,
+    StatementSource,
+};
+use crate::utils::DecimalUtils;
+use super::{calculate_schedule, CalculationError, PaymentHistoryReconciler};
+
+/// Reports a tax year's mortgage interest the way a servicer files Form 1098
+///
+/// Without a payment history, installments are taken as paid on their due
+/// dates. With one, payments count in the year they were received and
+/// principal follows what was actually applied, curtailments included.
+pub struct InterestStatementCalculator;
+
+impl InterestStatementCalculator {
+    pub fn statement(request: &InterestStatementRequest) -> Result<InterestStatement, CalculationError> {
+        let schedule = calculate_schedule(&request.input)?;
+        match &request.payments {
+            Some(payments) => Self::from_history(request, &schedule, payments),
+            None => Ok(Self::from_schedule(&request.input, &schedule, request.tax_year, request.points_paid)),
+        }
+    }
+
+    /// Statement for `tax_year` assuming every installment of `schedule` is paid when due
+    pub fn from_schedule(
+        input: &MortgageInput,
+        schedule: &MortgageSchedule,
+        tax_year: i32,
+        points_paid: Decimal,
+    ) -> InterestStatement {
+        let before_year = schedule.schedule.iter()
+            .take_while(|entry| entry.payment_date.year() < tax_year)
+            .last();
+        let outstanding_principal = match before_year {
+            Some(entry) if input.start_date.year() < tax_year => entry.remaining_principal,
+            _ => input.principal,
+        };
+
+        let mut statement = Self::statement_for(input, tax_year, points_paid, StatementSource::Schedule);
+        statement.outstanding_principal = DecimalUtils::round_currency(outstanding_principal);
+        statement.ending_principal = statement.outstanding_principal;
+        for entry in schedule.schedule.iter().filter(|entry| entry.payment_date.year() == tax_year) {
+            statement.mortgage_interest += DecimalUtils::round_currency(entry.interest_component);
+            statement.mortgage_insurance_premiums +=
+                DecimalUtils::round_currency(entry.mortgage_insurance.unwrap_or(Decimal::ZERO));
+            statement.number_of_payments += 1;
+            statement.ending_principal = DecimalUtils::round_currency(entry.remaining_principal);
+        }
+        statement
+    }
+
+    fn from_history(
+        request: &InterestStatementRequest,
+        schedule: &MortgageSchedule,
+        payments: &[ReceivedPayment],
+    ) -> Result<InterestStatement, CalculationError> {
+        let out_of_range = || CalculationError::InvalidInput(format!("tax year {} is out of range", request.tax_year));
+        let year_start = NaiveDate::from_ymd_opt(request.tax_year, 1, 1).ok_or_else(out_of_range)?;
+        let year_end = NaiveDate::from_ymd_opt(request.tax_year, 12, 31).ok_or_else(out_of_range)?;
+
+        let history = PaymentHistoryReconciler::reconcile(&PaymentHistoryRequest {
+            input: request.input.clone(),
+            payments: payments.to_vec(),
+            as_of_date: year_end,
+            late_fee: request.late_fee.clone(),
+            day_count: request.day_count,
+        })?;
+
+        let mut statement = Self::statement_for(
+            &request.input,
+            request.tax_year,
+            request.points_paid,
+            StatementSource::PaymentHistory,
+        );
+        let mut balance = request.input.principal;
+        let mut balance_at_year_start = balance;
+        for application in &history.applications {
+            balance -= application.principal + application.principal_curtailment;
+            if application.received_date < year_start {
+                balance_at_year_start = balance;
+                continue;
+            }
+
+            statement.mortgage_interest += application.interest;
+            statement.number_of_payments += application.installments_paid.len() as u32;
+            // Escrow also holds taxes and hazard insurance, so take premiums from the installments paid
+            statement.mortgage_insurance_premiums += application.installments_paid.iter()
+                .filter_map(|payment_number| schedule.schedule.get(*payment_number as usize - 1))
+                .map(|entry| DecimalUtils::round_currency(entry.mortgage_insurance.unwrap_or(Decimal::ZERO)))
+                .sum::<Decimal>();
+        }
+
+        if request.input.start_date.year() < request.tax_year {
+            statement.outstanding_principal = DecimalUtils::round_currency(balance_at_year_start);
+        } else {
+            statement.outstanding_principal = request.input.principal;
+        }
+        statement.ending_principal = DecimalUtils::round_currency(balance);
+        Ok(statement)
+    }
+
+    /// Empty statement with the boxes that depend only on the loan terms filled in
+    fn statement_for(
+        input: &MortgageInput,
+        tax_year: i32,
+        points_paid: Decimal,
+        source: StatementSource,
+    ) -> InterestStatement {
+        InterestStatement {
+            tax_year,
+            source,
+            mortgage_interest: Decimal::ZERO,
+            outstanding_principal: Decimal::ZERO,
+            origination_date: input.start_date,
+            mortgage_insurance_premiums: Decimal::ZERO,
+            points_paid: if input.start_date.year() == tax_year { points_paid } else { Decimal::ZERO },
+            number_of_payments: 0,
+            ending_principal: Decimal::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+    use crate::models::{
+        DayCountConvention,
+        LateFeePolicy,
+        MortgageInsuranceInput,
+        MortgageInsuranceType,
+        RepaymentType,
+    };
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn statement_request(tax_year: i32, payments: Option<Vec<(NaiveDate, Decimal)>>) -> InterestStatementRequest {
+        InterestStatementRequest {
+            input: MortgageInput::new(
+                dec!(200000),
+                dec!(0.06),
+                30,
+                RepaymentType::StandardPrincipalAndInterest,
+                date(2024, 1, 1),
+            ),
+            tax_year,
+            points_paid: dec!(2000),
+            payments: payments.map(|payments| payments.into_iter()
+                .map(|(received_date, amount)| ReceivedPayment { received_date, amount })
+                .collect()),
+            late_fee: LateFeePolicy::default(),
+            day_count: DayCountConvention::Actual360,
+        }
+    }
+
+    /// Due dates step 30 days from the start date
+    fn due_date(payment_number: i64) -> NaiveDate {
+        date(2024, 1, 1) + Duration::days(30 * (payment_number - 1))
+    }
+
+    // 200,000 at 6% over 30 years
+    const INSTALLMENT: Decimal = dec!(1199.10);
+
+    #[test]
+    fn test_schedule_statement_covers_payments_due_in_year() {
+        let request = statement_request(2025, None);
+        let schedule = calculate_schedule(&request.input).unwrap();
+        let statement = InterestStatementCalculator::statement(&request).unwrap();
+
+        let in_year: Vec<_> = schedule.schedule.iter().filter(|entry| entry.payment_date.year() == 2025).collect();
+        let prior = schedule.schedule.iter().rfind(|entry| entry.payment_date.year() < 2025).unwrap();
+        assert_eq!(statement.source, StatementSource::Schedule);
+        assert_eq!(statement.number_of_payments as usize, in_year.len());
+        assert_eq!(
+            statement.mortgage_interest,
+            in_year.iter().map(|entry| DecimalUtils::round_currency(entry.interest_component)).sum::<Decimal>()
+        );
+        assert_eq!(statement.outstanding_principal, DecimalUtils::round_currency(prior.remaining_principal));
+        assert_eq!(statement.ending_principal, DecimalUtils::round_currency(in_year.last().unwrap().remaining_principal));
+        assert_eq!(statement.points_paid, Decimal::ZERO);
+        assert_eq!(statement.origination_date, date(2024, 1, 1));
+    }
+
+    #[test]
+    fn test_origination_year_reports_points_and_original_principal() {
+        let statement = InterestStatementCalculator::statement(&statement_request(2024, None)).unwrap();
+
+        assert_eq!(statement.points_paid, dec!(2000));
+        assert_eq!(statement.outstanding_principal, dec!(200000));
+        assert_eq!(statement.mortgage_insurance_premiums, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_history_counts_payments_in_year_received() {
+        // Every installment through the end of 2025 paid on time, except the last one of 2025 paid in January
+        let scheduled = InterestStatementCalculator::statement(&statement_request(2025, None)).unwrap();
+        let due_in_2025 = (1..).map(due_date).take_while(|due| due.year() <= 2025).count() as i64;
+        let mut payments: Vec<_> = (1..due_in_2025).map(|n| (due_date(n), INSTALLMENT)).collect();
+        payments.push((date(2026, 1, 5), INSTALLMENT));
+
+        let on_time = InterestStatementCalculator::statement(&statement_request(2025, Some(payments.clone()))).unwrap();
+        assert_eq!(on_time.source, StatementSource::PaymentHistory);
+        assert_eq!(on_time.number_of_payments, scheduled.number_of_payments - 1);
+        assert!((on_time.outstanding_principal - scheduled.outstanding_principal).abs() <= dec!(0.05));
+        assert!(on_time.mortgage_interest < scheduled.mortgage_interest);
+
+        let following = InterestStatementCalculator::statement(&statement_request(2026, Some(payments))).unwrap();
+        assert_eq!(following.number_of_payments, 1);
+        assert_eq!(following.outstanding_principal, on_time.ending_principal);
+    }
+
+    #[test]
+    fn test_mortgage_insurance_premiums_paid_in_year() {
+        let mut request = statement_request(2025, None);
+        request.input.principal = dec!(285000);
+        request.input.property_value = Some(dec!(300000));
+        request.input.mortgage_insurance = Some(MortgageInsuranceInput {
+            insurance_type: MortgageInsuranceType::PrivateMortgageInsurance,
+            credit_score: Some(745),
+            annual_rate: None,
+            borrower_requested_cancellation: false,
+        });
+        let schedule = calculate_schedule(&request.input).unwrap();
+        let statement = InterestStatementCalculator::statement(&request).unwrap();
+
+        let premium = DecimalUtils::round_currency(schedule.schedule[0].mortgage_insurance.unwrap());
+        assert!(premium > Decimal::ZERO);
+        assert_eq!(statement.mortgage_insurance_premiums, premium * Decimal::from(statement.number_of_payments));
+    }
+}
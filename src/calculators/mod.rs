@@ -3,16 +3,221 @@ mod interest_only;
 mod accelerated;
 mod balloon;
 mod floating;
+mod escrow;
+mod mortgage_insurance;
+mod affordability;
+mod solver;
+mod refinance;
+mod comparison;
+mod rent_vs_buy;
+mod payoff;
+mod prepayment_penalty;
+mod payment_history;
+mod modification;
+mod pool;
+mod batch;
+mod analytics;
+mod valuation;
+mod registry;
+mod amortization;
+mod schedule_aggregator;
+mod interest_statement;
 
 pub use standard::StandardCalculator;
 pub use interest_only::InterestOnlyCalculator;
 pub use accelerated::AcceleratedCalculator;
 pub use balloon::BalloonCalculator;
 pub use floating::FloatingRateCalculator;
+pub use escrow::EscrowCalculator;
+pub use mortgage_insurance::MortgageInsuranceCalculator;
+pub use affordability::AffordabilityCalculator;
+pub use solver::{GoalSeekSolver, SolveError};
+pub use refinance::{RefinanceCalculator, RefinanceError};
+pub use comparison::ComparisonCalculator;
+pub use rent_vs_buy::RentVsBuyCalculator;
+pub use payoff::{PayoffCalculator, PayoffError};
+pub use prepayment_penalty::PrepaymentPenaltyCalculator;
+pub use payment_history::PaymentHistoryReconciler;
+pub use modification::ModificationCalculator;
+pub use pool::PoolCalculator;
+pub use batch::{BatchCalculator, BatchError, BatchItem};
+pub use analytics::{AnalyticsCalculator, AnalyticsError};
+pub use valuation::{ValuationCalculator, ValuationError};
+pub use schedule_aggregator::ScheduleAggregator;
+pub use interest_statement::InterestStatementCalculator;
+pub use registry::{
+    calculator_for, register_calculator, registry, repayment_type_for, repayment_type_infos, repayment_types,
+    CalculatorRegistry,
+};
 
-use crate::models::{MortgageInput, MortgageSchedule, MortgageSummary};
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use thiserror::Error;
+use validator::Validate;
 
-pub trait MortgageCalculator {
-    fn calculate_schedule(input: &MortgageInput) -> MortgageSchedule;
-    fn calculate_summary(input: &MortgageInput) -> MortgageSummary;
+use crate::models::{
+    MortgageInput, MortgageSchedule, MortgageSummary, PaymentScheduleEntry, RepaymentType, RepaymentTypeInfo,
+};
+use crate::utils::DecimalUtils;
+
+/// Reasons a schedule cannot be calculated
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CalculationError {
+    #[error("invalid loan: {0}")]
+    InvalidInput(String),
+
+    #[error("{0} overflowed")]
+    Overflow(&'static str),
+
+    #[error("no calculator is registered for {0}")]
+    UnregisteredRepaymentType(RepaymentType),
+}
+
+/// Schedule entries produced one at a time
+pub type ScheduleEntries = Box<dyn Iterator<Item = PaymentScheduleEntry> + Send>;
+
+/// Prices one repayment type; object safe so products can be registered at runtime
+pub trait MortgageCalculator: Send + Sync {
+    /// Repayment type this calculator is registered under
+    fn repayment_type(&self) -> RepaymentType;
+
+    /// One-sentence description listed by `/repayment-types`
+    fn description(&self) -> &str;
+
+    fn calculate_schedule(&self, input: &MortgageInput) -> Result<MortgageSchedule, CalculationError>;
+
+    /// Streams the schedule one payment at a time
+    fn schedule_entries(&self, input: &MortgageInput) -> Result<ScheduleEntries, CalculationError> {
+        Ok(Box::new(self.calculate_schedule(input)?.schedule.into_iter()))
+    }
+
+    /// Summarizes a schedule this calculator produced for `input`
+    fn summarize(&self, input: &MortgageInput, schedule: &MortgageSchedule) -> MortgageSummary {
+        MortgageSummary::new(
+            self.repayment_type(),
+            schedule.monthly_payment,
+            schedule.total_payments,
+            schedule.total_interest,
+            input.principal,
+            input.annual_interest_rate,
+            schedule.payment_count() as u32,
+        )
+        .with_escrow_from(schedule)
+        .with_mortgage_insurance_from(schedule)
+        .with_prepayment_penalty_from(schedule)
+        .with_modifications_from(schedule)
+    }
+
+    fn calculate_summary(&self, input: &MortgageInput) -> Result<MortgageSummary, CalculationError> {
+        Ok(self.summarize(input, &self.calculate_schedule(input)?))
+    }
+
+    /// Name and payment terms come from the repayment type itself
+    fn info(&self) -> RepaymentTypeInfo {
+        let repayment_type = self.repayment_type();
+        RepaymentTypeInfo {
+            repayment_type,
+            name: repayment_type.name().to_string(),
+            description: self.description().to_string(),
+            requires_balloon_percentage: repayment_type.requires_balloon_percentage(),
+        }
+    }
+}
+
+/// Calculates the schedule with the calculator registered for the input's repayment type
+pub fn calculate_schedule(input: &MortgageInput) -> Result<MortgageSchedule, CalculationError> {
+    registered(input.repayment_type)?.calculate_schedule(input)
+}
+
+/// Calculates the summary with the calculator registered for the input's repayment type
+pub fn calculate_summary(input: &MortgageInput) -> Result<MortgageSummary, CalculationError> {
+    registered(input.repayment_type)?.calculate_summary(input)
+}
+
+/// Summarizes a schedule already calculated for the input, consistently with its totals
+pub fn summarize_schedule(input: &MortgageInput, schedule: &MortgageSchedule) -> Result<MortgageSummary, CalculationError> {
+    Ok(registered(input.repayment_type)?.summarize(input, schedule))
+}
+
+/// Streams the schedule of the calculator registered for the input's repayment type
+pub fn schedule_entries(input: &MortgageInput) -> Result<ScheduleEntries, CalculationError> {
+    registered(input.repayment_type)?.schedule_entries(input)
+}
+
+fn registered(repayment_type: RepaymentType) -> Result<std::sync::Arc<dyn MortgageCalculator>, CalculationError> {
+    calculator_for(repayment_type).ok_or(CalculationError::UnregisteredRepaymentType(repayment_type))
+}
+
+/// Rejects inputs outside the field ranges the schedule arithmetic is bounded by
+pub(crate) fn check_input(input: &MortgageInput) -> Result<(), CalculationError> {
+    input.validate().map_err(|errors| CalculationError::InvalidInput(errors.to_string()))?;
+    // Every scheduled payment falls due within the term
+    input.start_date.checked_add_signed(Duration::days(i64::from(input.term_years) * 366))
+        .map(|_| ())
+        .ok_or(CalculationError::Overflow("payment date"))
+}
+
+/// Due date of a payment made every `interval_days` from `start`
+pub(crate) fn payment_date(start: NaiveDate, payment_number: u32, interval_days: i64) -> Result<NaiveDate, CalculationError> {
+    let days = i64::from(payment_number.saturating_sub(1)) * interval_days;
+    start.checked_add_signed(Duration::days(days))
+        .ok_or(CalculationError::Overflow("payment date"))
+}
+
+/// Level payment per unit of balance, failing where `(1 + rate)^payments` overflows
+pub(crate) fn payment_factor(periodic_rate: Decimal, payments: u32) -> Result<Decimal, CalculationError> {
+    DecimalUtils::checked_payment_factor(periodic_rate, payments)
+        .ok_or(CalculationError::Overflow("payment factor"))
+}
+
+/// Annual rate in force for a schedule entry; floating schedules report it as a percentage
+pub(crate) fn accrual_rate(input: &MortgageInput, entry: &PaymentScheduleEntry) -> Decimal {
+    match (input.repayment_type, entry.current_rate) {
+        (RepaymentType::FloatingRate, Some(rate)) => rate / dec!(100),
+        (_, rate) => rate.unwrap_or(input.annual_interest_rate),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loan(repayment_type: RepaymentType) -> MortgageInput {
+        MortgageInput {
+            balloon_payment_percentage: if repayment_type == RepaymentType::BalloonPayment { dec!(30) } else { dec!(0) },
+            ..MortgageInput::new(
+                dec!(250000),
+                dec!(0.055),
+                15,
+                repayment_type,
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_dispatch_matches_each_calculator() {
+        // The shared dispatch must produce what each calculator produces directly
+        let calculators: [&dyn MortgageCalculator; 4] =
+            [&StandardCalculator, &InterestOnlyCalculator, &AcceleratedCalculator, &BalloonCalculator];
+        for calculator in calculators {
+            let input = loan(calculator.repayment_type());
+            assert_eq!(
+                serde_json::to_value(calculate_schedule(&input).unwrap()).unwrap(),
+                serde_json::to_value(calculator.calculate_schedule(&input).unwrap()).unwrap(),
+            );
+            assert_eq!(
+                serde_json::to_value(calculate_summary(&input).unwrap()).unwrap(),
+                serde_json::to_value(calculator.calculate_summary(&input).unwrap()).unwrap(),
+            );
+        }
+
+        // Floating rates are redrawn on every calculation, so only the shape can match
+        let input = loan(RepaymentType::FloatingRate);
+        let schedule = calculate_schedule(&input).unwrap();
+        assert_eq!(schedule.payment_count(), 180);
+        assert!(schedule.schedule.iter().all(|entry| entry.current_rate.is_some()));
+        assert_eq!(calculate_summary(&input).unwrap().repayment_type, RepaymentType::FloatingRate);
+    }
 }
@@ -0,0 +1,229 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::models::{
+    ForbearanceInterest,
+    LoanModification,
+    MortgageInput,
+    MortgageSchedule,
+    PaymentScheduleEntry,
+    RepaymentType,
+};
+use super::{accrual_rate, payment_date, payment_factor, CalculationError};
+
+/// Largest balance interest may capitalize or defer into before the schedule gives up
+const MAX_BALANCE: Decimal = dec!(100_000_000_000_000_000_000);
+
+/// Applies forbearance and modification events to a schedule
+pub struct ModificationCalculator;
+
+impl ModificationCalculator {
+    /// Rebuilds `schedule` from the first modification event onwards
+    ///
+    /// From that point the loan amortizes in level payments at the latest rate
+    /// over the payments left; events re-amortize whatever balance remains.
+    pub fn apply(input: &MortgageInput, schedule: &mut MortgageSchedule) -> Result<(), CalculationError> {
+        let mut events: Vec<&LoanModification> = input.modifications.iter().collect();
+        events.sort_by_key(|event| event.start_payment());
+        let first_payment = match events.first() {
+            Some(event) if (event.start_payment() as usize) <= schedule.schedule.len() => event.start_payment(),
+            _ => return Ok(()),
+        };
+
+        let biweekly = input.repayment_type == RepaymentType::AcceleratedBiweekly;
+        let (interval_days, periods_per_month, rate_divisor) = if biweekly {
+            (14, dec!(26) / dec!(12), dec!(24))
+        } else {
+            (30, dec!(1), dec!(12))
+        };
+        let periods = |months: u32| (Decimal::from(months) * periods_per_month).ceil();
+
+        schedule.schedule.truncate(first_payment as usize - 1);
+        let last_paid = schedule.schedule.last();
+        let mut balance = last_paid.map_or(input.principal, |entry| entry.remaining_principal);
+        let mut annual_rate = last_paid.map_or(input.annual_interest_rate, |entry| accrual_rate(input, entry));
+        let mut remaining = Decimal::from(
+            (input.term_years * input.repayment_type.payments_per_year()).saturating_sub(first_payment - 1)
+        );
+
+        let mut deferred = Decimal::ZERO;
+        let mut largest_deferred = Decimal::ZERO;
+        let mut forbearance = Decimal::ZERO;
+        let mut forbearance_interest = ForbearanceInterest::Capitalized;
+        let mut payment = Decimal::ZERO;
+        let mut reamortize = true;
+        let mut payment_number = first_payment;
+        let mut pending = events.into_iter().peekable();
+
+        while balance > Decimal::ZERO || deferred > Decimal::ZERO {
+            let mut applied = Vec::new();
+            while let Some(event) = pending.next_if(|event| event.start_payment() <= payment_number) {
+                match event {
+                    LoanModification::Forbearance { months, interest, .. } => {
+                        forbearance = periods(*months);
+                        forbearance_interest = *interest;
+                    }
+                    LoanModification::TermExtension { additional_months, .. } =>
+                        remaining += periods(*additional_months),
+                    LoanModification::RateReduction { annual_interest_rate, .. } =>
+                        annual_rate = *annual_interest_rate,
+                    LoanModification::PrincipalForbearance { amount, .. } => {
+                        let moved = (*amount).min(balance);
+                        balance -= moved;
+                        deferred += moved;
+                    }
+                }
+                reamortize = true;
+                applied.push(event.clone());
+            }
+
+            let periodic_rate = annual_rate / rate_divisor;
+            let interest = balance * periodic_rate;
+            let (payment_amount, principal_component, interest_component) = if forbearance > Decimal::ZERO {
+                // Payments are suspended; interest builds up instead of being paid
+                forbearance -= dec!(1);
+                match forbearance_interest {
+                    ForbearanceInterest::Capitalized => balance += interest,
+                    ForbearanceInterest::Deferred => deferred += interest,
+                }
+                if balance.max(deferred) > MAX_BALANCE {
+                    return Err(CalculationError::Overflow("capitalized interest"));
+                }
+                reamortize = true;
+                (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO)
+            } else {
+                let remaining_payments = remaining.max(dec!(1));
+                if reamortize {
+                    let payments = remaining_payments.try_into().unwrap_or(u32::MAX);
+                    payment = balance * payment_factor(periodic_rate, payments)?;
+                    reamortize = false;
+                }
+                let principal = if remaining_payments == dec!(1) {
+                    balance
+                } else {
+                    (payment - interest + input.extra_payment).min(balance)
+                };
+                balance -= principal;
+                remaining = remaining_payments - dec!(1);
+
+                // The deferred balance falls due with the final payment
+                let deferred_paid = if balance <= Decimal::ZERO { deferred } else { Decimal::ZERO };
+                deferred -= deferred_paid;
+                (principal + interest + deferred_paid, principal, interest)
+            };
+            largest_deferred = largest_deferred.max(deferred);
+
+            schedule.schedule.push(PaymentScheduleEntry {
+                payment_date: payment_date(input.start_date, payment_number, interval_days)?,
+                payment_number,
+                payment_amount,
+                principal_component,
+                interest_component,
+                remaining_principal: balance,
+                // Floating schedules report rates as percentages
+                current_rate: Some(match input.repayment_type {
+                    RepaymentType::FloatingRate => annual_rate * dec!(100),
+                    _ => annual_rate,
+                }),
+                escrow_deposit: None,
+                escrow_balance: None,
+                mortgage_insurance: None,
+                prepayment_penalty: None,
+                deferred_balance: Some(deferred),
+                modifications: applied,
+            });
+            payment_number += 1;
+        }
+
+        // Capitalized and deferred interest is paid too, so count everything above principal
+        schedule.total_payments = schedule.schedule.iter().map(|entry| entry.payment_amount).sum();
+        schedule.total_interest = schedule.total_payments - input.principal;
+        schedule.deferred_balance = Some(largest_deferred);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::calculators::{calculate_schedule, calculate_summary};
+
+    fn modified_input(modifications: Vec<LoanModification>) -> MortgageInput {
+        MortgageInput {
+            modifications,
+            ..MortgageInput::new(
+                dec!(300000),
+                dec!(0.07),
+                30,
+                RepaymentType::StandardPrincipalAndInterest,
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_rate_reduction_lowers_payment() {
+        let original = calculate_schedule(&modified_input(Vec::new())).unwrap();
+        let modified = calculate_schedule(&modified_input(vec![
+            LoanModification::RateReduction { start_payment: 61, annual_interest_rate: dec!(0.04) },
+        ])).unwrap();
+
+        assert_eq!(modified.payment_count(), 360);
+        assert!(modified.schedule[59].modifications.is_empty());
+        assert_eq!(modified.schedule[60].modifications.len(), 1);
+        assert!(modified.schedule[60].payment_amount < original.schedule[60].payment_amount);
+        assert_eq!(modified.schedule.last().unwrap().remaining_principal, Decimal::ZERO);
+        assert!(modified.total_interest < original.total_interest);
+    }
+
+    #[test]
+    fn test_capitalized_forbearance_grows_balance() {
+        let schedule = calculate_schedule(&modified_input(vec![
+            LoanModification::Forbearance {
+                start_payment: 25,
+                months: 6,
+                interest: ForbearanceInterest::Capitalized,
+            },
+        ])).unwrap();
+        let before = schedule.schedule[23].remaining_principal;
+
+        assert_eq!(schedule.payment_count(), 366);
+        assert!(schedule.schedule[24..30].iter().all(|entry| entry.payment_amount.is_zero()));
+        assert!(schedule.schedule[29].remaining_principal > before);
+        assert_eq!(schedule.deferred_balance, Some(Decimal::ZERO));
+    }
+
+    #[test]
+    fn test_deferred_forbearance_due_at_maturity() {
+        let input = modified_input(vec![
+            LoanModification::Forbearance {
+                start_payment: 25,
+                months: 6,
+                interest: ForbearanceInterest::Deferred,
+            },
+        ]);
+        let schedule = calculate_schedule(&input).unwrap();
+        let summary = calculate_summary(&input).unwrap();
+        let deferred = schedule.schedule[29].deferred_balance.unwrap();
+
+        assert_eq!(schedule.schedule[29].remaining_principal, schedule.schedule[23].remaining_principal);
+        assert_eq!(summary.deferred_balance, Some(deferred));
+        let last = schedule.schedule.last().unwrap();
+        assert_eq!(last.payment_amount, last.principal_component + last.interest_component + deferred);
+        assert_eq!(last.deferred_balance, Some(Decimal::ZERO));
+    }
+
+    #[test]
+    fn test_principal_forbearance_and_term_extension() {
+        let schedule = calculate_schedule(&modified_input(vec![
+            LoanModification::PrincipalForbearance { start_payment: 121, amount: dec!(50000) },
+            LoanModification::TermExtension { start_payment: 121, additional_months: 120 },
+        ])).unwrap();
+
+        assert_eq!(schedule.payment_count(), 480);
+        assert_eq!(schedule.schedule[120].modifications.len(), 2);
+        assert_eq!(schedule.deferred_balance, Some(dec!(50000)));
+        assert!(schedule.schedule.last().unwrap().payment_amount > dec!(50000));
+    }
+}
@@ -0,0 +1,312 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::models::{MortgageInput, MortgageInsuranceInput, MortgageInsuranceType, MortgageSchedule};
+use crate::utils::DecimalUtils;
+use super::{calculate_schedule, CalculationError};
+
+/// Balance, as a share of original value, at which PMI terminates automatically (HPA)
+const AUTOMATIC_TERMINATION_LTV: Decimal = dec!(0.78);
+
+/// Balance, as a share of original value, at which the borrower may request cancellation (HPA)
+const REQUESTED_CANCELLATION_LTV: Decimal = dec!(0.80);
+
+/// FHA upfront mortgage insurance premium as a share of the base loan amount
+const FHA_UPFRONT_RATE: Decimal = dec!(0.0175);
+
+/// Base loan amount above which the FHA high-balance annual MIP table applies
+const FHA_HIGH_BALANCE_THRESHOLD: Decimal = dec!(726200);
+
+/// Minimum credit scores for each PMI pricing tier, best tier first
+const PMI_CREDIT_TIERS: [u32; 5] = [760, 740, 720, 700, 680];
+
+/// Annual PMI rates by LTV band (rows) and credit tier (columns, last is below 680)
+const PMI_RATES: [(Decimal, [Decimal; 6]); 4] = [
+    (dec!(0.85), [dec!(0.0019), dec!(0.0020), dec!(0.0023), dec!(0.0027), dec!(0.0032), dec!(0.0048)]),
+    (dec!(0.90), [dec!(0.0028), dec!(0.0038), dec!(0.0046), dec!(0.0055), dec!(0.0065), dec!(0.0090)]),
+    (dec!(0.95), [dec!(0.0038), dec!(0.0053), dec!(0.0066), dec!(0.0078), dec!(0.0096), dec!(0.0128)]),
+    (dec!(1.00), [dec!(0.0058), dec!(0.0070), dec!(0.0087), dec!(0.0099), dec!(0.0121), dec!(0.0154)]),
+];
+
+/// Adds PMI or FHA mortgage insurance premiums to a schedule
+pub struct MortgageInsuranceCalculator;
+
+impl MortgageInsuranceCalculator {
+    /// Fills the mortgage insurance column of `schedule` when `input` carries insurance terms
+    pub fn apply(input: &MortgageInput, schedule: &mut MortgageSchedule) -> Result<(), CalculationError> {
+        let (insurance, property_value) = match (&input.mortgage_insurance, input.property_value) {
+            (Some(insurance), Some(property_value)) => (insurance, property_value),
+            _ => return Ok(()),
+        };
+        let ltv = match input.original_ltv() {
+            Some(ltv) => ltv,
+            None => return Ok(()),
+        };
+
+        match insurance.insurance_type {
+            MortgageInsuranceType::PrivateMortgageInsurance =>
+                Self::apply_pmi(input, insurance, property_value, ltv, schedule)?,
+            MortgageInsuranceType::Fha =>
+                Self::apply_fha(input, insurance, ltv, schedule),
+        }
+        Ok(())
+    }
+
+    /// Annual PMI rate for an original LTV and credit score, or `None` at or below 80% LTV
+    pub fn pmi_rate(ltv: Decimal, credit_score: Option<u32>) -> Option<Decimal> {
+        if ltv <= REQUESTED_CANCELLATION_LTV {
+            return None;
+        }
+        let tier = credit_score
+            .and_then(|score| PMI_CREDIT_TIERS.iter().position(|minimum| score >= *minimum))
+            .unwrap_or(PMI_CREDIT_TIERS.len());
+        let rates = PMI_RATES.iter()
+            .find(|(max_ltv, _)| ltv <= *max_ltv)
+            .map(|(_, rates)| rates)
+            .unwrap_or(&PMI_RATES[PMI_RATES.len() - 1].1);
+        Some(rates[tier])
+    }
+
+    /// Annual FHA MIP rate for an original LTV, term and base loan amount
+    pub fn fha_annual_rate(ltv: Decimal, term_years: u32, base_loan_amount: Decimal) -> Decimal {
+        let high_balance = base_loan_amount > FHA_HIGH_BALANCE_THRESHOLD;
+        match (term_years > 15, high_balance) {
+            (true, false) => if ltv <= dec!(0.95) { dec!(0.0050) } else { dec!(0.0055) },
+            (true, true) => if ltv <= dec!(0.95) { dec!(0.0070) } else { dec!(0.0075) },
+            (false, false) => if ltv <= dec!(0.90) { dec!(0.0015) } else { dec!(0.0040) },
+            (false, true) => {
+                if ltv <= dec!(0.78) {
+                    dec!(0.0015)
+                } else if ltv <= dec!(0.90) {
+                    dec!(0.0040)
+                } else {
+                    dec!(0.0065)
+                }
+            }
+        }
+    }
+
+    /// Years of annual FHA MIP for an original LTV, or `None` for the life of the loan
+    pub fn fha_premium_years(ltv: Decimal) -> Option<u32> {
+        if ltv <= dec!(0.90) { Some(11) } else { None }
+    }
+
+    fn apply_pmi(
+        input: &MortgageInput,
+        insurance: &MortgageInsuranceInput,
+        property_value: Decimal,
+        ltv: Decimal,
+        schedule: &mut MortgageSchedule,
+    ) -> Result<(), CalculationError> {
+        let annual_rate = match insurance.annual_rate.or_else(|| Self::pmi_rate(ltv, insurance.credit_score)) {
+            Some(rate) => rate,
+            None => return Ok(()),
+        };
+        let payments_per_year = input.repayment_type.payments_per_year();
+        let monthly_premium = DecimalUtils::round_currency(input.principal * annual_rate / dec!(12));
+        let periodic_premium = DecimalUtils::round_currency(
+            monthly_premium * dec!(12) / Decimal::from(payments_per_year)
+        );
+
+        let termination_ltv = if insurance.borrower_requested_cancellation {
+            REQUESTED_CANCELLATION_LTV
+        } else {
+            AUTOMATIC_TERMINATION_LTV
+        };
+        let termination_balance = property_value * termination_ltv;
+        // Final termination at the midpoint of the amortization period
+        let midpoint = input.term_years * payments_per_year / 2;
+
+        // Automatic termination follows the amortization schedule whatever extra principal is
+        // paid; a borrower may request cancellation once the actual balance is low enough
+        let closing_balances = if insurance.borrower_requested_cancellation || input.extra_payment.is_zero() {
+            schedule.schedule.iter().map(|entry| entry.remaining_principal).collect()
+        } else {
+            Self::scheduled_balances(input)?
+        };
+
+        let mut balance = input.principal;
+        let mut end_date = None;
+        for (index, entry) in schedule.schedule.iter_mut().enumerate() {
+            let terminated = balance <= termination_balance || entry.payment_number > midpoint;
+            if terminated && end_date.is_none() {
+                end_date = Some(entry.payment_date);
+            }
+            entry.mortgage_insurance = Some(if end_date.is_some() { Decimal::ZERO } else { periodic_premium });
+            balance = closing_balances.get(index).copied().unwrap_or(Decimal::ZERO);
+        }
+
+        // No premium is ever charged on a loan already at the termination LTV
+        let charged = end_date != schedule.schedule.first().map(|entry| entry.payment_date);
+        schedule.monthly_mortgage_insurance = charged.then_some(monthly_premium);
+        schedule.mortgage_insurance_end_date = end_date;
+        Ok(())
+    }
+
+    /// Closing balances of the loan's schedule without extra principal
+    fn scheduled_balances(input: &MortgageInput) -> Result<Vec<Decimal>, CalculationError> {
+        let scheduled = MortgageInput {
+            extra_payment: Decimal::ZERO,
+            escrow: None,
+            mortgage_insurance: None,
+            prepayment_penalty: None,
+            ..input.clone()
+        };
+        Ok(calculate_schedule(&scheduled)?.schedule.into_iter().map(|entry| entry.remaining_principal).collect())
+    }
+
+    fn apply_fha(
+        input: &MortgageInput,
+        insurance: &MortgageInsuranceInput,
+        ltv: Decimal,
+        schedule: &mut MortgageSchedule,
+    ) {
+        let annual_rate = insurance.annual_rate
+            .unwrap_or_else(|| Self::fha_annual_rate(ltv, input.term_years, input.principal));
+        let payments_per_year = input.repayment_type.payments_per_year() as usize;
+        let premium_payments = Self::fha_premium_years(ltv)
+            .map(|years| years as usize * payments_per_year);
+
+        let mut balance = input.principal;
+        let mut opening_balances = Vec::with_capacity(schedule.schedule.len());
+        for entry in &schedule.schedule {
+            opening_balances.push(balance);
+            balance = entry.remaining_principal;
+        }
+
+        let mut end_date = None;
+        let mut monthly_premium = None;
+        for block_start in (0..opening_balances.len()).step_by(payments_per_year) {
+            let block_end = (block_start + payments_per_year).min(opening_balances.len());
+            let block = &opening_balances[block_start..block_end];
+            let annual_premium = block.iter().sum::<Decimal>() / Decimal::from(block.len()) * annual_rate;
+            let periodic_premium = DecimalUtils::round_currency(annual_premium / Decimal::from(payments_per_year));
+            monthly_premium.get_or_insert(DecimalUtils::round_currency(annual_premium / dec!(12)));
+
+            for (offset, entry) in schedule.schedule[block_start..block_end].iter_mut().enumerate() {
+                let terminated = premium_payments.is_some_and(|limit| block_start + offset >= limit);
+                if terminated && end_date.is_none() {
+                    end_date = Some(entry.payment_date);
+                }
+                entry.mortgage_insurance = Some(if terminated { Decimal::ZERO } else { periodic_premium });
+            }
+        }
+
+        schedule.monthly_mortgage_insurance = monthly_premium;
+        schedule.upfront_mortgage_insurance = Some(DecimalUtils::round_currency(input.principal * FHA_UPFRONT_RATE));
+        schedule.mortgage_insurance_end_date = end_date;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::calculators::{MortgageCalculator, StandardCalculator};
+    use crate::models::RepaymentType;
+
+    fn insured_input(insurance_type: MortgageInsuranceType, property_value: Decimal) -> MortgageInput {
+        MortgageInput {
+            property_value: Some(property_value),
+            mortgage_insurance: Some(MortgageInsuranceInput {
+                insurance_type,
+                credit_score: Some(745),
+                annual_rate: None,
+                borrower_requested_cancellation: false,
+            }),
+            ..MortgageInput::new(
+                dec!(285000),
+                dec!(0.06),
+                30,
+                RepaymentType::StandardPrincipalAndInterest,
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_pmi_rate_table() {
+        assert_eq!(MortgageInsuranceCalculator::pmi_rate(dec!(0.80), Some(800)), None);
+        assert_eq!(MortgageInsuranceCalculator::pmi_rate(dec!(0.95), Some(745)), Some(dec!(0.0053)));
+        assert_eq!(MortgageInsuranceCalculator::pmi_rate(dec!(0.97), None), Some(dec!(0.0154)));
+    }
+
+    #[test]
+    fn test_pmi_terminates_at_78_percent() {
+        let input = insured_input(MortgageInsuranceType::PrivateMortgageInsurance, dec!(300000));
+        let schedule = StandardCalculator.calculate_schedule(&input).unwrap();
+
+        let end_date = schedule.mortgage_insurance_end_date.unwrap();
+        let last_charged = schedule.schedule.iter()
+            .rev()
+            .find(|entry| entry.mortgage_insurance.unwrap() > Decimal::ZERO)
+            .unwrap();
+        assert!(last_charged.payment_date < end_date);
+        assert!(last_charged.remaining_principal <= dec!(234000));
+        assert_eq!(schedule.schedule[0].mortgage_insurance, Some(dec!(125.88)));
+    }
+
+    #[test]
+    fn test_no_premium_at_or_below_termination_ltv() {
+        let mut input = insured_input(MortgageInsuranceType::PrivateMortgageInsurance, dec!(400000));
+        input.mortgage_insurance.as_mut().unwrap().annual_rate = Some(dec!(0.005));
+        let schedule = StandardCalculator.calculate_schedule(&input).unwrap();
+        let summary = StandardCalculator.calculate_summary(&input).unwrap();
+
+        assert_eq!(schedule.monthly_mortgage_insurance, None);
+        assert_eq!(schedule.mortgage_insurance_end_date, Some(schedule.schedule[0].payment_date));
+        assert!(schedule.schedule.iter().all(|entry| entry.mortgage_insurance == Some(Decimal::ZERO)));
+        assert_eq!(summary.total_monthly_payment, summary.monthly_payment);
+    }
+
+    #[test]
+    fn test_extra_payments_only_advance_requested_cancellation() {
+        let mut input = insured_input(MortgageInsuranceType::PrivateMortgageInsurance, dec!(300000));
+        let automatic = StandardCalculator.calculate_schedule(&input).unwrap();
+        input.extra_payment = dec!(500);
+        let automatic_with_extra = StandardCalculator.calculate_schedule(&input).unwrap();
+
+        assert_eq!(automatic_with_extra.mortgage_insurance_end_date, automatic.mortgage_insurance_end_date);
+
+        input.mortgage_insurance.as_mut().unwrap().borrower_requested_cancellation = true;
+        let requested_with_extra = StandardCalculator.calculate_schedule(&input).unwrap();
+        input.extra_payment = Decimal::ZERO;
+        let requested = StandardCalculator.calculate_schedule(&input).unwrap();
+
+        assert!(requested_with_extra.mortgage_insurance_end_date < requested.mortgage_insurance_end_date);
+    }
+
+    #[test]
+    fn test_requested_cancellation_ends_earlier() {
+        let automatic = StandardCalculator.calculate_schedule(
+            &insured_input(MortgageInsuranceType::PrivateMortgageInsurance, dec!(300000))
+        ).unwrap();
+        let mut input = insured_input(MortgageInsuranceType::PrivateMortgageInsurance, dec!(300000));
+        input.mortgage_insurance.as_mut().unwrap().borrower_requested_cancellation = true;
+        let requested = StandardCalculator.calculate_schedule(&input).unwrap();
+
+        assert!(requested.mortgage_insurance_end_date < automatic.mortgage_insurance_end_date);
+    }
+
+    #[test]
+    fn test_fha_premiums() {
+        let input = insured_input(MortgageInsuranceType::Fha, dec!(295000));
+        let schedule = StandardCalculator.calculate_schedule(&input).unwrap();
+
+        assert_eq!(schedule.upfront_mortgage_insurance, Some(dec!(4987.50)));
+        // LTV above 90% keeps annual MIP for the life of the loan
+        assert!(schedule.mortgage_insurance_end_date.is_none());
+        assert!(schedule.schedule[359].mortgage_insurance.unwrap() > Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_fha_eleven_year_premium() {
+        let input = insured_input(MortgageInsuranceType::Fha, dec!(330000));
+        let schedule = StandardCalculator.calculate_schedule(&input).unwrap();
+
+        assert_eq!(
+            schedule.mortgage_insurance_end_date,
+            Some(schedule.schedule[132].payment_date)
+        );
+    }
+}
@@ -0,0 +1,378 @@
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::models::{
+    DelinquencyStatus,
+    InstallmentStatus,
+    PaymentApplication,
+    PaymentHistory,
+    PaymentHistoryRequest,
+    PaymentScheduleEntry,
+    ReconciledInstallment,
+    RepaymentType,
+};
+use crate::utils::DecimalUtils;
+use super::{accrual_rate, calculate_schedule, CalculationError};
+
+/// Replays received payments against the contractual schedule
+///
+/// Interest accrues daily on the actual balance between application dates.
+/// Funds go to suspense and are applied oldest installment first once they
+/// cover a full installment; the next installment may be paid ahead within
+/// its own period. Late fees are collected once nothing is past due, and
+/// anything left after a full installment curtails principal.
+pub struct PaymentHistoryReconciler;
+
+impl PaymentHistoryReconciler {
+    pub fn reconcile(request: &PaymentHistoryRequest) -> Result<PaymentHistory, CalculationError> {
+        let schedule = calculate_schedule(&request.input)?;
+        let entries = &schedule.schedule;
+        let period_days = match request.input.repayment_type {
+            RepaymentType::AcceleratedBiweekly => 14,
+            _ => 30,
+        };
+
+        let mut servicer = Servicer {
+            request,
+            entries,
+            period_days,
+            balance: request.input.principal,
+            accrued_interest: Decimal::ZERO,
+            accrued_through: request.input.start_date - Duration::days(period_days),
+            suspense: Decimal::ZERO,
+            late_fees_outstanding: Decimal::ZERO,
+            next_unpaid: 0,
+            assessed_through: 0,
+            applied: vec![None; entries.len()],
+            late_fees: vec![Decimal::ZERO; entries.len()],
+        };
+
+        let mut payments: Vec<_> = request.payments.iter()
+            .filter(|payment| payment.received_date <= request.as_of_date)
+            .collect();
+        payments.sort_by_key(|payment| payment.received_date);
+
+        let applications = payments.into_iter()
+            .map(|payment| {
+                servicer.assess_late_fees(payment.received_date);
+                servicer.receive(payment.received_date, payment.amount)
+            })
+            .collect();
+
+        servicer.assess_late_fees(request.as_of_date);
+        servicer.accrue(request.as_of_date);
+        Ok(servicer.history(applications))
+    }
+}
+
+/// Principal and interest actually applied to one installment
+#[derive(Debug, Clone, Copy)]
+struct AppliedInstallment {
+    paid_date: NaiveDate,
+    interest: Decimal,
+    principal: Decimal,
+    balance: Decimal,
+}
+
+/// Running state of the loan as payments are applied
+struct Servicer<'a> {
+    request: &'a PaymentHistoryRequest,
+    entries: &'a [PaymentScheduleEntry],
+    period_days: i64,
+    balance: Decimal,
+    accrued_interest: Decimal,
+    accrued_through: NaiveDate,
+    suspense: Decimal,
+    late_fees_outstanding: Decimal,
+    next_unpaid: usize,
+    assessed_through: usize,
+    applied: Vec<Option<AppliedInstallment>>,
+    late_fees: Vec<Decimal>,
+}
+
+impl<'a> Servicer<'a> {
+    /// Principal, interest, escrow and mortgage insurance due on an installment
+    fn amount_due(entry: &PaymentScheduleEntry) -> Decimal {
+        DecimalUtils::round_currency(
+            entry.payment_amount
+                + entry.escrow_deposit.unwrap_or(Decimal::ZERO)
+                + entry.mortgage_insurance.unwrap_or(Decimal::ZERO)
+        )
+    }
+
+    /// Accrues interest on the current balance up to `date`
+    fn accrue(&mut self, date: NaiveDate) {
+        let days = self.request.day_count.days(self.accrued_through, date);
+        if days <= 0 {
+            return;
+        }
+        let rate = self.entries.get(self.next_unpaid)
+            .map_or(self.request.input.annual_interest_rate, |entry| accrual_rate(&self.request.input, entry));
+        self.accrued_interest += DecimalUtils::round_currency(
+            self.balance * rate * Decimal::from(days) / self.request.day_count.year_basis()
+        );
+        self.accrued_through = date;
+    }
+
+    /// Charges a late fee on every unpaid installment whose grace period ended before `date`
+    fn assess_late_fees(&mut self, date: NaiveDate) {
+        let grace = Duration::days(i64::from(self.request.late_fee.grace_period_days));
+        let mut index = self.assessed_through.max(self.next_unpaid);
+        while let Some(entry) = self.entries.get(index) {
+            if entry.payment_date + grace >= date {
+                break;
+            }
+            let fee = DecimalUtils::round_currency(
+                entry.payment_amount * self.request.late_fee.percentage / dec!(100)
+            );
+            self.late_fees[index] = fee;
+            self.late_fees_outstanding += fee;
+            index += 1;
+        }
+        self.assessed_through = index;
+    }
+
+    /// Whether the oldest unpaid installment is due on or before `date`
+    fn past_due(&self, date: NaiveDate) -> bool {
+        self.entries.get(self.next_unpaid)
+            .is_some_and(|entry| entry.payment_date <= date)
+    }
+
+    fn receive(&mut self, date: NaiveDate, amount: Decimal) -> PaymentApplication {
+        self.accrue(date);
+        self.suspense += amount;
+
+        let mut application = PaymentApplication {
+            received_date: date,
+            amount,
+            installments_paid: Vec::new(),
+            interest: Decimal::ZERO,
+            principal: Decimal::ZERO,
+            escrow: Decimal::ZERO,
+            late_fees: Decimal::ZERO,
+            principal_curtailment: Decimal::ZERO,
+            suspense_balance: Decimal::ZERO,
+        };
+
+        let mut paid_ahead = false;
+        while let Some(entry) = self.entries.get(self.next_unpaid) {
+            let due_now = entry.payment_date <= date;
+            let due_next = !paid_ahead && (entry.payment_date - date).num_days() < self.period_days;
+            let amount_due = Self::amount_due(entry);
+            if self.suspense < amount_due || !(due_now || due_next) {
+                break;
+            }
+            paid_ahead |= !due_now;
+
+            let principal_and_interest = DecimalUtils::round_currency(entry.payment_amount);
+            let interest = self.accrued_interest.min(principal_and_interest);
+            let principal = (principal_and_interest - interest).min(self.balance);
+            self.accrued_interest -= interest;
+            self.balance -= principal;
+            self.suspense -= amount_due;
+
+            self.applied[self.next_unpaid] = Some(AppliedInstallment {
+                paid_date: date,
+                interest,
+                principal,
+                balance: self.balance,
+            });
+            application.installments_paid.push(entry.payment_number);
+            application.interest += interest;
+            application.principal += principal;
+            application.escrow += amount_due - principal_and_interest;
+            self.next_unpaid += 1;
+        }
+
+        if !self.past_due(date) {
+            let late_fees = self.suspense.min(self.late_fees_outstanding);
+            self.late_fees_outstanding -= late_fees;
+            self.suspense -= late_fees;
+            application.late_fees = late_fees;
+
+            // Partial payments stay in suspense; overpayments reduce principal
+            if !application.installments_paid.is_empty() || self.next_unpaid == self.entries.len() {
+                let curtailment = self.suspense.min(self.balance);
+                self.balance -= curtailment;
+                self.suspense -= curtailment;
+                application.principal_curtailment = curtailment;
+            }
+        }
+
+        application.suspense_balance = self.suspense;
+        application
+    }
+
+    fn history(self, applications: Vec<PaymentApplication>) -> PaymentHistory {
+        let as_of_date = self.request.as_of_date;
+        let grace = Duration::days(i64::from(self.request.late_fee.grace_period_days));
+
+        let installments: Vec<ReconciledInstallment> = self.entries.iter()
+            .zip(&self.applied)
+            .zip(&self.late_fees)
+            .filter(|((entry, applied), _)| entry.payment_date <= as_of_date || applied.is_some())
+            .map(|((entry, applied), late_fee)| {
+                let status = match applied {
+                    Some(_) if entry.payment_date > as_of_date => InstallmentStatus::PaidAhead,
+                    Some(applied) if applied.paid_date <= entry.payment_date + grace => InstallmentStatus::Paid,
+                    Some(_) => InstallmentStatus::PaidLate,
+                    None => InstallmentStatus::PastDue,
+                };
+                let settled = applied.map_or(as_of_date, |applied| applied.paid_date);
+
+                ReconciledInstallment {
+                    payment_number: entry.payment_number,
+                    due_date: entry.payment_date,
+                    amount_due: Self::amount_due(entry),
+                    scheduled_principal: DecimalUtils::round_currency(entry.principal_component),
+                    scheduled_interest: DecimalUtils::round_currency(entry.interest_component),
+                    scheduled_balance: DecimalUtils::round_currency(entry.remaining_principal),
+                    status,
+                    paid_date: applied.map(|applied| applied.paid_date),
+                    days_late: (settled - entry.payment_date).num_days().max(0),
+                    actual_interest: applied.map(|applied| applied.interest),
+                    actual_principal: applied.map(|applied| applied.principal),
+                    actual_balance: applied.map(|applied| DecimalUtils::round_currency(applied.balance)),
+                    late_fee: *late_fee,
+                }
+            })
+            .collect();
+
+        let past_due: Vec<&ReconciledInstallment> = installments.iter()
+            .filter(|installment| installment.status == InstallmentStatus::PastDue)
+            .collect();
+        let days_past_due = past_due.first().map_or(0, |installment| installment.days_late);
+
+        let scheduled_balance = DecimalUtils::round_currency(
+            self.entries.iter()
+                .take_while(|entry| entry.payment_date <= as_of_date)
+                .last()
+                .map_or(self.request.input.principal, |entry| entry.remaining_principal)
+        );
+        let actual_balance = DecimalUtils::round_currency(self.balance);
+
+        PaymentHistory {
+            as_of_date,
+            actual_balance,
+            scheduled_balance,
+            balance_variance: actual_balance - scheduled_balance,
+            accrued_interest: self.accrued_interest,
+            suspense_balance: self.suspense,
+            late_fees_assessed: self.late_fees.iter().sum(),
+            late_fees_outstanding: self.late_fees_outstanding,
+            installments_past_due: past_due.len() as u32,
+            amount_past_due: past_due.iter().map(|installment| installment.amount_due).sum(),
+            days_past_due,
+            delinquency_status: DelinquencyStatus::from_days_past_due(days_past_due),
+            installments,
+            applications,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DayCountConvention, LateFeePolicy, MortgageInput, ReceivedPayment};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn history_request(payments: Vec<(NaiveDate, Decimal)>, as_of_date: NaiveDate) -> PaymentHistoryRequest {
+        PaymentHistoryRequest {
+            input: MortgageInput::new(
+                dec!(200000),
+                dec!(0.06),
+                30,
+                RepaymentType::StandardPrincipalAndInterest,
+                date(2024, 1, 1),
+            ),
+            payments: payments.into_iter()
+                .map(|(received_date, amount)| ReceivedPayment { received_date, amount })
+                .collect(),
+            as_of_date,
+            late_fee: LateFeePolicy::default(),
+            day_count: DayCountConvention::Actual360,
+        }
+    }
+
+    /// Due dates step 30 days from the start date
+    fn due_date(payment_number: i64) -> NaiveDate {
+        date(2024, 1, 1) + Duration::days(30 * (payment_number - 1))
+    }
+
+    // 200,000 at 6% over 30 years
+    const INSTALLMENT: Decimal = dec!(1199.10);
+
+    #[test]
+    fn test_on_time_payments_match_schedule() {
+        let payments = (1..=6).map(|n| (due_date(n), INSTALLMENT)).collect();
+        let history = PaymentHistoryReconciler::reconcile(&history_request(payments, due_date(6))).unwrap();
+
+        assert_eq!(history.installments.len(), 6);
+        assert!(history.installments.iter().all(|installment| installment.status == InstallmentStatus::Paid));
+        assert_eq!(history.installments[0].actual_interest, Some(history.installments[0].scheduled_interest));
+        assert!(history.balance_variance.abs() <= dec!(0.05));
+        assert_eq!(history.delinquency_status, DelinquencyStatus::Current);
+        assert_eq!(history.late_fees_assessed, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_partial_payments_held_in_suspense() {
+        let payments = vec![
+            (due_date(1), INSTALLMENT),
+            (due_date(2), dec!(600)),
+            (due_date(2) + Duration::days(10), dec!(599.10)),
+        ];
+        let history = PaymentHistoryReconciler::reconcile(&history_request(payments, due_date(2) + Duration::days(10))).unwrap();
+
+        assert_eq!(history.applications[1].suspense_balance, dec!(600));
+        assert!(history.applications[1].installments_paid.is_empty());
+        assert_eq!(history.applications[2].installments_paid, vec![2]);
+        assert_eq!(history.installments[1].paid_date, Some(due_date(2) + Duration::days(10)));
+        assert_eq!(history.suspense_balance, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_late_payment_accrues_more_interest_and_fee() {
+        let payments = vec![
+            (due_date(1), INSTALLMENT),
+            (due_date(2) + Duration::days(20), INSTALLMENT + dec!(59.96)),
+        ];
+        let history = PaymentHistoryReconciler::reconcile(&history_request(payments, due_date(2) + Duration::days(20))).unwrap();
+        let late = &history.installments[1];
+
+        assert_eq!(late.status, InstallmentStatus::PaidLate);
+        assert_eq!(late.days_late, 20);
+        assert_eq!(late.late_fee, dec!(59.96));
+        assert!(late.actual_interest.unwrap() > late.scheduled_interest);
+        assert_eq!(history.applications[1].late_fees, dec!(59.96));
+        assert_eq!(history.late_fees_outstanding, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_missed_payments_become_delinquent() {
+        let payments = vec![(due_date(1), INSTALLMENT), (due_date(2), INSTALLMENT)];
+        let as_of_date = due_date(3) + Duration::days(65);
+        let history = PaymentHistoryReconciler::reconcile(&history_request(payments, as_of_date)).unwrap();
+
+        // Installments 3, 4 and 5 are unpaid; the oldest is 65 days past due
+        assert_eq!(history.installments_past_due, 3);
+        assert_eq!(history.amount_past_due, INSTALLMENT * dec!(3));
+        assert_eq!(history.days_past_due, 65);
+        assert_eq!(history.delinquency_status, DelinquencyStatus::SixtyDays);
+        assert_eq!(history.late_fees_outstanding, dec!(59.96) * dec!(2));
+        assert!(history.accrued_interest > Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_overpayment_curtails_principal() {
+        let payments = vec![(due_date(1), INSTALLMENT + dec!(5000))];
+        let history = PaymentHistoryReconciler::reconcile(&history_request(payments, due_date(1))).unwrap();
+
+        assert_eq!(history.applications[0].principal_curtailment, dec!(5000));
+        assert!(history.balance_variance < dec!(-4999));
+    }
+}
@@ -0,0 +1,165 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+use crate::models::{PayoffQuote, PayoffRequest};
+use crate::utils::DecimalUtils;
+use super::{accrual_rate, calculate_schedule, CalculationError, PrepaymentPenaltyCalculator};
+
+/// Errors raised while quoting a payoff
+#[derive(Debug, Error, PartialEq)]
+pub enum PayoffError {
+    #[error("payoff date {payoff_date} is before the first payment on {first_payment_date}")]
+    BeforeFirstPayment {
+        payoff_date: NaiveDate,
+        first_payment_date: NaiveDate,
+    },
+
+    #[error("loan is repaid by its final payment on {final_payment_date}")]
+    LoanRepaid { final_payment_date: NaiveDate },
+
+    #[error(transparent)]
+    Calculation(#[from] CalculationError),
+}
+
+/// Quotes the amount needed to retire a loan on a given date
+///
+/// Installments due on or before the payoff date are treated as paid on schedule;
+/// interest then accrues on the remaining principal from the last installment.
+pub struct PayoffCalculator;
+
+impl PayoffCalculator {
+    pub fn quote(request: &PayoffRequest) -> Result<PayoffQuote, PayoffError> {
+        let schedule = calculate_schedule(&request.input)?;
+        let last_paid = schedule.last_payment_on_or_before(request.payoff_date)
+            .ok_or(PayoffError::BeforeFirstPayment {
+                payoff_date: request.payoff_date,
+                first_payment_date: request.input.start_date,
+            })?;
+
+        let next_due = match schedule.schedule.get(last_paid.payment_number as usize) {
+            Some(entry) if last_paid.remaining_principal > Decimal::ZERO => entry,
+            _ => return Err(PayoffError::LoanRepaid { final_payment_date: last_paid.payment_date }),
+        };
+
+        // Interest accrues at the rate of the installment now running
+        let annual_interest_rate = accrual_rate(&request.input, next_due);
+
+        let outstanding_principal = DecimalUtils::round_currency(last_paid.remaining_principal);
+        let days_accrued = request.day_count.days(last_paid.payment_date, request.payoff_date);
+        let per_diem = outstanding_principal * annual_interest_rate / request.day_count.year_basis();
+        let accrued_interest = DecimalUtils::round_currency(per_diem * Decimal::from(days_accrued));
+        let prepayment_penalty = PrepaymentPenaltyCalculator::penalty(
+            &request.input,
+            last_paid.payment_number,
+            outstanding_principal,
+            outstanding_principal,
+        )?;
+
+        Ok(PayoffQuote {
+            payoff_date: request.payoff_date,
+            last_paid_payment_number: last_paid.payment_number,
+            interest_paid_through: last_paid.payment_date,
+            outstanding_principal,
+            annual_interest_rate,
+            day_count: request.day_count,
+            days_accrued,
+            per_diem: DecimalUtils::round_currency(per_diem),
+            accrued_interest,
+            prepayment_penalty,
+            total_payoff_amount: outstanding_principal + accrued_interest + prepayment_penalty,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use crate::models::{DayCountConvention, MortgageInput, PrepaymentPenalty, RepaymentType};
+
+    fn payoff_request(payoff_date: NaiveDate, day_count: DayCountConvention) -> PayoffRequest {
+        PayoffRequest {
+            input: MortgageInput::new(
+                dec!(360000),
+                dec!(0.06),
+                30,
+                RepaymentType::StandardPrincipalAndInterest,
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            ),
+            payoff_date,
+            day_count,
+        }
+    }
+
+    #[test]
+    fn test_payoff_on_payment_date_has_no_accrual() {
+        // The 13th installment falls 360 days after the first
+        let payment_13 = NaiveDate::from_ymd_opt(2024, 12, 26).unwrap();
+        let quote = PayoffCalculator::quote(&payoff_request(payment_13, DayCountConvention::Thirty360)).unwrap();
+
+        assert_eq!(quote.last_paid_payment_number, 13);
+        assert_eq!(quote.days_accrued, 0);
+        assert_eq!(quote.accrued_interest, Decimal::ZERO);
+        assert_eq!(quote.total_payoff_amount, quote.outstanding_principal);
+    }
+
+    #[test]
+    fn test_per_diem_follows_day_count() {
+        let payoff_date = NaiveDate::from_ymd_opt(2024, 1, 16).unwrap();
+        let thirty_360 = PayoffCalculator::quote(&payoff_request(payoff_date, DayCountConvention::Thirty360)).unwrap();
+        let actual_365 = PayoffCalculator::quote(&payoff_request(payoff_date, DayCountConvention::Actual365)).unwrap();
+
+        assert_eq!(thirty_360.days_accrued, 15);
+        assert_eq!(actual_365.days_accrued, 15);
+        assert!(thirty_360.per_diem > actual_365.per_diem);
+
+        let per_diem = thirty_360.outstanding_principal * dec!(0.06) / dec!(360);
+        assert_eq!(thirty_360.accrued_interest, DecimalUtils::round_currency(per_diem * dec!(15)));
+    }
+
+    #[test]
+    fn test_thirty_360_ignores_month_length() {
+        // Payment 6 falls on 2024-05-30; May has 31 actual days
+        let payoff_date = NaiveDate::from_ymd_opt(2024, 6, 28).unwrap();
+        let thirty_360 = PayoffCalculator::quote(&payoff_request(payoff_date, DayCountConvention::Thirty360)).unwrap();
+        let actual_360 = PayoffCalculator::quote(&payoff_request(payoff_date, DayCountConvention::Actual360)).unwrap();
+
+        assert_eq!(thirty_360.interest_paid_through, NaiveDate::from_ymd_opt(2024, 5, 30).unwrap());
+        assert_eq!(thirty_360.days_accrued, 28);
+        assert_eq!(actual_360.days_accrued, 29);
+        assert_eq!(thirty_360.per_diem, actual_360.per_diem);
+    }
+
+    #[test]
+    fn test_payoff_includes_prepayment_penalty() {
+        let mut request = payoff_request(NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(), DayCountConvention::Thirty360);
+        request.input.prepayment_penalty = Some(PrepaymentPenalty::StepDown {
+            percentages: vec![dec!(5), dec!(4), dec!(3)],
+        });
+        let quote = PayoffCalculator::quote(&request).unwrap();
+
+        // Eighteen payments made puts the payoff in the second loan year
+        assert_eq!(quote.last_paid_payment_number, 18);
+        assert_eq!(quote.prepayment_penalty, DecimalUtils::round_currency(quote.outstanding_principal * dec!(0.04)));
+        assert_eq!(
+            quote.total_payoff_amount,
+            quote.outstanding_principal + quote.accrued_interest + quote.prepayment_penalty
+        );
+    }
+
+    #[test]
+    fn test_payoff_outside_loan_rejected() {
+        let before = NaiveDate::from_ymd_opt(2023, 12, 1).unwrap();
+        assert!(matches!(
+            PayoffCalculator::quote(&payoff_request(before, DayCountConvention::Thirty360)),
+            Err(PayoffError::BeforeFirstPayment { .. })
+        ));
+
+        let after = NaiveDate::from_ymd_opt(2060, 1, 1).unwrap();
+        assert!(matches!(
+            PayoffCalculator::quote(&payoff_request(after, DayCountConvention::Thirty360)),
+            Err(PayoffError::LoanRepaid { .. })
+        ));
+    }
+}
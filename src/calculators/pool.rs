@@ -0,0 +1,252 @@
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+use chrono::NaiveDate;
+
+use crate::models::{MortgageInput, PoolCashFlow, PoolProjection, PoolRequest};
+use crate::utils::DecimalUtils;
+use super::{calculate_schedule, CalculationError};
+
+/// Length of a pool month, matching the calculators' date stepping
+const DAYS_PER_MONTH: i64 = 30;
+
+/// Projects aggregated cash flows for a pool of loans under prepayment and default assumptions
+pub struct PoolCalculator;
+
+/// Unrounded flows accumulated for one pool month
+#[derive(Debug, Clone, Default)]
+struct MonthlyFlows {
+    beginning_balance: Decimal,
+    scheduled_principal: Decimal,
+    interest: Decimal,
+    prepayments: Decimal,
+    defaults: Decimal,
+    recoveries: Decimal,
+    losses: Decimal,
+}
+
+impl PoolCalculator {
+    /// Runs every loan's schedule against the surviving balance and sums the results by month
+    ///
+    /// Each payment pays the same share of the surviving balance as it does of the
+    /// scheduled balance, so any repayment type can be pooled. Defaults come off the
+    /// balance first, then scheduled principal, then prepayments of what is left.
+    pub fn project(request: &PoolRequest) -> Result<PoolProjection, CalculationError> {
+        let pool_start = request.loans.iter()
+            .map(|loan| loan.start_date)
+            .min()
+            .unwrap_or_default();
+
+        let mut months: Vec<MonthlyFlows> = Vec::new();
+        for loan in &request.loans {
+            Self::project_loan(request, loan, pool_start, &mut months)?;
+        }
+
+        if let Some(defaults) = &request.defaults {
+            let lag = defaults.recovery_lag_months as usize;
+            let severity = defaults.loss_severity / dec!(100);
+            let defaulted: Vec<Decimal> = months.iter().map(|flows| flows.defaults).collect();
+            for (month, amount) in defaulted.into_iter().enumerate().filter(|(_, amount)| !amount.is_zero()) {
+                let recovered_in = month + lag;
+                if months.len() <= recovered_in {
+                    months.resize(recovered_in + 1, MonthlyFlows::default());
+                }
+                let loss = amount * severity;
+                months[recovered_in].losses += loss;
+                months[recovered_in].recoveries += amount - loss;
+            }
+        }
+
+        let cash_flows: Vec<PoolCashFlow> = months.iter()
+            .enumerate()
+            .map(|(month, flows)| {
+                let ending_balance = flows.beginning_balance
+                    - flows.scheduled_principal
+                    - flows.prepayments
+                    - flows.defaults;
+                PoolCashFlow {
+                    month: month as u32 + 1,
+                    beginning_balance: DecimalUtils::round_currency(flows.beginning_balance),
+                    scheduled_principal: DecimalUtils::round_currency(flows.scheduled_principal),
+                    interest: DecimalUtils::round_currency(flows.interest),
+                    prepayments: DecimalUtils::round_currency(flows.prepayments),
+                    defaults: DecimalUtils::round_currency(flows.defaults),
+                    recoveries: DecimalUtils::round_currency(flows.recoveries),
+                    losses: DecimalUtils::round_currency(flows.losses),
+                    ending_balance: DecimalUtils::round_currency(ending_balance.max(Decimal::ZERO)),
+                    total_cash_flow: DecimalUtils::round_currency(
+                        flows.scheduled_principal + flows.interest + flows.prepayments + flows.recoveries
+                    ),
+                }
+            })
+            .collect();
+
+        let total = |field: fn(&MonthlyFlows) -> Decimal| {
+            DecimalUtils::round_currency(months.iter().map(field).sum())
+        };
+
+        Ok(PoolProjection {
+            loan_count: request.loans.len(),
+            original_balance: request.loans.iter().map(|loan| loan.principal).sum(),
+            total_scheduled_principal: total(|flows| flows.scheduled_principal),
+            total_interest: total(|flows| flows.interest),
+            total_prepayments: total(|flows| flows.prepayments),
+            total_defaults: total(|flows| flows.defaults),
+            total_recoveries: total(|flows| flows.recoveries),
+            total_losses: total(|flows| flows.losses),
+            total_cash_flow: total(|flows| flows.scheduled_principal + flows.interest + flows.prepayments + flows.recoveries),
+            cash_flows,
+        })
+    }
+
+    /// Adds one loan's flows to the pool months, aligned on the pool's earliest start date
+    fn project_loan(
+        request: &PoolRequest,
+        loan: &MortgageInput,
+        pool_start: NaiveDate,
+        months: &mut Vec<MonthlyFlows>,
+    ) -> Result<(), CalculationError> {
+        let schedule = calculate_schedule(loan)?;
+        let payments_per_year = Decimal::from(loan.repayment_type.payments_per_year());
+        let default_rate = request.defaults.as_ref()
+            .map_or(Decimal::ZERO, |defaults| Self::periodic_rate(defaults.cdr / dec!(100), payments_per_year));
+        let offset = (loan.start_date - pool_start).num_days();
+
+        let mut balance = loan.principal;
+        let mut current_month = None;
+        for entry in &schedule.schedule {
+            let scheduled_balance = entry.remaining_principal + entry.principal_component;
+            if balance <= Decimal::ZERO || scheduled_balance <= Decimal::ZERO {
+                break;
+            }
+
+            let loan_days = (entry.payment_date - loan.start_date).num_days();
+            let month = ((loan_days + offset) / DAYS_PER_MONTH) as usize;
+            if months.len() <= month {
+                months.resize(month + 1, MonthlyFlows::default());
+            }
+            let flows = &mut months[month];
+            if current_month != Some(month) {
+                flows.beginning_balance += balance;
+                current_month = Some(month);
+            }
+
+            let defaulted = balance * default_rate;
+            let performing = balance - defaulted;
+            let interest = performing * entry.interest_component / scheduled_balance;
+            let scheduled_principal = performing * entry.principal_component / scheduled_balance;
+
+            let loan_month = (loan_days / DAYS_PER_MONTH) as u32 + 1;
+            let prepayment_rate = request.prepayment
+                .map_or(Decimal::ZERO, |speed| Self::periodic_rate(speed.cpr(loan_month), payments_per_year));
+            let prepaid = (performing - scheduled_principal) * prepayment_rate;
+
+            balance = performing - scheduled_principal - prepaid;
+            flows.defaults += defaulted;
+            flows.interest += interest;
+            flows.scheduled_principal += scheduled_principal;
+            flows.prepayments += prepaid;
+        }
+        Ok(())
+    }
+
+    /// Per-payment rate compounding to `annual_rate` over a year of payments
+    fn periodic_rate(annual_rate: Decimal, payments_per_year: Decimal) -> Decimal {
+        if annual_rate <= Decimal::ZERO {
+            Decimal::ZERO
+        } else if annual_rate >= dec!(1) {
+            dec!(1)
+        } else {
+            dec!(1) - (dec!(1) - annual_rate).powd(dec!(1) / payments_per_year)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DefaultAssumptions, PrepaymentSpeed, RepaymentType};
+
+    fn pool_loan(principal: Decimal, start_date: NaiveDate) -> MortgageInput {
+        MortgageInput::new(
+            principal,
+            dec!(0.06),
+            30,
+            RepaymentType::StandardPrincipalAndInterest,
+            start_date,
+        )
+    }
+
+    fn pool(prepayment: Option<PrepaymentSpeed>, defaults: Option<DefaultAssumptions>) -> PoolRequest {
+        PoolRequest {
+            loans: vec![
+                pool_loan(dec!(200000), NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
+                pool_loan(dec!(100000), NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()),
+            ],
+            prepayment,
+            defaults,
+        }
+    }
+
+    #[test]
+    fn test_speed_conversions() {
+        assert_eq!(DecimalUtils::round_currency(PoolCalculator::periodic_rate(dec!(0.06), dec!(12)) * dec!(10000)), dec!(51.43));
+        assert_eq!(PrepaymentSpeed::Psa { speed: dec!(100) }.cpr(1), dec!(0.002));
+        assert_eq!(PrepaymentSpeed::Psa { speed: dec!(150) }.cpr(30), dec!(0.09));
+        assert_eq!(PrepaymentSpeed::Psa { speed: dec!(100) }.cpr(45), dec!(0.06));
+        assert_eq!(
+            DecimalUtils::round_currency(PrepaymentSpeed::Smm { rate: dec!(1) }.cpr(1) * dec!(100)),
+            dec!(11.36)
+        );
+    }
+
+    #[test]
+    fn test_pool_without_assumptions_follows_schedules() {
+        let request = pool(None, None);
+        let projection = PoolCalculator::project(&request).unwrap();
+        let scheduled_interest: Decimal = request.loans.iter()
+            .map(|loan| calculate_schedule(loan).unwrap().total_interest)
+            .sum();
+
+        assert_eq!(projection.loan_count, 2);
+        assert_eq!(projection.total_scheduled_principal, dec!(300000));
+        assert_eq!(projection.total_interest, DecimalUtils::round_currency(scheduled_interest));
+        assert_eq!(projection.total_prepayments, Decimal::ZERO);
+        // The second loan joins the pool in its third month
+        assert_eq!(projection.cash_flows[0].beginning_balance, dec!(200000));
+        assert!(projection.cash_flows[2].beginning_balance > dec!(299000));
+        assert_eq!(projection.cash_flows.len(), 362);
+    }
+
+    #[test]
+    fn test_prepayments_shorten_the_pool() {
+        let scheduled = PoolCalculator::project(&pool(None, None)).unwrap();
+        let prepaid = PoolCalculator::project(&pool(Some(PrepaymentSpeed::Psa { speed: dec!(200) }), None)).unwrap();
+
+        assert!(prepaid.total_prepayments > dec!(150000));
+        assert_eq!(prepaid.total_scheduled_principal + prepaid.total_prepayments, dec!(300000));
+        assert!(prepaid.total_interest < scheduled.total_interest);
+        assert!(prepaid.cash_flows[120].ending_balance < scheduled.cash_flows[120].ending_balance);
+    }
+
+    #[test]
+    fn test_defaults_recovered_after_lag() {
+        let projection = PoolCalculator::project(&pool(
+            Some(PrepaymentSpeed::Cpr { rate: dec!(6) }),
+            Some(DefaultAssumptions {
+                cdr: dec!(2),
+                loss_severity: dec!(40),
+                recovery_lag_months: 6,
+            }),
+        )).unwrap();
+
+        assert!(projection.cash_flows[0].defaults > Decimal::ZERO);
+        assert_eq!(projection.cash_flows[5].recoveries, Decimal::ZERO);
+        assert!(projection.cash_flows[6].recoveries > Decimal::ZERO);
+        assert_eq!(projection.total_losses + projection.total_recoveries, projection.total_defaults);
+        assert!((projection.total_losses - projection.total_defaults * dec!(0.4)).abs() < dec!(0.01));
+        assert!(
+            (projection.total_scheduled_principal + projection.total_prepayments + projection.total_defaults
+                - dec!(300000)).abs() < dec!(0.05)
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::models::{MortgageInput, MortgageSchedule, PrepaymentPenalty};
+use crate::utils::DecimalUtils;
+use super::{payment_factor, CalculationError};
+
+/// Prices prepayment penalties and charges them on extra principal in a schedule
+pub struct PrepaymentPenaltyCalculator;
+
+impl PrepaymentPenaltyCalculator {
+    /// Fills the prepayment penalty column of `schedule` for payments carrying extra principal
+    pub fn apply(input: &MortgageInput, schedule: &mut MortgageSchedule) -> Result<(), CalculationError> {
+        if input.prepayment_penalty.is_none() || input.extra_payment <= Decimal::ZERO {
+            return Ok(());
+        }
+
+        for entry in &mut schedule.schedule {
+            let prepaid = input.extra_payment.min(entry.principal_component);
+            let balance = entry.remaining_principal + entry.principal_component;
+            entry.prepayment_penalty = Some(Self::penalty(input, entry.payment_number - 1, prepaid, balance)?);
+        }
+        Ok(())
+    }
+
+    /// Penalty for prepaying `prepaid` of `balance` after `payments_made` scheduled payments
+    pub fn penalty(
+        input: &MortgageInput,
+        payments_made: u32,
+        prepaid: Decimal,
+        balance: Decimal,
+    ) -> Result<Decimal, CalculationError> {
+        let spec = match &input.prepayment_penalty {
+            Some(spec) if prepaid > Decimal::ZERO => spec,
+            _ => return Ok(Decimal::ZERO),
+        };
+
+        let payments_per_year = input.repayment_type.payments_per_year();
+        let remaining_payments = (input.term_years * payments_per_year).saturating_sub(payments_made);
+        if remaining_payments == 0 {
+            return Ok(Decimal::ZERO);
+        }
+        let periods_per_year = Decimal::from(payments_per_year);
+
+        let penalty = match spec {
+            PrepaymentPenalty::StepDown { percentages } => {
+                let loan_year = (payments_made / payments_per_year) as usize;
+                percentages.get(loan_year)
+                    .map_or(Decimal::ZERO, |percentage| prepaid * percentage / dec!(100))
+            }
+            PrepaymentPenalty::YieldMaintenance { treasury_rate, minimum_percentage } => {
+                // Lost spread on the prepaid principal for each remaining period, discounted at treasury
+                let spread = (input.annual_interest_rate - treasury_rate).max(Decimal::ZERO) / periods_per_year;
+                let annuity = Self::annuity_factor(*treasury_rate / periods_per_year, remaining_payments)?;
+                (prepaid * spread * annuity).max(prepaid * minimum_percentage / dec!(100))
+            }
+            PrepaymentPenalty::Defeasance { treasury_rate, fees } => {
+                // Treasuries must replicate the payments the prepaid principal would have made
+                let payment = prepaid * payment_factor(input.annual_interest_rate / periods_per_year, remaining_payments)?;
+                let annuity = Self::annuity_factor(*treasury_rate / periods_per_year, remaining_payments)?;
+                let fees = if prepaid >= balance { *fees } else { Decimal::ZERO };
+                (payment * annuity - prepaid).max(Decimal::ZERO) + fees
+            }
+        };
+
+        Ok(DecimalUtils::round_currency(penalty))
+    }
+
+    /// Present value of one unit paid at the end of each of `periods` periods
+    fn annuity_factor(periodic_rate: Decimal, periods: u32) -> Result<Decimal, CalculationError> {
+        Ok(dec!(1) / payment_factor(periodic_rate, periods)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::calculators::calculate_schedule;
+    use crate::models::RepaymentType;
+
+    fn penalty_input(prepayment_penalty: PrepaymentPenalty) -> MortgageInput {
+        MortgageInput {
+            prepayment_penalty: Some(prepayment_penalty),
+            ..MortgageInput::new(
+                dec!(1000000),
+                dec!(0.07),
+                10,
+                RepaymentType::StandardPrincipalAndInterest,
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            )
+        }
+    }
+
+    fn step_down() -> PrepaymentPenalty {
+        PrepaymentPenalty::StepDown {
+            percentages: vec![dec!(5), dec!(4), dec!(3), dec!(2), dec!(1)],
+        }
+    }
+
+    #[test]
+    fn test_step_down_by_loan_year() {
+        let input = penalty_input(step_down());
+
+        assert_eq!(PrepaymentPenaltyCalculator::penalty(&input, 0, dec!(100000), dec!(1000000)).unwrap(), dec!(5000));
+        assert_eq!(PrepaymentPenaltyCalculator::penalty(&input, 12, dec!(100000), dec!(1000000)).unwrap(), dec!(4000));
+        assert_eq!(PrepaymentPenaltyCalculator::penalty(&input, 59, dec!(100000), dec!(1000000)).unwrap(), dec!(1000));
+        assert_eq!(PrepaymentPenaltyCalculator::penalty(&input, 60, dec!(100000), dec!(1000000)).unwrap(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_yield_maintenance_floors_at_minimum() {
+        let input = penalty_input(PrepaymentPenalty::YieldMaintenance {
+            treasury_rate: dec!(0.04),
+            minimum_percentage: dec!(1),
+        });
+        let early = PrepaymentPenaltyCalculator::penalty(&input, 0, dec!(1000000), dec!(1000000)).unwrap();
+        let late = PrepaymentPenaltyCalculator::penalty(&input, 119, dec!(1000000), dec!(1000000)).unwrap();
+
+        // Three points of spread for ten years is worth far more than the 1% floor
+        assert!(early > dec!(200000));
+        assert_eq!(late, dec!(10000));
+    }
+
+    #[test]
+    fn test_defeasance_costs_nothing_at_note_rate() {
+        let input = penalty_input(PrepaymentPenalty::Defeasance {
+            treasury_rate: dec!(0.07),
+            fees: dec!(25000),
+        });
+
+        assert_eq!(PrepaymentPenaltyCalculator::penalty(&input, 24, dec!(500000), dec!(800000)).unwrap(), Decimal::ZERO);
+        assert_eq!(PrepaymentPenaltyCalculator::penalty(&input, 24, dec!(800000), dec!(800000)).unwrap(), dec!(25000));
+    }
+
+    #[test]
+    fn test_extra_payments_charged_in_schedule() {
+        let mut input = penalty_input(step_down());
+        input.extra_payment = dec!(1000);
+        let schedule = calculate_schedule(&input).unwrap();
+
+        assert_eq!(schedule.schedule[0].prepayment_penalty, Some(dec!(50)));
+        assert_eq!(schedule.schedule[12].prepayment_penalty, Some(dec!(40)));
+        assert_eq!(schedule.schedule[60].prepayment_penalty, Some(Decimal::ZERO));
+        assert_eq!(schedule.total_prepayment_penalty(), Some(dec!(1800)));
+    }
+}
@@ -0,0 +1,297 @@
+use chrono::Duration;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use thiserror::Error;
+
+use crate::models::{
+    MortgageInput,
+    PaymentScheduleEntry,
+    RefinanceAnalysis,
+    RefinanceInput,
+    RefinanceOffer,
+    RefinanceOfferResult,
+};
+use crate::utils::DecimalUtils;
+use super::comparison::DAYS_PER_MONTH;
+use super::{calculate_schedule, summarize_schedule, CalculationError, ComparisonCalculator, PrepaymentPenaltyCalculator};
+
+/// Errors raised while analysing a refinance
+#[derive(Debug, Error, PartialEq)]
+pub enum RefinanceError {
+    #[error("existing loan has only {scheduled} payments but {made} were reported as made")]
+    LoanRepaid { made: u32, scheduled: u32 },
+
+    #[error(transparent)]
+    Calculation(#[from] CalculationError),
+}
+
+/// Compares keeping the existing loan against each refinance offer
+pub struct RefinanceCalculator;
+
+impl RefinanceCalculator {
+    pub fn analyze(input: &RefinanceInput) -> Result<RefinanceAnalysis, RefinanceError> {
+        let existing = calculate_schedule(&input.existing_loan)?;
+        let payments_made = match (input.current_payment_number, input.current_balance) {
+            (Some(payment_number), _) => payment_number,
+            (None, Some(balance)) => existing.schedule.iter()
+                .take_while(|entry| entry.remaining_principal >= balance)
+                .count() as u32,
+            (None, None) => 0,
+        };
+
+        let scheduled = existing.payment_count() as u32;
+        if payments_made >= scheduled {
+            return Err(RefinanceError::LoanRepaid { made: payments_made, scheduled });
+        }
+
+        let current_balance = input.current_balance.unwrap_or_else(|| match payments_made {
+            0 => input.existing_loan.principal,
+            made => existing.schedule[made as usize - 1].remaining_principal,
+        });
+        let remaining = &existing.schedule[payments_made as usize..];
+        let remaining_interest = remaining.iter().map(|entry| entry.interest_component).sum();
+        let current_monthly_payment = existing.monthly_payment;
+        let prepayment_penalty = PrepaymentPenaltyCalculator::penalty(
+            &input.existing_loan,
+            payments_made,
+            current_balance,
+            current_balance,
+        )?;
+
+        let offers = input.offers.iter()
+            .map(|offer| Self::evaluate_offer(
+                input,
+                offer,
+                current_balance,
+                prepayment_penalty,
+                remaining,
+                remaining_interest,
+                current_monthly_payment,
+            ))
+            .collect::<Result<_, _>>()?;
+
+        Ok(RefinanceAnalysis {
+            current_payment_number: payments_made,
+            current_balance,
+            current_monthly_payment,
+            remaining_payments: remaining.len() as u32,
+            remaining_interest,
+            prepayment_penalty,
+            offers,
+        })
+    }
+
+    fn evaluate_offer(
+        input: &RefinanceInput,
+        offer: &RefinanceOffer,
+        current_balance: Decimal,
+        prepayment_penalty: Decimal,
+        remaining: &[PaymentScheduleEntry],
+        remaining_interest: Decimal,
+        current_monthly_payment: Decimal,
+    ) -> Result<RefinanceOfferResult, CalculationError> {
+        let total_closing_costs = DecimalUtils::round_currency(
+            offer.closing_costs + current_balance * offer.points / dec!(100)
+        ) + prepayment_penalty;
+        let (new_loan_amount, upfront_costs) = if offer.finance_closing_costs {
+            (current_balance + total_closing_costs, Decimal::ZERO)
+        } else {
+            (current_balance, total_closing_costs)
+        };
+
+        // Compare principal and interest only; escrow is unchanged by a refinance
+        let new_input = MortgageInput {
+            property_value: input.existing_loan.property_value,
+            ..MortgageInput::new(
+                new_loan_amount,
+                offer.annual_interest_rate,
+                offer.term_years,
+                offer.repayment_type,
+                remaining[0].payment_date,
+            )
+        };
+        let new_schedule = calculate_schedule(&new_input)?;
+        let summary = summarize_schedule(&new_input, &new_schedule)?;
+
+        let monthly_discount = dec!(1) / (dec!(1) + input.discount_rate / dec!(12));
+        let mut discount_factor = dec!(1);
+        let mut cumulative_savings = Decimal::ZERO;
+        let mut npv_of_savings = -upfront_costs;
+        let mut break_even = None;
+
+        // Biweekly and monthly loans only line up once their payments are grouped by month
+        let current_months = ComparisonCalculator::monthly_costs(new_input.start_date, remaining);
+        let new_months = ComparisonCalculator::monthly_costs(new_input.start_date, &new_schedule.schedule);
+
+        for month in 0..current_months.len().max(new_months.len()) {
+            let current_payment = current_months.get(month).map_or(Decimal::ZERO, |(payments, _)| *payments);
+            let new_payment = new_months.get(month).map_or(Decimal::ZERO, |(payments, _)| *payments);
+            let savings = current_payment - new_payment;
+
+            discount_factor *= monthly_discount;
+            npv_of_savings += savings * discount_factor;
+            cumulative_savings += savings;
+
+            if break_even.is_none() && cumulative_savings >= total_closing_costs {
+                let month_start = new_input.start_date
+                    .checked_add_signed(Duration::days(month as i64 * DAYS_PER_MONTH));
+                break_even = Some((month as u32 + 1, month_start));
+            }
+        }
+
+        Ok(RefinanceOfferResult {
+            name: offer.name.clone(),
+            new_loan_amount,
+            new_monthly_payment: new_schedule.monthly_payment,
+            monthly_savings: current_monthly_payment - new_schedule.monthly_payment,
+            total_closing_costs,
+            break_even_month: break_even.map(|(month, _)| month),
+            break_even_date: break_even.and_then(|(_, date)| date),
+            new_total_interest: new_schedule.total_interest,
+            lifetime_interest_savings: remaining_interest - new_schedule.total_interest,
+            npv_of_savings: DecimalUtils::round_currency(npv_of_savings),
+            summary,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::models::{PrepaymentPenalty, RepaymentType};
+
+    fn refinance_input(offers: Vec<RefinanceOffer>) -> RefinanceInput {
+        RefinanceInput {
+            existing_loan: MortgageInput::new(
+                dec!(300000),
+                dec!(0.07),
+                30,
+                RepaymentType::StandardPrincipalAndInterest,
+                NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+            ),
+            current_payment_number: Some(48),
+            current_balance: None,
+            offers,
+            discount_rate: dec!(0.05),
+        }
+    }
+
+    fn offer(name: &str, rate: Decimal, closing_costs: Decimal) -> RefinanceOffer {
+        RefinanceOffer {
+            name: name.to_string(),
+            annual_interest_rate: rate,
+            term_years: 30,
+            repayment_type: RepaymentType::StandardPrincipalAndInterest,
+            closing_costs,
+            points: dec!(0),
+            finance_closing_costs: false,
+        }
+    }
+
+    #[test]
+    fn test_lower_rate_breaks_even() {
+        let analysis = RefinanceCalculator::analyze(&refinance_input(vec![
+            offer("5.5% no points", dec!(0.055), dec!(4000)),
+        ])).unwrap();
+        let result = &analysis.offers[0];
+
+        assert_eq!(analysis.remaining_payments, 312);
+        assert!(result.monthly_savings > Decimal::ZERO);
+        let expected_month = (dec!(4000) / result.monthly_savings).ceil();
+        assert_eq!(Decimal::from(result.break_even_month.unwrap()), expected_month);
+        assert!(result.npv_of_savings > Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_higher_rate_never_breaks_even() {
+        let analysis = RefinanceCalculator::analyze(&refinance_input(vec![
+            offer("8%", dec!(0.08), dec!(3000)),
+        ])).unwrap();
+        let result = &analysis.offers[0];
+
+        assert!(result.monthly_savings < Decimal::ZERO);
+        assert!(result.npv_of_savings < Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_biweekly_offer_is_compared_month_by_month() {
+        // Re-amortizing the balance at the same rate over the remaining 26 years costs a
+        // thirteenth monthly payment each year, so it never saves money
+        let mut biweekly = offer("biweekly", dec!(0.07), dec!(3000));
+        biweekly.term_years = 26;
+        biweekly.repayment_type = RepaymentType::AcceleratedBiweekly;
+        let analysis = RefinanceCalculator::analyze(&refinance_input(vec![biweekly])).unwrap();
+        let result = &analysis.offers[0];
+
+        assert!(result.monthly_savings.abs() < dec!(1));
+        assert_eq!(result.break_even_month, None);
+        assert!(result.npv_of_savings < dec!(-3000));
+    }
+
+    #[test]
+    fn test_floating_offer_summary_describes_compared_schedule() {
+        let mut floating = offer("floating", dec!(0.05), dec!(0));
+        floating.repayment_type = RepaymentType::FloatingRate;
+        let analysis = RefinanceCalculator::analyze(&refinance_input(vec![floating])).unwrap();
+        let result = &analysis.offers[0];
+
+        assert_eq!(result.summary.total_interest, result.new_total_interest);
+        assert_eq!(result.summary.monthly_payment, result.new_monthly_payment);
+    }
+
+    #[test]
+    fn test_current_balance_locates_payment_number() {
+        let mut input = refinance_input(vec![offer("6%", dec!(0.06), dec!(0))]);
+        let by_number = RefinanceCalculator::analyze(&input).unwrap();
+
+        input.current_payment_number = None;
+        input.current_balance = Some(by_number.current_balance);
+        let by_balance = RefinanceCalculator::analyze(&input).unwrap();
+
+        assert_eq!(by_balance.current_payment_number, 48);
+    }
+
+    #[test]
+    fn test_financed_costs_increase_loan() {
+        let mut financed = offer("financed", dec!(0.06), dec!(5000));
+        financed.finance_closing_costs = true;
+        financed.points = dec!(1);
+        let analysis = RefinanceCalculator::analyze(&refinance_input(vec![financed])).unwrap();
+        let result = &analysis.offers[0];
+
+        assert_eq!(result.new_loan_amount, analysis.current_balance + result.total_closing_costs);
+    }
+
+    #[test]
+    fn test_prepayment_penalty_delays_break_even() {
+        let without_penalty = RefinanceCalculator::analyze(&refinance_input(vec![
+            offer("5.5%", dec!(0.055), dec!(4000)),
+        ])).unwrap();
+
+        let mut input = refinance_input(vec![offer("5.5%", dec!(0.055), dec!(4000))]);
+        input.existing_loan.prepayment_penalty = Some(PrepaymentPenalty::StepDown {
+            percentages: vec![dec!(3), dec!(2), dec!(1), dec!(1), dec!(1)],
+        });
+        let with_penalty = RefinanceCalculator::analyze(&input).unwrap();
+
+        // Forty-eight payments made falls in the fifth loan year
+        assert_eq!(with_penalty.prepayment_penalty, DecimalUtils::round_currency(with_penalty.current_balance * dec!(0.01)));
+        assert_eq!(
+            with_penalty.offers[0].total_closing_costs,
+            without_penalty.offers[0].total_closing_costs + with_penalty.prepayment_penalty
+        );
+        assert!(with_penalty.offers[0].break_even_month > without_penalty.offers[0].break_even_month);
+    }
+
+    #[test]
+    fn test_repaid_loan_rejected() {
+        let mut input = refinance_input(vec![offer("6%", dec!(0.06), dec!(0))]);
+        input.current_payment_number = Some(360);
+
+        assert!(matches!(
+            RefinanceCalculator::analyze(&input),
+            Err(RefinanceError::LoanRepaid { .. })
+        ));
+    }
+}
@@ -0,0 +1,227 @@
+use std::sync::{Arc, OnceLock, PoisonError, RwLock, RwLockReadGuard};
+
+use serde::{de, Deserialize, Deserializer};
+
+use crate::models::{MortgageInput, MortgageSchedule, MortgageSummary, RepaymentType, RepaymentTypeInfo};
+use super::{
+    CalculationError, MortgageCalculator,
+    StandardCalculator, InterestOnlyCalculator, AcceleratedCalculator, BalloonCalculator, FloatingRateCalculator,
+};
+
+static REGISTRY: OnceLock<RwLock<CalculatorRegistry>> = OnceLock::new();
+
+/// Calculators keyed by the repayment type they price, in registration order
+#[derive(Clone)]
+pub struct CalculatorRegistry {
+    calculators: Vec<Arc<dyn MortgageCalculator>>,
+}
+
+impl CalculatorRegistry {
+    /// Registry without any calculators
+    pub fn empty() -> Self {
+        Self { calculators: Vec::new() }
+    }
+
+    /// Adds a calculator, replacing any already registered for its repayment type
+    pub fn register(&mut self, calculator: impl MortgageCalculator + 'static) {
+        let repayment_type = calculator.repayment_type();
+        let calculator: Arc<dyn MortgageCalculator> = Arc::new(calculator);
+        match self.calculators.iter().position(|existing| existing.repayment_type() == repayment_type) {
+            Some(index) => self.calculators[index] = calculator,
+            None => self.calculators.push(calculator),
+        }
+    }
+
+    /// Calculator registered for a repayment type
+    pub fn get(&self, repayment_type: RepaymentType) -> Option<Arc<dyn MortgageCalculator>> {
+        self.calculators.iter()
+            .find(|calculator| calculator.repayment_type() == repayment_type)
+            .cloned()
+    }
+
+    /// Registered repayment type serialized as `code`
+    pub fn repayment_type(&self, code: &str) -> Option<RepaymentType> {
+        self.calculators.iter()
+            .map(|calculator| calculator.repayment_type())
+            .find(|repayment_type| repayment_type.code() == code)
+    }
+
+    /// Registered repayment types, in registration order
+    pub fn repayment_types(&self) -> Vec<RepaymentType> {
+        self.calculators.iter().map(|calculator| calculator.repayment_type()).collect()
+    }
+
+    /// Describes every registered repayment type
+    pub fn infos(&self) -> Vec<RepaymentTypeInfo> {
+        self.calculators.iter().map(|calculator| calculator.info()).collect()
+    }
+
+    /// Calculates the schedule with the calculator registered for the input's repayment type
+    pub fn calculate_schedule(&self, input: &MortgageInput) -> Result<MortgageSchedule, CalculationError> {
+        self.require(input.repayment_type)?.calculate_schedule(input)
+    }
+
+    /// Calculates the summary with the calculator registered for the input's repayment type
+    pub fn calculate_summary(&self, input: &MortgageInput) -> Result<MortgageSummary, CalculationError> {
+        self.require(input.repayment_type)?.calculate_summary(input)
+    }
+
+    fn require(&self, repayment_type: RepaymentType) -> Result<Arc<dyn MortgageCalculator>, CalculationError> {
+        self.get(repayment_type).ok_or(CalculationError::UnregisteredRepaymentType(repayment_type))
+    }
+}
+
+impl Default for CalculatorRegistry {
+    /// Registry holding the built-in calculators
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(StandardCalculator);
+        registry.register(InterestOnlyCalculator);
+        registry.register(AcceleratedCalculator);
+        registry.register(BalloonCalculator);
+        registry.register(FloatingRateCalculator);
+        registry
+    }
+}
+
+fn global() -> &'static RwLock<CalculatorRegistry> {
+    REGISTRY.get_or_init(|| RwLock::new(CalculatorRegistry::default()))
+}
+
+fn read_global() -> RwLockReadGuard<'static, CalculatorRegistry> {
+    global().read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Registers a calculator process-wide, replacing any registered for its repayment type
+///
+/// Registered custom products are accepted by the API, listed by `/repayment-types`
+/// and returned by `RepaymentTypeInfo::all()`.
+pub fn register_calculator(calculator: impl MortgageCalculator + 'static) {
+    global().write().unwrap_or_else(PoisonError::into_inner).register(calculator);
+}
+
+/// Snapshot of the process-wide registry
+///
+/// Calculators run outside the lock, so they may themselves consult the registry.
+pub fn registry() -> CalculatorRegistry {
+    read_global().clone()
+}
+
+/// Calculator registered process-wide for a repayment type
+pub fn calculator_for(repayment_type: RepaymentType) -> Option<Arc<dyn MortgageCalculator>> {
+    read_global().get(repayment_type)
+}
+
+/// Built-in or process-wide registered repayment type serialized as `code`
+pub fn repayment_type_for(code: &str) -> Option<RepaymentType> {
+    RepaymentType::builtin_from_code(code).or_else(|| read_global().repayment_type(code))
+}
+
+/// Repayment types registered process-wide, custom products included
+pub fn repayment_types() -> Vec<RepaymentType> {
+    read_global().repayment_types()
+}
+
+/// Describes every repayment type registered process-wide
+pub fn repayment_type_infos() -> Vec<RepaymentTypeInfo> {
+    read_global().infos()
+}
+
+/// Custom codes are only known to the registry, so repayment types are parsed here rather than in `models`
+impl<'de> Deserialize<'de> for RepaymentType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        repayment_type_for(&code).ok_or_else(|| {
+            let expected = repayment_types().iter()
+                .map(|repayment_type| format!("`{}`", repayment_type.code()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            de::Error::custom(format!("unknown variant `{}`, expected one of {}", code, expected))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+    use crate::models::CustomRepaymentType;
+
+    static STRAIGHT_LINE: CustomRepaymentType = CustomRepaymentType::new("straightLine", "Straight Line");
+
+    /// Pays the same share of the original principal every month
+    struct StraightLineCalculator;
+
+    impl MortgageCalculator for StraightLineCalculator {
+        fn repayment_type(&self) -> RepaymentType {
+            RepaymentType::Custom(&STRAIGHT_LINE)
+        }
+
+        fn description(&self) -> &str {
+            "Equal principal every month with interest on the remaining balance."
+        }
+
+        fn calculate_schedule(&self, input: &MortgageInput) -> Result<MortgageSchedule, CalculationError> {
+            let mut schedule = StandardCalculator.calculate_schedule(input)?;
+            schedule.monthly_payment = input.principal / rust_decimal::Decimal::from(input.term_years * 12);
+            Ok(schedule)
+        }
+    }
+
+    fn loan(repayment_type: RepaymentType) -> MortgageInput {
+        MortgageInput::new(
+            dec!(120000),
+            dec!(0.05),
+            10,
+            repayment_type,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_default_registry_dispatches_builtins() {
+        let registry = CalculatorRegistry::default();
+        assert_eq!(registry.repayment_types(), RepaymentType::all());
+        for repayment_type in RepaymentType::all() {
+            let summary = registry.calculate_summary(&loan(repayment_type)).unwrap();
+            assert_eq!(summary.repayment_type, repayment_type);
+        }
+    }
+
+    #[test]
+    fn test_register_custom_product() {
+        let mut registry = CalculatorRegistry::default();
+        registry.register(StraightLineCalculator);
+        let product = RepaymentType::Custom(&STRAIGHT_LINE);
+
+        assert_eq!(registry.repayment_types().last(), Some(&product));
+        assert_eq!(registry.infos().last().unwrap().name, "Straight Line");
+        let summary = registry.calculate_summary(&loan(product)).unwrap();
+        assert_eq!(summary.repayment_type, product);
+        assert_eq!(summary.monthly_payment, dec!(1000));
+    }
+
+    #[test]
+    fn test_register_replaces_and_unregistered_errors() {
+        let mut registry = CalculatorRegistry::empty();
+        assert_eq!(
+            registry.calculate_schedule(&loan(RepaymentType::InterestOnly)).unwrap_err(),
+            CalculationError::UnregisteredRepaymentType(RepaymentType::InterestOnly)
+        );
+
+        registry.register(StraightLineCalculator);
+        registry.register(StraightLineCalculator);
+        assert_eq!(registry.repayment_types().len(), 1);
+    }
+
+    #[test]
+    fn test_codes_deserialize() {
+        for repayment_type in RepaymentType::all() {
+            let json = serde_json::to_string(&repayment_type).unwrap();
+            assert_eq!(serde_json::from_str::<RepaymentType>(&json).unwrap(), repayment_type);
+        }
+        let error = serde_json::from_str::<RepaymentType>("\"graduated\"").unwrap_err();
+        assert!(error.to_string().starts_with("unknown variant `graduated`"));
+    }
+}
@@ -0,0 +1,204 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::models::{MortgageInput, RentVsBuyAnalysis, RentVsBuyInput, RentVsBuyYear};
+use crate::utils::DecimalUtils;
+use super::{calculate_schedule, CalculationError};
+
+/// Cap on the state and local tax deduction, which limits deductible property tax
+const SALT_DEDUCTION_CAP: Decimal = dec!(10000);
+
+/// Projects owning a home against renting and investing the difference
+///
+/// Both households spend the same each year: whichever path is cheaper invests
+/// the gap at year end, and the renter starts by investing the buyer's upfront cash.
+pub struct RentVsBuyCalculator;
+
+impl RentVsBuyCalculator {
+    pub fn analyze(input: &RentVsBuyInput) -> Result<RentVsBuyAnalysis, CalculationError> {
+        let loan = Self::loan_input(input);
+        let schedule = calculate_schedule(&loan)?;
+        let payments_per_year = input.repayment_type.payments_per_year();
+
+        let upfront_cash = input.down_payment
+            + input.closing_costs
+            + schedule.upfront_mortgage_insurance.unwrap_or(Decimal::ZERO);
+
+        let mut loan_balance = loan.principal;
+        let mut owner_investments = Decimal::ZERO;
+        let mut renter_investments = upfront_cash;
+        let mut years = Vec::with_capacity(input.horizon_years as usize);
+
+        for year in 1..=input.horizon_years {
+            let elapsed = i64::from(year - 1);
+            let opening_value = input.home_price * DecimalUtils::power(dec!(1) + input.home_appreciation_rate, elapsed);
+            let home_value = opening_value * (dec!(1) + input.home_appreciation_rate);
+            let inflation = DecimalUtils::power(dec!(1) + input.cost_inflation_rate, elapsed);
+
+            let mut debt_service = Decimal::ZERO;
+            let mut interest = Decimal::ZERO;
+            for entry in schedule.schedule.iter()
+                .filter(|entry| (entry.payment_number - 1) / payments_per_year == year - 1)
+            {
+                debt_service += entry.payment_amount + entry.mortgage_insurance.unwrap_or(Decimal::ZERO);
+                interest += entry.interest_component;
+                loan_balance = entry.remaining_principal;
+            }
+
+            let property_tax = opening_value * input.property_tax_rate;
+            let deductions = interest + property_tax.min(SALT_DEDUCTION_CAP);
+            let tax_savings = (deductions - input.standard_deduction).max(Decimal::ZERO) * input.marginal_tax_rate;
+
+            let ownership_cost = debt_service
+                + property_tax
+                + input.annual_insurance * inflation
+                + input.monthly_hoa * dec!(12) * inflation
+                + opening_value * input.maintenance_rate
+                - tax_savings;
+            let rental_cost = input.monthly_rent * dec!(12)
+                * DecimalUtils::power(dec!(1) + input.rent_growth_rate, elapsed)
+                + input.annual_renters_insurance;
+
+            let growth = dec!(1) + input.investment_return_rate;
+            owner_investments = owner_investments * growth + (rental_cost - ownership_cost).max(Decimal::ZERO);
+            renter_investments = renter_investments * growth + (ownership_cost - rental_cost).max(Decimal::ZERO);
+
+            let owner_equity = home_value * (dec!(1) - input.selling_cost_rate) - loan_balance;
+            let owner_net_worth = owner_equity + owner_investments;
+
+            years.push(RentVsBuyYear {
+                year,
+                home_value: DecimalUtils::round_currency(home_value),
+                loan_balance: DecimalUtils::round_currency(loan_balance),
+                ownership_cost: DecimalUtils::round_currency(ownership_cost),
+                rental_cost: DecimalUtils::round_currency(rental_cost),
+                tax_savings: DecimalUtils::round_currency(tax_savings),
+                owner_equity: DecimalUtils::round_currency(owner_equity),
+                owner_investments: DecimalUtils::round_currency(owner_investments),
+                owner_net_worth: DecimalUtils::round_currency(owner_net_worth),
+                renter_net_worth: DecimalUtils::round_currency(renter_investments),
+                buy_advantage: DecimalUtils::round_currency(owner_net_worth - renter_investments),
+            });
+        }
+
+        // Buying wins from the first year after which it never falls behind again
+        let crossover_year = years.iter()
+            .rev()
+            .take_while(|year| year.buy_advantage >= Decimal::ZERO)
+            .last()
+            .map(|year| year.year);
+
+        Ok(RentVsBuyAnalysis {
+            loan_amount: loan.principal,
+            upfront_cash: DecimalUtils::round_currency(upfront_cash),
+            monthly_payment: schedule.monthly_payment,
+            crossover_year,
+            years,
+        })
+    }
+
+    /// Loan financing the purchase; taxes and insurance are projected separately
+    fn loan_input(input: &RentVsBuyInput) -> MortgageInput {
+        MortgageInput {
+            property_value: Some(input.home_price),
+            mortgage_insurance: input.mortgage_insurance.clone(),
+            ..MortgageInput::new(
+                input.home_price - input.down_payment,
+                input.annual_interest_rate,
+                input.term_years,
+                input.repayment_type,
+                input.start_date,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::models::{MortgageInsuranceInput, MortgageInsuranceType, RepaymentType};
+
+    fn rent_vs_buy_input(monthly_rent: Decimal) -> RentVsBuyInput {
+        RentVsBuyInput {
+            home_price: dec!(400000),
+            down_payment: dec!(80000),
+            annual_interest_rate: dec!(0.065),
+            term_years: 30,
+            repayment_type: RepaymentType::StandardPrincipalAndInterest,
+            start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            mortgage_insurance: None,
+            closing_costs: dec!(8000),
+            selling_cost_rate: dec!(0.06),
+            property_tax_rate: dec!(0.012),
+            annual_insurance: dec!(1500),
+            monthly_hoa: dec!(0),
+            maintenance_rate: dec!(0.01),
+            home_appreciation_rate: dec!(0.03),
+            cost_inflation_rate: dec!(0.03),
+            marginal_tax_rate: dec!(0),
+            standard_deduction: dec!(0),
+            monthly_rent,
+            rent_growth_rate: dec!(0.03),
+            annual_renters_insurance: dec!(200),
+            investment_return_rate: dec!(0.05),
+            horizon_years: 30,
+        }
+    }
+
+    #[test]
+    fn test_renter_starts_ahead_and_buyer_crosses_over() {
+        let analysis = RentVsBuyCalculator::analyze(&rent_vs_buy_input(dec!(2500))).unwrap();
+        let crossover = analysis.crossover_year.unwrap();
+
+        assert_eq!(analysis.upfront_cash, dec!(88000));
+        assert!(analysis.years[0].buy_advantage < Decimal::ZERO);
+        assert!(crossover > 1 && crossover < 30);
+        assert!(analysis.years[crossover as usize - 1].buy_advantage >= Decimal::ZERO);
+        assert!(analysis.years[crossover as usize - 2].buy_advantage < Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_cheap_rent_never_crosses_over() {
+        let analysis = RentVsBuyCalculator::analyze(&rent_vs_buy_input(dec!(800))).unwrap();
+
+        assert_eq!(analysis.crossover_year, None);
+        assert!(analysis.years.iter().all(|year| year.buy_advantage < Decimal::ZERO));
+    }
+
+    #[test]
+    fn test_loan_repaid_within_horizon() {
+        let mut input = rent_vs_buy_input(dec!(2500));
+        input.term_years = 15;
+        input.horizon_years = 20;
+        let analysis = RentVsBuyCalculator::analyze(&input).unwrap();
+
+        assert_eq!(analysis.years[14].loan_balance, Decimal::ZERO);
+        assert!(analysis.years[15].ownership_cost < analysis.years[14].ownership_cost);
+    }
+
+    #[test]
+    fn test_tax_savings_and_mortgage_insurance_change_costs() {
+        let base = RentVsBuyCalculator::analyze(&rent_vs_buy_input(dec!(2500))).unwrap();
+
+        let mut deducting = rent_vs_buy_input(dec!(2500));
+        deducting.marginal_tax_rate = dec!(0.24);
+        deducting.standard_deduction = dec!(14600);
+        let deducting = RentVsBuyCalculator::analyze(&deducting).unwrap();
+        assert!(deducting.years[0].tax_savings > Decimal::ZERO);
+        assert!(deducting.years[0].ownership_cost < base.years[0].ownership_cost);
+
+        let mut uninsured = rent_vs_buy_input(dec!(2500));
+        uninsured.down_payment = dec!(20000);
+        let mut insured = uninsured.clone();
+        insured.mortgage_insurance = Some(MortgageInsuranceInput {
+            insurance_type: MortgageInsuranceType::PrivateMortgageInsurance,
+            credit_score: Some(700),
+            annual_rate: None,
+            borrower_requested_cancellation: false,
+        });
+        let uninsured = RentVsBuyCalculator::analyze(&uninsured).unwrap();
+        let insured = RentVsBuyCalculator::analyze(&insured).unwrap();
+        assert!(insured.years[0].ownership_cost > uninsured.years[0].ownership_cost);
+    }
+}
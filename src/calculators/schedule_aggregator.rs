@@ -0,0 +1,136 @@
+use chrono::Datelike;
+
+use crate::models::{PaymentScheduleEntry, ScheduleAggregation, ScheduleQuery, ScheduleYear};
+
+/// Selects and rolls up schedule rows as requested by a `ScheduleQuery`
+pub struct ScheduleAggregator;
+
+impl ScheduleAggregator {
+    /// Payments inside the query's date range, with how many there are before paging
+    pub fn select(
+        entries: impl IntoIterator<Item = PaymentScheduleEntry>,
+        query: &ScheduleQuery,
+    ) -> (Vec<PaymentScheduleEntry>, usize) {
+        let selected = entries.into_iter().filter(|entry| query.includes(entry.payment_date)).collect();
+        Self::page(selected, query)
+    }
+
+    /// Yearly totals of the payments inside the query's date range, with how many years there are before paging
+    pub fn yearly(
+        entries: impl IntoIterator<Item = PaymentScheduleEntry>,
+        query: &ScheduleQuery,
+        aggregation: ScheduleAggregation,
+        payments_per_year: u32,
+    ) -> (Vec<ScheduleYear>, usize) {
+        let entries = entries.into_iter().filter(|entry| query.includes(entry.payment_date));
+        Self::page(Self::annual_totals(entries, aggregation, payments_per_year), query)
+    }
+
+    /// Rolls consecutive payments up into calendar or loan years
+    pub fn annual_totals(
+        entries: impl IntoIterator<Item = PaymentScheduleEntry>,
+        aggregation: ScheduleAggregation,
+        payments_per_year: u32,
+    ) -> Vec<ScheduleYear> {
+        let mut years: Vec<ScheduleYear> = Vec::new();
+        for entry in entries {
+            let year = match aggregation {
+                ScheduleAggregation::CalendarYear => entry.payment_date.year(),
+                ScheduleAggregation::LoanYear =>
+                    ((entry.payment_number.saturating_sub(1)) / payments_per_year.max(1)) as i32 + 1,
+            };
+            match years.last_mut().filter(|totals| totals.year == year) {
+                Some(totals) => {
+                    totals.last_payment_date = entry.payment_date;
+                    totals.number_of_payments += 1;
+                    totals.total_payment += entry.payment_amount;
+                    totals.total_principal += entry.principal_component;
+                    totals.total_interest += entry.interest_component;
+                    totals.ending_balance = entry.remaining_principal;
+                }
+                None => years.push(ScheduleYear {
+                    year,
+                    first_payment_date: entry.payment_date,
+                    last_payment_date: entry.payment_date,
+                    number_of_payments: 1,
+                    total_payment: entry.payment_amount,
+                    total_principal: entry.principal_component,
+                    total_interest: entry.interest_component,
+                    ending_balance: entry.remaining_principal,
+                }),
+            }
+        }
+        years
+    }
+
+    /// The requested page of rows, with the row count before paging
+    fn page<T>(rows: Vec<T>, query: &ScheduleQuery) -> (Vec<T>, usize) {
+        let total = rows.len();
+        let rows = match query.page_bounds() {
+            Some((skip, take)) => rows.into_iter().skip(skip).take(take).collect(),
+            None => rows,
+        };
+        (rows, total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+    use rust_decimal::Decimal;
+    use crate::calculators::{MortgageCalculator, StandardCalculator};
+    use crate::models::{MortgageInput, MortgageSchedule, RepaymentType};
+
+    fn schedule() -> MortgageSchedule {
+        StandardCalculator.calculate_schedule(&MortgageInput::new(
+            dec!(200000),
+            dec!(0.06),
+            30,
+            RepaymentType::StandardPrincipalAndInterest,
+            NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+        )).unwrap()
+    }
+
+    #[test]
+    fn test_pages_and_date_range() {
+        let schedule = schedule();
+        let query = ScheduleQuery {
+            page: Some(2),
+            page_size: Some(10),
+            from: NaiveDate::from_ymd_opt(2025, 1, 1),
+            to: NaiveDate::from_ymd_opt(2026, 12, 31),
+            aggregate: None,
+        };
+        let (rows, total) = ScheduleAggregator::select(schedule.schedule, &query);
+
+        assert_eq!(total, 24);
+        assert_eq!(rows.len(), 10);
+        assert!(rows.iter().all(|entry| query.includes(entry.payment_date)));
+        assert_eq!(rows[0].payment_number, 19);
+    }
+
+    #[test]
+    fn test_loan_years_add_up_to_schedule() {
+        let schedule = schedule();
+        let years = ScheduleAggregator::annual_totals(schedule.schedule.clone(), ScheduleAggregation::LoanYear, 12);
+
+        assert_eq!(years.len(), 30);
+        assert!(years.iter().all(|year| year.number_of_payments == 12));
+        let total_interest: Decimal = years.iter().map(|year| year.total_interest).sum();
+        assert_eq!(total_interest.round_dp(8), schedule.total_interest.round_dp(8));
+        assert_eq!(years[0].ending_balance, schedule.schedule[11].remaining_principal);
+    }
+
+    #[test]
+    fn test_calendar_years_split_on_january() {
+        let schedule = schedule();
+        let years = ScheduleAggregator::annual_totals(schedule.schedule.clone(), ScheduleAggregation::CalendarYear, 12);
+
+        assert_eq!(years[0].year, 2024);
+        assert_eq!(years[1].year, 2025);
+        assert_eq!(years[0].last_payment_date.year(), 2024);
+        assert_eq!(years.iter().map(|year| year.number_of_payments as usize).sum::<usize>(), schedule.payment_count());
+    }
+}
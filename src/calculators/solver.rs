@@ -0,0 +1,389 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal_macros::dec;
+use thiserror::Error;
+
+use crate::models::{
+    MortgageInput,
+    MortgageSummary,
+    RepaymentType,
+    SolveRequest,
+    SolveResult,
+    SolveTarget,
+    SolveVariable,
+};
+use super::{calculate_summary, schedule_entries, summarize_schedule, CalculationError, FloatingRateCalculator};
+
+/// Upper bound on calculator evaluations per solve
+const MAX_EVALUATIONS: u32 = 100;
+
+/// Currency targets are considered met within half a cent
+const CURRENCY_TOLERANCE: Decimal = dec!(0.005);
+
+/// Longest term a solve can reach, in months
+const MAX_TERM_MONTHS: u32 = 50 * 12;
+
+/// Errors raised while goal-seeking
+#[derive(Debug, Error, PartialEq)]
+pub enum SolveError {
+    #[error("cannot solve for a {target}: {reason}")]
+    UnsupportedTarget { target: String, reason: &'static str },
+
+    #[error("no {variable} between {low} and {high} reaches a {target}")]
+    Unattainable {
+        variable: SolveVariable,
+        target: String,
+        low: Decimal,
+        high: Decimal,
+    },
+
+    #[error(transparent)]
+    Calculation(#[from] CalculationError),
+}
+
+/// Goal-seeks one loan input by bisection over the real calculators
+pub struct GoalSeekSolver;
+
+impl GoalSeekSolver {
+    pub fn solve(request: &SolveRequest) -> Result<SolveResult, SolveError> {
+        if matches!(request.target, SolveTarget::BalloonAmount { .. })
+            && request.input.repayment_type != RepaymentType::BalloonPayment
+        {
+            return Err(Self::unsupported(request, "the target requires a balloon payment loan"));
+        }
+        if request.input.repayment_type == RepaymentType::FloatingRate
+            && request.variable == SolveVariable::AnnualInterestRate
+        {
+            return Err(Self::unsupported(request, "floating-rate loans draw their own rates"));
+        }
+
+        // Bisection needs every candidate to see the same floating rates
+        let rates = (request.input.repayment_type == RepaymentType::FloatingRate)
+            .then(|| FloatingRateCalculator::draw_rates(MAX_TERM_MONTHS));
+        let rates = rates.as_deref();
+
+        let mut evaluator = Evaluator::new(request, rates);
+        let (mut low, mut high) = Self::bounds(request.variable, &request.input);
+        let mut low_offset = evaluator.offset(low)?;
+        let mut high_offset = evaluator.offset(high)?;
+
+        if !low_offset.is_zero() && !high_offset.is_zero()
+            && (low_offset < Decimal::ZERO) == (high_offset < Decimal::ZERO)
+        {
+            return Err(SolveError::Unattainable {
+                variable: request.variable,
+                target: request.target.to_string(),
+                low,
+                high,
+            });
+        }
+
+        let resolution = Self::resolution(request.variable);
+        let tolerance = match request.target {
+            SolveTarget::PayoffDate { .. } => Decimal::ZERO,
+            _ => CURRENCY_TOLERANCE,
+        };
+
+        while evaluator.evaluations < MAX_EVALUATIONS
+            && high - low > resolution
+            && low_offset.abs() > tolerance
+            && high_offset.abs() > tolerance
+        {
+            let mid = ((low + high) / dec!(2) / resolution).floor() * resolution;
+            let mid = if mid <= low { low + resolution } else { mid };
+
+            let mid_offset = evaluator.offset(mid)?;
+            if (mid_offset < Decimal::ZERO) == (low_offset < Decimal::ZERO) {
+                low = mid;
+                low_offset = mid_offset;
+            } else {
+                high = mid;
+                high_offset = mid_offset;
+            }
+        }
+
+        let solved_value = Self::pick(&request.target, (low, low_offset), (high, high_offset));
+        let input = Self::with_value(&request.input, request.variable, solved_value);
+        let achieved = Self::achieved(&request.target, &input, rates)?;
+        let summary = Self::summary(&input, rates)?;
+
+        Ok(SolveResult {
+            variable: request.variable,
+            solved_value,
+            achieved,
+            iterations: evaluator.evaluations,
+            input,
+            summary,
+        })
+    }
+
+    /// Search interval for each variable, kept inside `MortgageInput` validation
+    fn bounds(variable: SolveVariable, input: &MortgageInput) -> (Decimal, Decimal) {
+        match variable {
+            SolveVariable::Principal => (dec!(1), dec!(1_000_000_000)),
+            SolveVariable::AnnualInterestRate => (Decimal::ZERO, dec!(1)),
+            SolveVariable::TermYears => (dec!(1), dec!(50)),
+            SolveVariable::ExtraPayment => (Decimal::ZERO, input.principal.round_dp(2)),
+        }
+    }
+
+    /// Applies a candidate value to a copy of the base input
+    fn with_value(input: &MortgageInput, variable: SolveVariable, value: Decimal) -> MortgageInput {
+        let mut input = input.clone();
+        match variable {
+            SolveVariable::Principal => input.principal = value,
+            SolveVariable::AnnualInterestRate => input.annual_interest_rate = value,
+            SolveVariable::TermYears => input.term_years = value.to_u32().unwrap_or(1),
+            SolveVariable::ExtraPayment => input.extra_payment = value,
+        }
+        input
+    }
+
+    /// Summary of a candidate loan, following `rates` when it floats
+    fn summary(input: &MortgageInput, rates: Option<&[Decimal]>) -> Result<MortgageSummary, CalculationError> {
+        match rates {
+            Some(rates) => summarize_schedule(input, &FloatingRateCalculator::schedule_with_rates(input, rates)?),
+            None => calculate_summary(input),
+        }
+    }
+
+    /// Measures the targeted outcome of a loan on the target's scale
+    fn measure(target: &SolveTarget, input: &MortgageInput, rates: Option<&[Decimal]>) -> Result<Decimal, CalculationError> {
+        Ok(match target {
+            SolveTarget::MonthlyPayment { .. } => {
+                let summary = Self::summary(input, rates)?;
+                let payments_per_year = Decimal::from(input.repayment_type.payments_per_year());
+                summary.monthly_payment + input.extra_payment * payments_per_year / dec!(12)
+            }
+            SolveTarget::TotalInterest { .. } => Self::summary(input, rates)?.total_interest,
+            SolveTarget::PayoffDate { .. } => {
+                Decimal::from((Self::payoff_date(input, rates)? - input.start_date).num_days())
+            }
+            SolveTarget::BalloonAmount { .. } => {
+                Self::summary(input, rates)?.balloon_payment.unwrap_or(Decimal::ZERO)
+            }
+        })
+    }
+
+    /// Target expressed on the same scale as `measure`
+    fn goal(target: &SolveTarget, input: &MortgageInput) -> Decimal {
+        match target {
+            SolveTarget::MonthlyPayment { value }
+            | SolveTarget::TotalInterest { value }
+            | SolveTarget::BalloonAmount { value } => *value,
+            SolveTarget::PayoffDate { date } => Decimal::from((*date - input.start_date).num_days()),
+        }
+    }
+
+    /// Outcome of the solved loan, in the shape of the requested target
+    fn achieved(target: &SolveTarget, input: &MortgageInput, rates: Option<&[Decimal]>) -> Result<SolveTarget, CalculationError> {
+        Ok(match target {
+            SolveTarget::MonthlyPayment { .. } =>
+                SolveTarget::MonthlyPayment { value: Self::measure(target, input, rates)? },
+            SolveTarget::TotalInterest { .. } =>
+                SolveTarget::TotalInterest { value: Self::measure(target, input, rates)? },
+            SolveTarget::PayoffDate { .. } => SolveTarget::PayoffDate {
+                date: Self::payoff_date(input, rates)?,
+            },
+            SolveTarget::BalloonAmount { .. } =>
+                SolveTarget::BalloonAmount { value: Self::measure(target, input, rates)? },
+        })
+    }
+
+    /// Date of the final payment, streamed rather than collecting the schedule unless it floats
+    fn payoff_date(input: &MortgageInput, rates: Option<&[Decimal]>) -> Result<NaiveDate, CalculationError> {
+        let final_payment = match rates {
+            Some(rates) => FloatingRateCalculator::schedule_with_rates(input, rates)?.schedule.pop(),
+            None => schedule_entries(input)?.last(),
+        };
+        Ok(final_payment.map_or(input.start_date, |entry| entry.payment_date))
+    }
+
+    /// Chooses the better end of the final bracket
+    fn pick(target: &SolveTarget, low: (Decimal, Decimal), high: (Decimal, Decimal)) -> Decimal {
+        match target {
+            // Prefer paying off on or before the target date
+            SolveTarget::PayoffDate { .. } => match (low.1 <= Decimal::ZERO, high.1 <= Decimal::ZERO) {
+                (true, false) => low.0,
+                (false, true) => high.0,
+                _ => if low.1.abs() <= high.1.abs() { low.0 } else { high.0 },
+            },
+            _ => if low.1.abs() <= high.1.abs() { low.0 } else { high.0 },
+        }
+    }
+
+    /// Rejects a request the solver cannot answer, saying why
+    fn unsupported(request: &SolveRequest, reason: &'static str) -> SolveError {
+        SolveError::UnsupportedTarget { target: request.target.to_string(), reason }
+    }
+
+    /// Smallest step worth distinguishing for each variable
+    fn resolution(variable: SolveVariable) -> Decimal {
+        match variable {
+            SolveVariable::Principal | SolveVariable::ExtraPayment => dec!(0.01),
+            SolveVariable::AnnualInterestRate => dec!(0.000001),
+            SolveVariable::TermYears => dec!(1),
+        }
+    }
+}
+
+/// Counts calculator runs while measuring distance from the goal
+struct Evaluator<'a> {
+    request: &'a SolveRequest,
+    rates: Option<&'a [Decimal]>,
+    goal: Decimal,
+    evaluations: u32,
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(request: &'a SolveRequest, rates: Option<&'a [Decimal]>) -> Self {
+        Self {
+            request,
+            rates,
+            goal: GoalSeekSolver::goal(&request.target, &request.input),
+            evaluations: 0,
+        }
+    }
+
+    fn offset(&mut self, value: Decimal) -> Result<Decimal, CalculationError> {
+        self.evaluations += 1;
+        let input = GoalSeekSolver::with_value(&self.request.input, self.request.variable, value);
+        Ok(GoalSeekSolver::measure(&self.request.target, &input, self.rates)? - self.goal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn base_input(repayment_type: RepaymentType) -> MortgageInput {
+        MortgageInput {
+            balloon_payment_percentage: dec!(20),
+            ..MortgageInput::new(
+                dec!(300000),
+                dec!(0.05),
+                30,
+                repayment_type,
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            )
+        }
+    }
+
+    fn request(target: SolveTarget, variable: SolveVariable) -> SolveRequest {
+        SolveRequest {
+            input: base_input(RepaymentType::StandardPrincipalAndInterest),
+            target,
+            variable,
+        }
+    }
+
+    #[test]
+    fn test_solve_principal_for_payment() {
+        let result = GoalSeekSolver::solve(&request(
+            SolveTarget::MonthlyPayment { value: dec!(1610.46) },
+            SolveVariable::Principal,
+        )).unwrap();
+
+        match result.achieved {
+            SolveTarget::MonthlyPayment { value } => assert!((value - dec!(1610.46)).abs() <= CURRENCY_TOLERANCE),
+            other => panic!("unexpected achieved target {:?}", other),
+        }
+        assert!((result.solved_value - dec!(300000)).abs() < dec!(2));
+    }
+
+    #[test]
+    fn test_solve_rate_for_payment() {
+        let result = GoalSeekSolver::solve(&request(
+            SolveTarget::MonthlyPayment { value: dec!(1610.46) },
+            SolveVariable::AnnualInterestRate,
+        )).unwrap();
+
+        assert!((result.solved_value - dec!(0.05)).abs() < dec!(0.0001));
+    }
+
+    #[test]
+    fn test_solve_term_for_payment() {
+        let result = GoalSeekSolver::solve(&request(
+            SolveTarget::MonthlyPayment { value: dec!(1980) },
+            SolveVariable::TermYears,
+        )).unwrap();
+
+        assert_eq!(result.solved_value, dec!(20));
+        assert_eq!(result.input.term_years, 20);
+    }
+
+    #[test]
+    fn test_solve_extra_payment_for_payoff_date() {
+        let target_date = NaiveDate::from_ymd_opt(2043, 1, 1).unwrap();
+        let result = GoalSeekSolver::solve(&request(
+            SolveTarget::PayoffDate { date: target_date },
+            SolveVariable::ExtraPayment,
+        )).unwrap();
+
+        match result.achieved {
+            SolveTarget::PayoffDate { date } => assert!(date <= target_date),
+            other => panic!("unexpected achieved target {:?}", other),
+        }
+        assert!(result.solved_value > Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_solve_principal_for_balloon() {
+        let mut solve_request = request(
+            SolveTarget::BalloonAmount { value: dec!(50000) },
+            SolveVariable::Principal,
+        );
+        solve_request.input.repayment_type = RepaymentType::BalloonPayment;
+        let result = GoalSeekSolver::solve(&solve_request).unwrap();
+
+        assert!((result.solved_value - dec!(250000)).abs() < dec!(1));
+    }
+
+    #[test]
+    fn test_balloon_target_requires_balloon_loan() {
+        let result = GoalSeekSolver::solve(&request(
+            SolveTarget::BalloonAmount { value: dec!(50000) },
+            SolveVariable::Principal,
+        ));
+
+        assert!(matches!(result, Err(SolveError::UnsupportedTarget { .. })));
+    }
+
+    #[test]
+    fn test_solve_floating_rate_loans() {
+        let mut solve_request = request(
+            SolveTarget::MonthlyPayment { value: dec!(1500) },
+            SolveVariable::Principal,
+        );
+        solve_request.input.repayment_type = RepaymentType::FloatingRate;
+        let result = GoalSeekSolver::solve(&solve_request).unwrap();
+
+        // The reported payment comes from the rate path the solve converged on
+        match result.achieved {
+            SolveTarget::MonthlyPayment { value } => {
+                assert!((value - dec!(1500)).abs() <= CURRENCY_TOLERANCE);
+                assert_eq!(result.summary.monthly_payment, value);
+            }
+            other => panic!("unexpected achieved target {:?}", other),
+        }
+
+        solve_request.target = SolveTarget::PayoffDate { date: NaiveDate::from_ymd_opt(2043, 1, 1).unwrap() };
+        solve_request.variable = SolveVariable::ExtraPayment;
+        assert!(GoalSeekSolver::solve(&solve_request).unwrap().solved_value > Decimal::ZERO);
+
+        solve_request.variable = SolveVariable::AnnualInterestRate;
+        assert!(matches!(GoalSeekSolver::solve(&solve_request), Err(SolveError::UnsupportedTarget { .. })));
+    }
+
+    #[test]
+    fn test_unattainable_target() {
+        let result = GoalSeekSolver::solve(&request(
+            SolveTarget::MonthlyPayment { value: dec!(100) },
+            SolveVariable::AnnualInterestRate,
+        ));
+
+        assert!(matches!(result, Err(SolveError::Unattainable { .. })));
+    }
+}
@@ -1,69 +1,71 @@
-use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use chrono::Duration;
 
-use crate::models::{MortgageInput, MortgageSchedule, PaymentScheduleEntry, MortgageSummary, RepaymentType};
-use crate::utils::DecimalUtils;
-use super::MortgageCalculator;
+use crate::models::{MortgageInput, MortgageSchedule, MortgageSummary, RepaymentType};
+use super::amortization::{is_plain_amortization, Amortization};
+use super::{
+    check_input, payment_factor, CalculationError, MortgageCalculator, ScheduleEntries,
+    ModificationCalculator, EscrowCalculator, MortgageInsuranceCalculator, PrepaymentPenaltyCalculator,
+};
 
 pub struct StandardCalculator;
 
 impl MortgageCalculator for StandardCalculator {
-    fn calculate_schedule(input: &MortgageInput) -> MortgageSchedule {
-        let monthly_rate = input.annual_interest_rate / dec!(12);
-        let num_payments = input.term_years * 12;
-        
-        // Use DecimalUtils::power instead of powi
-        let rate_factor = DecimalUtils::power(dec!(1) + monthly_rate, num_payments as i64);
-        let monthly_payment = input.principal * 
-            (monthly_rate * rate_factor) /
-            (rate_factor - dec!(1));
-
-        let mut schedule = Vec::with_capacity(num_payments as usize);
-        let mut remaining_principal = input.principal;
-        let mut total_interest = Decimal::ZERO;
-
-        for payment_number in 1..=num_payments {
-            let interest_component = remaining_principal * monthly_rate;
-            let principal_component = monthly_payment - interest_component;
-            
-            total_interest += interest_component;
-            remaining_principal -= principal_component;
-
-            let payment_date = input.start_date + 
-                Duration::days(((payment_number - 1) * 30) as i64);
-
-            schedule.push(PaymentScheduleEntry {
-                payment_date,
-                payment_number,
-                payment_amount: monthly_payment,
-                principal_component,
-                interest_component,
-                remaining_principal,
-                current_rate: Some(input.annual_interest_rate),
-            });
-        }
+    fn repayment_type(&self) -> RepaymentType {
+        RepaymentType::StandardPrincipalAndInterest
+    }
 
-        MortgageSchedule {
-            monthly_payment,
-            total_payments: monthly_payment * Decimal::from(num_payments),
-            total_interest,
-            schedule,
+    fn description(&self) -> &str {
+        "Regular monthly payments of both principal and interest over the loan term."
+    }
+
+    fn calculate_schedule(&self, input: &MortgageInput) -> Result<MortgageSchedule, CalculationError> {
+        let amortization = Self::amortization(input)?;
+        let monthly_payment = amortization.scheduled_payment();
+        let mut schedule = amortization.into_schedule(monthly_payment);
+
+        ModificationCalculator::apply(input, &mut schedule)?;
+        EscrowCalculator::apply(input, &mut schedule)?;
+        MortgageInsuranceCalculator::apply(input, &mut schedule)?;
+        PrepaymentPenaltyCalculator::apply(input, &mut schedule)?;
+        Ok(schedule)
+    }
+
+    fn schedule_entries(&self, input: &MortgageInput) -> Result<ScheduleEntries, CalculationError> {
+        if !is_plain_amortization(input) {
+            return Ok(Box::new(self.calculate_schedule(input)?.schedule.into_iter()));
         }
+        Ok(Box::new(Self::amortization(input)?))
     }
 
-    fn calculate_summary(input: &MortgageInput) -> MortgageSummary {
-        let schedule = Self::calculate_schedule(input);
-        
-        MortgageSummary::new(
-            RepaymentType::StandardPrincipalAndInterest,
-            schedule.monthly_payment,
-            schedule.total_payments,
-            schedule.total_interest,
+    fn calculate_summary(&self, input: &MortgageInput) -> Result<MortgageSummary, CalculationError> {
+        if !is_plain_amortization(input) {
+            return Ok(self.summarize(input, &self.calculate_schedule(input)?));
+        }
+        let amortization = Self::amortization(input)?;
+        let monthly_payment = amortization.scheduled_payment();
+        let totals = amortization.totals()?;
+
+        Ok(MortgageSummary::new(
+            self.repayment_type(),
+            monthly_payment,
+            totals.total_payments,
+            totals.total_interest,
             input.principal,
             input.annual_interest_rate,
-            input.term_years * 12,
-        )
+            totals.payment_count,
+        ))
+    }
+}
+
+impl StandardCalculator {
+    fn amortization(input: &MortgageInput) -> Result<Amortization, CalculationError> {
+        check_input(input)?;
+        let monthly_rate = input.annual_interest_rate / dec!(12);
+        let num_payments = input.term_years * 12;
+        let monthly_payment = input.principal *
+            payment_factor(monthly_rate, num_payments)?;
+
+        Ok(Amortization::new(input, monthly_rate, num_payments, 30).with_level_payment(monthly_payment))
     }
 }
 
@@ -71,22 +73,41 @@ impl MortgageCalculator for StandardCalculator {
 mod tests {
     use super::*;
     use chrono::NaiveDate;
+    use rust_decimal::Decimal;
 
     #[test]
     fn test_standard_calculator() {
-        let input = MortgageInput {
-            principal: dec!(300000),
-            annual_interest_rate: dec!(0.05),
-            term_years: 30,
-            repayment_type: RepaymentType::StandardPrincipalAndInterest,
-            start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-            balloon_payment_percentage: dec!(0),
-        };
-
-        let schedule = StandardCalculator::calculate_schedule(&input);
+        let input = MortgageInput::new(
+            dec!(300000),
+            dec!(0.05),
+            30,
+            RepaymentType::StandardPrincipalAndInterest,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        );
+
+        let schedule = StandardCalculator.calculate_schedule(&input).unwrap();
         assert_eq!(schedule.schedule.len(), 360);
         
         let final_payment = schedule.schedule.last().unwrap();
         assert!(final_payment.remaining_principal.abs() < dec!(0.01));
     }
+
+    #[test]
+    fn test_extra_payment_shortens_schedule() {
+        let mut input = MortgageInput::new(
+            dec!(300000),
+            dec!(0.05),
+            30,
+            RepaymentType::StandardPrincipalAndInterest,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        );
+        let baseline = StandardCalculator.calculate_schedule(&input).unwrap();
+
+        input.extra_payment = dec!(500);
+        let schedule = StandardCalculator.calculate_schedule(&input).unwrap();
+
+        assert!(schedule.schedule.len() < 360);
+        assert!(schedule.total_interest < baseline.total_interest);
+        assert_eq!(schedule.schedule.last().unwrap().remaining_principal, Decimal::ZERO);
+    }
 }
@@ -0,0 +1,223 @@
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+use thiserror::Error;
+
+use crate::models::{
+    DiscountCurve,
+    MortgageSchedule,
+    ParRatePoint,
+    PresentValue,
+    ValuationRequest,
+    ZeroRatePoint,
+};
+use crate::utils::DecimalUtils;
+use super::{calculate_schedule, CalculationError};
+
+/// Errors raised while discounting a payment stream
+#[derive(Debug, Error, PartialEq)]
+pub enum ValuationError {
+    #[error("the schedule has no payments to discount")]
+    NoPayments,
+
+    #[error("par rates imply a non-positive discount factor at {term_years} years")]
+    InvalidCurve { term_years: Decimal },
+
+    #[error(transparent)]
+    Calculation(#[from] CalculationError),
+}
+
+/// Discounts schedules against a flat rate or a bootstrapped zero curve
+pub struct ValuationCalculator;
+
+impl ValuationCalculator {
+    pub fn value(request: &ValuationRequest) -> Result<PresentValue, ValuationError> {
+        let schedule = calculate_schedule(&request.input)?;
+        Self::value_schedule(&schedule, request.input.repayment_type.payments_per_year(), &request.curve)
+    }
+
+    /// Present value of each payment in `schedule`, paid at whole periods from its start
+    ///
+    /// Principal counts everything repaid beyond interest, including any deferred balance.
+    pub fn value_schedule(
+        schedule: &MortgageSchedule,
+        payments_per_year: u32,
+        curve: &DiscountCurve,
+    ) -> Result<PresentValue, ValuationError> {
+        let first = schedule.schedule.first().ok_or(ValuationError::NoPayments)?;
+        let principal = first.remaining_principal + first.principal_component;
+        let periods_per_year = Decimal::from(payments_per_year);
+
+        let zero_curve = match curve {
+            DiscountCurve::Flat { .. } => Vec::new(),
+            DiscountCurve::ParRates { points, coupon_frequency } => Self::bootstrap(points, *coupon_frequency)?,
+        };
+
+        let mut flat_factor = dec!(1);
+        let mut totals = [Decimal::ZERO; 4];
+        for (period, entry) in (1u32..).zip(&schedule.schedule) {
+            let discount_factor = match curve {
+                DiscountCurve::Flat { rate } => {
+                    flat_factor /= dec!(1) + rate / periods_per_year;
+                    flat_factor
+                }
+                DiscountCurve::ParRates { .. } => {
+                    Self::discount_factor(&zero_curve, Decimal::from(period) / periods_per_year)
+                }
+            };
+            let repaid = entry.payment_amount - entry.interest_component;
+            totals[0] += repaid;
+            totals[1] += entry.interest_component;
+            totals[2] += repaid * discount_factor;
+            totals[3] += entry.interest_component * discount_factor;
+        }
+        let [total_principal, total_interest, pv_principal, pv_interest] = totals;
+        let total_pv = pv_principal + pv_interest;
+
+        Ok(PresentValue {
+            principal,
+            total_principal: DecimalUtils::round_currency(total_principal),
+            total_interest: DecimalUtils::round_currency(total_interest),
+            pv_principal: DecimalUtils::round_currency(pv_principal),
+            pv_interest: DecimalUtils::round_currency(pv_interest),
+            total_pv: DecimalUtils::round_currency(total_pv),
+            npv: DecimalUtils::round_currency(total_pv - principal),
+            price: (total_pv / principal * dec!(100)).round_dp(6),
+            zero_curve,
+        })
+    }
+
+    /// Discount factors at each coupon date out to the longest par maturity
+    ///
+    /// Par rates are interpolated linearly between maturities and held flat
+    /// beyond them; each par instrument prices to exactly one.
+    pub fn bootstrap(points: &[ParRatePoint], coupon_frequency: u32) -> Result<Vec<ZeroRatePoint>, ValuationError> {
+        let frequency = Decimal::from(coupon_frequency);
+        let longest = points.last().map_or(Decimal::ZERO, |point| point.term_years);
+        let coupons: u32 = (longest * frequency).ceil().try_into().unwrap_or(0);
+
+        let mut annuity = Decimal::ZERO;
+        let mut zero_curve = Vec::with_capacity(coupons as usize);
+        for coupon in 1..=coupons {
+            let term_years = Decimal::from(coupon) / frequency;
+            let coupon_rate = Self::par_rate(points, term_years) / frequency;
+            let discount_factor = (dec!(1) - coupon_rate * annuity) / (dec!(1) + coupon_rate);
+            if discount_factor <= Decimal::ZERO {
+                return Err(ValuationError::InvalidCurve { term_years });
+            }
+
+            annuity += discount_factor;
+            zero_curve.push(ZeroRatePoint {
+                term_years,
+                discount_factor,
+                zero_rate: -discount_factor.ln() / term_years,
+            });
+        }
+
+        Ok(zero_curve)
+    }
+
+    fn par_rate(points: &[ParRatePoint], term_years: Decimal) -> Decimal {
+        Self::interpolate(points.iter().map(|point| (point.term_years, point.rate)), term_years)
+    }
+
+    /// Discount factor from zero rates interpolated linearly in time
+    fn discount_factor(zero_curve: &[ZeroRatePoint], term_years: Decimal) -> Decimal {
+        let zero_rate = Self::interpolate(zero_curve.iter().map(|point| (point.term_years, point.zero_rate)), term_years);
+        (-zero_rate * term_years).exp()
+    }
+
+    /// Linear interpolation through ordered `(x, y)` points, flat beyond either end
+    fn interpolate(points: impl Iterator<Item = (Decimal, Decimal)>, x: Decimal) -> Decimal {
+        let mut previous: Option<(Decimal, Decimal)> = None;
+        for (point_x, point_y) in points {
+            if x <= point_x {
+                return match previous {
+                    Some((previous_x, previous_y)) => {
+                        previous_y + (point_y - previous_y) * (x - previous_x) / (point_x - previous_x)
+                    }
+                    None => point_y,
+                };
+            }
+            previous = Some((point_x, point_y));
+        }
+        previous.map_or(Decimal::ZERO, |(_, y)| y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::models::{MortgageInput, RepaymentType};
+
+    fn valuation(curve: DiscountCurve) -> PresentValue {
+        ValuationCalculator::value(&ValuationRequest {
+            input: MortgageInput::new(
+                dec!(300000),
+                dec!(0.06),
+                30,
+                RepaymentType::StandardPrincipalAndInterest,
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            ),
+            curve,
+        }).unwrap()
+    }
+
+    fn par_rates(rates: &[(u32, Decimal)], coupon_frequency: u32) -> DiscountCurve {
+        DiscountCurve::ParRates {
+            points: rates.iter()
+                .map(|(years, rate)| ParRatePoint { term_years: Decimal::from(*years), rate: *rate })
+                .collect(),
+            coupon_frequency,
+        }
+    }
+
+    #[test]
+    fn test_note_rate_discounts_to_par() {
+        let value = valuation(DiscountCurve::Flat { rate: dec!(0.06) });
+
+        assert_eq!(value.total_pv, dec!(300000));
+        assert_eq!(value.npv, Decimal::ZERO);
+        assert_eq!(value.pv_principal + value.pv_interest, value.total_pv);
+        assert!(value.pv_interest < value.total_interest);
+        assert!(value.zero_curve.is_empty());
+    }
+
+    #[test]
+    fn test_flat_par_curve_matches_flat_rate() {
+        // Monthly par instruments at the note rate reproduce monthly compounding exactly
+        let value = valuation(par_rates(&[(1, dec!(0.06)), (30, dec!(0.06))], 12));
+
+        assert_eq!(value.zero_curve.len(), 360);
+        assert!((value.total_pv - dec!(300000)).abs() < dec!(0.01));
+    }
+
+    #[test]
+    fn test_bootstrapped_curve_reprices_par_instruments() {
+        let points = [(1, dec!(0.04)), (2, dec!(0.045)), (5, dec!(0.05)), (10, dec!(0.055))];
+        let zero_curve = ValuationCalculator::bootstrap(
+            &points.iter().map(|(years, rate)| ParRatePoint { term_years: Decimal::from(*years), rate: *rate }).collect::<Vec<_>>(),
+            2,
+        ).unwrap();
+
+        // A 5-year 5% semiannual bond prices at par off the bootstrapped factors
+        let five_year = &zero_curve[..10];
+        let price: Decimal = five_year.iter().map(|point| point.discount_factor * dec!(0.025)).sum::<Decimal>()
+            + five_year[9].discount_factor;
+        assert!((price - dec!(1)).abs() < dec!(0.0000001));
+
+        // An upward-sloping par curve has zero rates above par rates at the long end
+        assert!(zero_curve[19].zero_rate > dec!(0.055));
+        assert!(zero_curve.windows(2).all(|pair| pair[1].discount_factor < pair[0].discount_factor));
+    }
+
+    #[test]
+    fn test_higher_curve_lowers_value() {
+        let low = valuation(par_rates(&[(1, dec!(0.04)), (30, dec!(0.05))], 2));
+        let high = valuation(par_rates(&[(1, dec!(0.06)), (30, dec!(0.07))], 2));
+
+        assert!(low.npv > Decimal::ZERO);
+        assert!(high.npv < Decimal::ZERO);
+        assert!(low.price > dec!(100) && high.price < dec!(100));
+    }
+}
@@ -0,0 +1,519 @@
+//! Server configuration
+//!
+//! Settings start from built-in defaults and are overridden, in order, by a
+//! TOML file, `MORTGAGEKIT_*` environment variables and command-line flags.
+//! Every source names settings by the same dotted keys, e.g. `server.port`
+//! in the file, `MORTGAGEKIT_SERVER_PORT` in the environment and `--port`
+//! on the command line.
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use thiserror::Error;
+use toml_edit::{value, Array, DocumentMut, Item, Table, Value};
+
+/// Prefix of environment variables naming settings
+pub const ENV_PREFIX: &str = "MORTGAGEKIT_";
+
+/// Environment variable naming a configuration file
+pub const CONFIG_FILE_ENV: &str = "MORTGAGEKIT_CONFIG";
+
+/// Command-line usage
+pub const USAGE: &str = "\
+Usage: mortgagekit-rs [OPTIONS]
+
+Options:
+  --config <PATH>                  TOML configuration file (or MORTGAGEKIT_CONFIG)
+  --host <HOST>                    Address to bind [server.host]
+  --port <PORT>                    Port to bind [server.port]
+  --workers <N>                    Worker threads; defaults to one per core [server.workers]
+  --cors-allowed-origins <LIST>    Comma-separated origins, or * for any [cors.allowed_origins]
+  --json-limit-bytes <BYTES>       Largest JSON request body [limits.json_bytes]
+  --batch-limit-bytes <BYTES>      Largest batch request body [limits.batch_bytes]
+  --log-level <LEVEL>              error, warn, info, debug, trace or off [log.level]
+  --log-format <FORMAT>            text or json [log.format]
+  --docs <BOOL>                    Serve the API documentation [features.docs]
+  --swagger-ui-url <URL>           Where the docs page loads Swagger UI from [docs.swagger_ui_url]
+  --batch <BOOL>                   Serve the batch endpoint [features.batch]
+  --print-config                   Print the effective configuration as TOML and exit
+  --help                           Print this help and exit
+";
+
+/// Command-line flags and the settings they override
+const FLAGS: [(&str, &str); 11] = [
+    ("--host", "server.host"),
+    ("--port", "server.port"),
+    ("--workers", "server.workers"),
+    ("--cors-allowed-origins", "cors.allowed_origins"),
+    ("--json-limit-bytes", "limits.json_bytes"),
+    ("--batch-limit-bytes", "limits.batch_bytes"),
+    ("--log-level", "log.level"),
+    ("--log-format", "log.format"),
+    ("--docs", "features.docs"),
+    ("--batch", "features.batch"),
+    ("--swagger-ui-url", "docs.swagger_ui_url"),
+];
+
+const LOG_LEVELS: [&str; 6] = ["error", "warn", "info", "debug", "trace", "off"];
+
+/// Errors raised while loading or validating configuration
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("cannot read config file {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("cannot parse config file {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+
+    #[error("unknown setting `{0}`")]
+    UnknownSetting(String),
+
+    #[error("unknown flag `{0}`")]
+    UnknownFlag(String),
+
+    #[error("flag `{0}` requires a value")]
+    MissingValue(String),
+
+    #[error("invalid value {value:?} for `{key}`: {reason}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        reason: String,
+    },
+}
+
+/// Command-line arguments
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CliArgs {
+    pub config_path: Option<PathBuf>,
+    pub print_config: bool,
+    pub help: bool,
+
+    /// Settings given as flags, as dotted keys and raw values
+    pub overrides: Vec<(String, String)>,
+}
+
+impl CliArgs {
+    /// Parses flags, accepting both `--flag value` and `--flag=value`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, ConfigError> {
+        let mut cli = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+
+            match flag.as_str() {
+                "--print-config" => cli.print_config = true,
+                "--help" | "-h" => cli.help = true,
+                _ => {
+                    let key = if flag == "--config" {
+                        None
+                    } else {
+                        let (_, key) = FLAGS.iter()
+                            .find(|(name, _)| *name == flag)
+                            .ok_or_else(|| ConfigError::UnknownFlag(flag.clone()))?;
+                        Some(key.to_string())
+                    };
+                    let value = inline_value
+                        .or_else(|| args.next())
+                        .ok_or_else(|| ConfigError::MissingValue(flag.clone()))?;
+
+                    match key {
+                        Some(key) => cli.overrides.push((key, value)),
+                        None => cli.config_path = Some(PathBuf::from(value)),
+                    }
+                }
+            }
+        }
+
+        Ok(cli)
+    }
+}
+
+/// How log lines are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Complete server configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    pub server: ListenConfig,
+    pub cors: CorsConfig,
+    pub limits: LimitsConfig,
+    pub log: LogConfig,
+    pub features: FeatureToggles,
+    pub docs: DocsConfig,
+}
+
+/// Where the server listens and how many workers serve it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListenConfig {
+    pub host: String,
+    pub port: u16,
+
+    /// Worker threads; one per core when unset
+    pub workers: Option<usize>,
+}
+
+/// Origins allowed to call the API from a browser
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorsConfig {
+    /// Exact origins, or `*` to allow any origin
+    pub allowed_origins: Vec<String>,
+}
+
+impl CorsConfig {
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|origin| origin == "*")
+    }
+}
+
+/// Largest request bodies accepted, in bytes
+#[derive(Debug, Clone, PartialEq)]
+pub struct LimitsConfig {
+    pub json_bytes: usize,
+    /// Batch bodies are buffered in full before calculating, so this bounds their memory
+    pub batch_bytes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogConfig {
+    /// Default level; `RUST_LOG` still takes precedence for per-module filters
+    pub level: String,
+    pub format: LogFormat,
+}
+
+/// Optional endpoints that can be switched off
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureToggles {
+    /// Swagger UI and OpenAPI spec under `/api/docs`
+    pub docs: bool,
+
+    /// Batch calculation under `/api/v1/batch`
+    pub batch: bool,
+}
+
+/// How the documentation page is served
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocsConfig {
+    /// Base URL of the `swagger-ui-dist` assets, e.g. a local mirror for offline use
+    pub swagger_ui_url: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            server: ListenConfig {
+                host: "0.0.0.0".to_string(),
+                port: 8080,
+                workers: None,
+            },
+            cors: CorsConfig {
+                allowed_origins: vec!["*".to_string()],
+            },
+            limits: LimitsConfig {
+                json_bytes: 1024 * 1024,
+                batch_bytes: 64 * 1024 * 1024,
+            },
+            log: LogConfig {
+                level: "info".to_string(),
+                format: LogFormat::Text,
+            },
+            features: FeatureToggles {
+                docs: true,
+                batch: true,
+            },
+            docs: DocsConfig {
+                swagger_ui_url: "https://unpkg.com/swagger-ui-dist@5".to_string(),
+            },
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Builds the configuration from defaults, the config file, the environment and `cli`
+    ///
+    /// `env` looks up an environment variable by name.
+    pub fn load(cli: &CliArgs, env: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+
+        if let Some(path) = cli.config_path.clone().or_else(|| env(CONFIG_FILE_ENV).map(PathBuf::from)) {
+            let text = fs::read_to_string(&path)
+                .map_err(|source| ConfigError::Io { path: path.clone(), source })?;
+            config.apply_toml(&text)
+                .map_err(|error| match error {
+                    ConfigError::Parse { message, .. } => ConfigError::Parse { path, message },
+                    other => other,
+                })?;
+        }
+
+        for key in Self::keys() {
+            let name = format!("{}{}", ENV_PREFIX, key.replace('.', "_").to_uppercase());
+            if let Some(raw) = env(&name) {
+                config.set(key, &raw)?;
+            }
+        }
+
+        for (key, raw) in &cli.overrides {
+            config.set(key, raw)?;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Every setting's dotted key
+    pub fn keys() -> impl Iterator<Item = &'static str> {
+        FLAGS.iter().map(|(_, key)| *key)
+    }
+
+    /// Applies settings from a TOML document of `[section]` tables
+    pub fn apply_toml(&mut self, text: &str) -> Result<(), ConfigError> {
+        let document: DocumentMut = text.parse().map_err(|error: toml_edit::TomlError| ConfigError::Parse {
+            path: PathBuf::new(),
+            message: error.to_string(),
+        })?;
+
+        for (section, item) in document.iter() {
+            let table = item.as_table().ok_or_else(|| ConfigError::UnknownSetting(section.to_string()))?;
+            for (name, item) in table.iter() {
+                let key = format!("{}.{}", section, name);
+                let raw = match item.as_value() {
+                    Some(Value::String(text)) => text.value().clone(),
+                    Some(Value::Integer(number)) => number.value().to_string(),
+                    Some(Value::Boolean(flag)) => flag.value().to_string(),
+                    Some(Value::Array(items)) => items.iter()
+                        .map(|item| item.as_str().unwrap_or_default())
+                        .collect::<Vec<_>>()
+                        .join(","),
+                    _ => return Err(Self::invalid(&key, &item.to_string(), "unsupported value type")),
+                };
+                self.set(&key, &raw)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets one setting from its raw text
+    pub fn set(&mut self, key: &str, raw: &str) -> Result<(), ConfigError> {
+        let raw = raw.trim();
+        match key {
+            "server.host" => self.server.host = raw.to_string(),
+            "server.port" => self.server.port = Self::parse(key, raw)?,
+            "server.workers" => self.server.workers = Some(Self::parse(key, raw)?),
+            "cors.allowed_origins" => {
+                self.cors.allowed_origins = raw.split(',')
+                    .map(str::trim)
+                    .filter(|origin| !origin.is_empty())
+                    .map(str::to_string)
+                    .collect();
+            }
+            "limits.json_bytes" => self.limits.json_bytes = Self::parse(key, raw)?,
+            "limits.batch_bytes" => self.limits.batch_bytes = Self::parse(key, raw)?,
+            "log.level" => self.log.level = raw.to_lowercase(),
+            "log.format" => {
+                self.log.format = match raw.to_lowercase().as_str() {
+                    "text" => LogFormat::Text,
+                    "json" => LogFormat::Json,
+                    _ => return Err(Self::invalid(key, raw, "expected text or json")),
+                };
+            }
+            "features.docs" => self.features.docs = Self::parse(key, raw)?,
+            "features.batch" => self.features.batch = Self::parse(key, raw)?,
+            "docs.swagger_ui_url" => self.docs.swagger_ui_url = raw.trim_end_matches('/').to_string(),
+            _ => return Err(ConfigError::UnknownSetting(key.to_string())),
+        }
+        Ok(())
+    }
+
+    /// Checks settings that are well-formed on their own but unusable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.server.host.is_empty() {
+            return Err(Self::invalid("server.host", "", "must not be empty"));
+        }
+        if self.server.port == 0 {
+            return Err(Self::invalid("server.port", "0", "must be between 1 and 65535"));
+        }
+        if let Some(workers) = self.server.workers.filter(|workers| !(1..=1024).contains(workers)) {
+            return Err(Self::invalid("server.workers", &workers.to_string(), "must be between 1 and 1024"));
+        }
+        if let Some(origin) = self.cors.allowed_origins.iter()
+            .find(|origin| *origin != "*" && !origin.starts_with("http://") && !origin.starts_with("https://"))
+        {
+            return Err(Self::invalid("cors.allowed_origins", origin, "origins must start with http:// or https://"));
+        }
+        if !(1024..=1024 * 1024 * 1024).contains(&self.limits.json_bytes) {
+            return Err(Self::invalid(
+                "limits.json_bytes",
+                &self.limits.json_bytes.to_string(),
+                "must be between 1 KiB and 1 GiB",
+            ));
+        }
+        if !(self.limits.json_bytes..=1024 * 1024 * 1024).contains(&self.limits.batch_bytes) {
+            return Err(Self::invalid(
+                "limits.batch_bytes",
+                &self.limits.batch_bytes.to_string(),
+                "must be at least limits.json_bytes and at most 1 GiB",
+            ));
+        }
+        let swagger_ui_url = &self.docs.swagger_ui_url;
+        if !swagger_ui_url.starts_with("http://") && !swagger_ui_url.starts_with("https://") && !swagger_ui_url.starts_with('/') {
+            return Err(Self::invalid("docs.swagger_ui_url", swagger_ui_url, "must be an http(s) URL or an absolute path"));
+        }
+        if !LOG_LEVELS.contains(&self.log.level.as_str()) {
+            return Err(Self::invalid("log.level", &self.log.level, "expected error, warn, info, debug, trace or off"));
+        }
+        Ok(())
+    }
+
+    /// The configuration as a TOML document that `apply_toml` reads back
+    pub fn to_toml(&self) -> String {
+        let mut document = DocumentMut::new();
+
+        let mut server = Table::new();
+        server["host"] = value(self.server.host.as_str());
+        server["port"] = value(i64::from(self.server.port));
+        if let Some(workers) = self.server.workers {
+            server["workers"] = value(workers as i64);
+        }
+        document["server"] = Item::Table(server);
+
+        let mut cors = Table::new();
+        cors["allowed_origins"] = value(self.cors.allowed_origins.iter().map(String::as_str).collect::<Array>());
+        document["cors"] = Item::Table(cors);
+
+        let mut limits = Table::new();
+        limits["json_bytes"] = value(self.limits.json_bytes as i64);
+        limits["batch_bytes"] = value(self.limits.batch_bytes as i64);
+        document["limits"] = Item::Table(limits);
+
+        let mut log = Table::new();
+        log["level"] = value(self.log.level.as_str());
+        log["format"] = value(self.log.format.to_string());
+        document["log"] = Item::Table(log);
+
+        let mut features = Table::new();
+        features["docs"] = value(self.features.docs);
+        features["batch"] = value(self.features.batch);
+        document["features"] = Item::Table(features);
+
+        let mut docs = Table::new();
+        docs["swagger_ui_url"] = value(self.docs.swagger_ui_url.as_str());
+        document["docs"] = Item::Table(docs);
+
+        document.to_string()
+    }
+
+    fn parse<T: std::str::FromStr>(key: &str, raw: &str) -> Result<T, ConfigError>
+    where
+        T::Err: fmt::Display,
+    {
+        raw.parse().map_err(|error: T::Err| Self::invalid(key, raw, &error.to_string()))
+    }
+
+    fn invalid(key: &str, value: &str, reason: &str) -> ConfigError {
+        ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn args(flags: &[&str]) -> CliArgs {
+        CliArgs::parse(flags.iter().map(|flag| flag.to_string())).unwrap()
+    }
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        let config = ServerConfig::load(&CliArgs::default(), no_env).unwrap();
+
+        assert_eq!(config, ServerConfig::default());
+        assert!(config.cors.allows_any_origin());
+    }
+
+    #[test]
+    fn test_sources_override_in_order() {
+        let mut config = ServerConfig::default();
+        config.apply_toml("[server]\nport = 9000\nworkers = 2\n\n[log]\nformat = \"json\"\n").unwrap();
+        assert_eq!(config.server.port, 9000);
+
+        let env: HashMap<&str, &str> = HashMap::from([
+            ("MORTGAGEKIT_SERVER_WORKERS", "6"),
+            ("MORTGAGEKIT_CORS_ALLOWED_ORIGINS", "https://a.example, https://b.example"),
+        ]);
+        let cli = args(&["--port", "9100", "--docs=false"]);
+        let loaded = ServerConfig::load(&cli, |name| env.get(name).map(|raw| raw.to_string())).unwrap();
+
+        assert_eq!(loaded.server.port, 9100);
+        assert_eq!(loaded.server.workers, Some(6));
+        assert_eq!(loaded.cors.allowed_origins, vec!["https://a.example", "https://b.example"]);
+        assert!(!loaded.features.docs);
+    }
+
+    #[test]
+    fn test_invalid_settings_are_rejected() {
+        assert!(matches!(
+            ServerConfig::load(&args(&["--port", "eighty"]), no_env),
+            Err(ConfigError::InvalidValue { key, .. }) if key == "server.port"
+        ));
+        assert!(matches!(
+            ServerConfig::load(&args(&["--cors-allowed-origins", "example.com"]), no_env),
+            Err(ConfigError::InvalidValue { key, .. }) if key == "cors.allowed_origins"
+        ));
+        assert!(matches!(
+            ServerConfig::default().apply_toml("[server]\nprot = 80\n"),
+            Err(ConfigError::UnknownSetting(key)) if key == "server.prot"
+        ));
+        assert!(matches!(
+            ServerConfig::load(&args(&["--swagger-ui-url", "unpkg.com/swagger-ui-dist"]), no_env),
+            Err(ConfigError::InvalidValue { key, .. }) if key == "docs.swagger_ui_url"
+        ));
+        assert!(matches!(CliArgs::parse(["--verbose".to_string()]), Err(ConfigError::UnknownFlag(_))));
+        assert!(matches!(CliArgs::parse(["--port".to_string()]), Err(ConfigError::MissingValue(_))));
+    }
+
+    #[test]
+    fn test_printed_config_reads_back() {
+        let cli = args(&[
+            "--workers", "3", "--log-level", "debug", "--batch", "false", "--swagger-ui-url", "/assets/swagger-ui/",
+            "--print-config",
+        ]);
+        let config = ServerConfig::load(&cli, no_env).unwrap();
+        let mut reread = ServerConfig::default();
+        reread.apply_toml(&config.to_toml()).unwrap();
+
+        assert!(cli.print_config);
+        assert_eq!(config.docs.swagger_ui_url, "/assets/swagger-ui");
+        assert_eq!(reread, config);
+    }
+}
@@ -0,0 +1,44 @@
+use super::Table;
+
+/// Writes a table as RFC 4180 CSV with a header row
+pub(super) fn write(table: &Table) -> String {
+    let mut csv = String::new();
+    write_record(&mut csv, table.columns.iter().map(String::as_str));
+    for row in &table.rows {
+        let values: Vec<String> = row.iter().map(|cell| cell.display()).collect();
+        write_record(&mut csv, values.iter().map(String::as_str));
+    }
+    csv
+}
+
+fn write_record<'a>(csv: &mut String, values: impl Iterator<Item = &'a str>) {
+    for (index, value) in values.enumerate() {
+        if index > 0 {
+            csv.push(',');
+        }
+        if value.contains([',', '"', '\r', '\n']) {
+            csv.push('"');
+            csv.push_str(&value.replace('"', "\"\""));
+            csv.push('"');
+        } else {
+            csv.push_str(value);
+        }
+    }
+    csv.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::Cell;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_quotes_fields_that_need_it() {
+        let mut table = Table::new("Summary", &["Item", "Value"]);
+        table.rows.push(vec![Cell::Text("Rate, \"teaser\"".to_string()), Cell::Money(dec!(1234.5))]);
+        table.rows.push(vec![Cell::Text("Balloon".to_string()), Cell::Empty]);
+
+        assert_eq!(write(&table), "Item,Value\r\n\"Rate, \"\"teaser\"\"\",1234.50\r\nBalloon,\r\n");
+    }
+}
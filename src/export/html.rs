@@ -0,0 +1,63 @@
+use super::{escape_markup, Cell, Table};
+
+const STYLE: &str = "\
+body { font-family: Helvetica, Arial, sans-serif; font-size: 11pt; color: #222; margin: 2em; }
+h1 { font-size: 16pt; margin-bottom: 0.5em; }
+h2 { font-size: 13pt; margin-top: 1.5em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border-bottom: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; }
+td.number { text-align: right; font-variant-numeric: tabular-nums; }
+thead th { border-bottom: 2px solid #222; }
+@page { size: letter; margin: 1.5cm; }
+@media print {
+  body { margin: 0; font-size: 9pt; }
+  thead { display: table-header-group; }
+  tr { page-break-inside: avoid; }
+  h2 { page-break-after: avoid; }
+}
+";
+
+/// Writes a standalone page with a heading and table per table
+pub(super) fn write(title: &str, tables: &[Table]) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n", escape_markup(title), STYLE));
+    html.push_str(&format!("<h1>{}</h1>\n", escape_markup(title)));
+    for table in tables {
+        html.push_str(&format!("<h2>{}</h2>\n<table>\n<thead>\n<tr>", escape_markup(&table.name)));
+        for column in &table.columns {
+            html.push_str(&format!("<th>{}</th>", escape_markup(column)));
+        }
+        html.push_str("</tr>\n</thead>\n<tbody>\n");
+        for row in &table.rows {
+            html.push_str("<tr>");
+            for cell in row {
+                let class = match cell {
+                    Cell::Number(_) | Cell::Money(_) => " class=\"number\"",
+                    _ => "",
+                };
+                html.push_str(&format!("<td{}>{}</td>", class, escape_markup(&cell.display())));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escapes_text_and_repeats_headers_in_print() {
+        let mut table = Table::new("Summary", &["Item", "Value"]);
+        table.rows.push(vec![Cell::Text("<Custom & Co>".to_string()), Cell::Empty]);
+        let html = write("Loan", &[table]);
+
+        assert!(html.contains("<td>&lt;Custom &amp; Co&gt;</td>"));
+        assert!(html.contains("thead { display: table-header-group; }"));
+        assert!(html.contains("<h1>Loan</h1>"));
+    }
+}
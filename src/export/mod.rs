@@ -0,0 +1,314 @@
+//! Downloadable renderings of schedules and summaries
+//!
+//! A [`Report`] holds a loan summary and its payments or yearly totals as
+//! plain tables, which render to CSV, an XLSX workbook or a print-friendly
+//! HTML page.
+
+mod csv;
+mod html;
+mod xlsx;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use crate::models::{MortgageSchedule, MortgageSummary, PaymentScheduleEntry, ScheduleYear};
+use crate::utils::DecimalUtils;
+
+/// Formats a schedule or summary can be exported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Xlsx,
+    Html,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [ExportFormat::Json, ExportFormat::Csv, ExportFormat::Xlsx, ExportFormat::Html];
+
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            ExportFormat::Html => "text/html",
+        }
+    }
+
+    /// `Content-Type` of a response in this format
+    pub fn content_type(&self) -> String {
+        match self {
+            ExportFormat::Csv | ExportFormat::Html => format!("{}; charset=utf-8", self.media_type()),
+            _ => self.media_type().to_string(),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    /// Picks the format an `Accept` header prefers; JSON when the header is absent or empty
+    ///
+    /// Returns `None` when the header accepts none of the formats.
+    pub fn negotiate(accept: Option<&str>) -> Option<ExportFormat> {
+        let accept = match accept.map(str::trim).filter(|accept| !accept.is_empty()) {
+            Some(accept) => accept,
+            None => return Some(ExportFormat::Json),
+        };
+
+        let mut best: Option<(Decimal, ExportFormat)> = None;
+        for range in accept.split(',') {
+            let mut parts = range.split(';').map(str::trim);
+            let media_range = parts.next().unwrap_or_default().to_ascii_lowercase();
+            let quality = parts
+                .filter_map(|param| param.strip_prefix("q="))
+                .find_map(|q| q.parse::<Decimal>().ok())
+                .unwrap_or(Decimal::ONE);
+            if quality <= Decimal::ZERO {
+                continue;
+            }
+            // Listed order breaks ties, so `*/*` keeps JSON the default
+            let matched = Self::ALL.into_iter().find(|format| format.matches(&media_range));
+            if let Some(format) = matched {
+                if best.filter(|(best_quality, _)| *best_quality >= quality).is_none() {
+                    best = Some((quality, format));
+                }
+            }
+        }
+        best.map(|(_, format)| format)
+    }
+
+    fn matches(&self, media_range: &str) -> bool {
+        let media_type = self.media_type();
+        let top_level = media_type.split('/').next().unwrap_or_default();
+        media_range == "*/*"
+            || media_range == media_type
+            || media_range.strip_suffix("/*") == Some(top_level)
+    }
+}
+
+/// One exported value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(Decimal),
+    /// Currency amount, rounded to cents when rendered
+    Money(Decimal),
+    Date(NaiveDate),
+    Empty,
+}
+
+impl Cell {
+    /// Text the cell renders as in CSV and HTML
+    pub fn display(&self) -> String {
+        match self {
+            Cell::Text(text) => text.clone(),
+            Cell::Number(number) => number.normalize().to_string(),
+            Cell::Money(amount) => format!("{:.2}", DecimalUtils::round_currency(*amount)),
+            Cell::Date(date) => date.format("%Y-%m-%d").to_string(),
+            Cell::Empty => String::new(),
+        }
+    }
+}
+
+impl From<Option<Decimal>> for Cell {
+    fn from(amount: Option<Decimal>) -> Self {
+        amount.map_or(Cell::Empty, Cell::Money)
+    }
+}
+
+/// Named table of rows, rendered as a CSV file, worksheet or HTML table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    pub fn new(name: &str, columns: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Payment-by-payment rows of a schedule
+    pub fn payments(entries: &[PaymentScheduleEntry]) -> Self {
+        let mut table = Self::new("Schedule", &[
+            "Payment", "Date", "Payment Amount", "Principal", "Interest", "Remaining Principal",
+            "Rate", "Escrow", "Mortgage Insurance", "Prepayment Penalty",
+        ]);
+        for entry in entries {
+            table.rows.push(vec![
+                Cell::Number(Decimal::from(entry.payment_number)),
+                Cell::Date(entry.payment_date),
+                Cell::Money(entry.payment_amount),
+                Cell::Money(entry.principal_component),
+                Cell::Money(entry.interest_component),
+                Cell::Money(entry.remaining_principal),
+                entry.current_rate.map_or(Cell::Empty, Cell::Number),
+                entry.escrow_deposit.into(),
+                entry.mortgage_insurance.into(),
+                entry.prepayment_penalty.into(),
+            ]);
+        }
+        table
+    }
+
+    /// Yearly totals of a schedule
+    pub fn years(years: &[ScheduleYear]) -> Self {
+        let mut table = Self::new("Years", &[
+            "Year", "First Payment", "Last Payment", "Payments", "Total Payment", "Principal", "Interest",
+            "Ending Balance",
+        ]);
+        for year in years {
+            table.rows.push(vec![
+                Cell::Number(Decimal::from(year.year)),
+                Cell::Date(year.first_payment_date),
+                Cell::Date(year.last_payment_date),
+                Cell::Number(Decimal::from(year.number_of_payments)),
+                Cell::Money(year.total_payment),
+                Cell::Money(year.total_principal),
+                Cell::Money(year.total_interest),
+                Cell::Money(year.ending_balance),
+            ]);
+        }
+        table
+    }
+
+    /// Label and value rows of a summary
+    pub fn summary(summary: &MortgageSummary) -> Self {
+        let mut table = Self::new("Summary", &["Item", "Value"]);
+        let mut row = |label: &str, value: Cell| {
+            if value != Cell::Empty {
+                table.rows.push(vec![Cell::Text(label.to_string()), value]);
+            }
+        };
+        row("Repayment Type", Cell::Text(summary.repayment_type.to_string()));
+        row("Monthly Payment", Cell::Money(summary.monthly_payment));
+        row("Total Monthly Payment", Cell::Money(summary.total_monthly_payment));
+        row("Number of Payments", Cell::Number(Decimal::from(summary.number_of_payments)));
+        row("Total Payments", Cell::Money(summary.total_payments));
+        row("Total Interest", Cell::Money(summary.total_interest));
+        row("Total Principal", Cell::Money(summary.total_principal_paid));
+        row("APR", Cell::Number(summary.apr));
+        row("Balloon Payment", summary.balloon_payment.into());
+        row("Monthly Escrow Payment", summary.monthly_escrow_payment.into());
+        row("Initial Escrow Deposit", summary.initial_escrow_deposit.into());
+        row("Monthly Mortgage Insurance", summary.monthly_mortgage_insurance.into());
+        row("Upfront Mortgage Insurance", summary.upfront_mortgage_insurance.into());
+        row("Total Mortgage Insurance", summary.total_mortgage_insurance.into());
+        row("Mortgage Insurance Ends", summary.mortgage_insurance_end_date.map_or(Cell::Empty, Cell::Date));
+        row("Total Prepayment Penalty", summary.total_prepayment_penalty.into());
+        row("Deferred Balance", summary.deferred_balance.into());
+        table
+    }
+}
+
+/// A loan summary with the rows detailing it, ready to export
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub title: String,
+    /// Summary first, then payments or yearly totals
+    pub tables: Vec<Table>,
+}
+
+impl Report {
+    /// Summary alone
+    pub fn summary(summary: &MortgageSummary) -> Self {
+        Self {
+            title: format!("{} Loan Summary", summary.repayment_type),
+            tables: vec![Table::summary(summary)],
+        }
+    }
+
+    /// Summary followed by every payment in the schedule
+    pub fn schedule(summary: &MortgageSummary, schedule: &MortgageSchedule) -> Self {
+        Self {
+            title: format!("{} Amortization Schedule", summary.repayment_type),
+            tables: vec![Table::summary(summary), Table::payments(&schedule.schedule)],
+        }
+    }
+
+    /// Summary followed by yearly totals
+    pub fn years(summary: &MortgageSummary, years: &[ScheduleYear]) -> Self {
+        Self {
+            title: format!("{} Annual Amortization", summary.repayment_type),
+            tables: vec![Table::summary(summary), Table::years(years)],
+        }
+    }
+
+    /// CSV of the most detailed table
+    pub fn to_csv(&self) -> String {
+        self.tables.last().map(csv::write).unwrap_or_default()
+    }
+
+    /// Workbook with a worksheet per table
+    pub fn to_xlsx(&self) -> Vec<u8> {
+        xlsx::write(&self.tables)
+    }
+
+    /// Standalone HTML page laid out for printing or saving as PDF
+    pub fn to_html(&self) -> String {
+        html::write(&self.title, &self.tables)
+    }
+
+    /// Renders in a download format; `None` for JSON, which is serialized from the models
+    pub fn render(&self, format: ExportFormat) -> Option<Vec<u8>> {
+        match format {
+            ExportFormat::Json => None,
+            ExportFormat::Csv => Some(self.to_csv().into_bytes()),
+            ExportFormat::Xlsx => Some(self.to_xlsx()),
+            ExportFormat::Html => Some(self.to_html().into_bytes()),
+        }
+    }
+}
+
+/// Escapes text for HTML and XML content and attributes
+fn escape_markup(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(ExportFormat::negotiate(None), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::negotiate(Some("*/*")), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::negotiate(Some("text/csv")), Some(ExportFormat::Csv));
+        assert_eq!(
+            ExportFormat::negotiate(Some("text/html;q=0.5, application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")),
+            Some(ExportFormat::Xlsx)
+        );
+        assert_eq!(ExportFormat::negotiate(Some("text/*, application/json;q=0.1")), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::negotiate(Some("text/csv;q=0, application/*")), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::negotiate(Some("image/png")), None);
+    }
+
+    #[test]
+    fn test_money_cells_round_to_cents() {
+        assert_eq!(Cell::Money(Decimal::new(1234567, 4)).display(), "123.46");
+        assert_eq!(Cell::Money(Decimal::from(5)).display(), "5.00");
+        assert_eq!(Cell::Number(Decimal::new(500, 4)).display(), "0.05");
+    }
+}
@@ -0,0 +1,258 @@
+//! Minimal SpreadsheetML workbook, stored uncompressed in a zip package
+
+use chrono::NaiveDate;
+
+use super::{escape_markup, Cell, Table};
+use crate::utils::DecimalUtils;
+
+const MAIN_NAMESPACE: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const RELATIONSHIP_NAMESPACE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const PACKAGE_RELATIONSHIP_NAMESPACE: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+
+/// Cell formats, indexing `cellXfs` in the stylesheet
+const HEADER_STYLE: u32 = 1;
+const DATE_STYLE: u32 = 2;
+const MONEY_STYLE: u32 = 3;
+
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><fonts count="2"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font></fonts><fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills><borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders><cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs><cellXfs count="4"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/><xf numFmtId="14" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/><xf numFmtId="4" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/></cellXfs></styleSheet>"#;
+
+/// Writes a workbook with a worksheet per table
+pub(super) fn write(tables: &[Table]) -> Vec<u8> {
+    let mut package = Package::default();
+    package.add("[Content_Types].xml", &content_types(tables.len()));
+    package.add(
+        "_rels/.rels",
+        &relationships(&[("officeDocument", "xl/workbook.xml".to_string())]),
+    );
+    package.add("xl/workbook.xml", &workbook(tables));
+
+    let mut targets: Vec<(&str, String)> = (1..=tables.len())
+        .map(|sheet| ("worksheet", format!("worksheets/sheet{}.xml", sheet)))
+        .collect();
+    targets.push(("styles", "styles.xml".to_string()));
+    package.add("xl/_rels/workbook.xml.rels", &relationships(&targets));
+    package.add("xl/styles.xml", STYLES);
+
+    for (index, table) in tables.iter().enumerate() {
+        package.add(&format!("xl/worksheets/sheet{}.xml", index + 1), &worksheet(table));
+    }
+    package.finish()
+}
+
+fn content_types(sheets: usize) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#, "\n",
+        r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+        r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+        r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+        r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+        r#"<Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>"#,
+    ));
+    for sheet in 1..=sheets {
+        xml.push_str(&format!(
+            r#"<Override PartName="/xl/worksheets/sheet{}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+            sheet
+        ));
+    }
+    xml.push_str("</Types>");
+    xml
+}
+
+/// Relationships numbered `rId1` onwards, by type and target
+fn relationships(targets: &[(&str, String)]) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"{}\">",
+        PACKAGE_RELATIONSHIP_NAMESPACE
+    );
+    for (index, (kind, target)) in targets.iter().enumerate() {
+        xml.push_str(&format!(
+            "<Relationship Id=\"rId{}\" Type=\"{}/{}\" Target=\"{}\"/>",
+            index + 1, RELATIONSHIP_NAMESPACE, kind, target
+        ));
+    }
+    xml.push_str("</Relationships>");
+    xml
+}
+
+fn workbook(tables: &[Table]) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<workbook xmlns=\"{}\" xmlns:r=\"{}\"><sheets>",
+        MAIN_NAMESPACE, RELATIONSHIP_NAMESPACE
+    );
+    for (index, table) in tables.iter().enumerate() {
+        xml.push_str(&format!(
+            "<sheet name=\"{}\" sheetId=\"{}\" r:id=\"rId{}\"/>",
+            escape_markup(&sheet_name(&table.name)), index + 1, index + 1
+        ));
+    }
+    xml.push_str("</sheets></workbook>");
+    xml
+}
+
+/// Worksheet with a bold, frozen header row
+fn worksheet(table: &Table) -> String {
+    let mut xml = format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<worksheet xmlns=\"{}\">",
+            "<sheetViews><sheetView workbookViewId=\"0\"><pane ySplit=\"1\" topLeftCell=\"A2\" activePane=\"bottomLeft\" state=\"frozen\"/></sheetView></sheetViews>",
+            "<cols><col min=\"1\" max=\"{}\" width=\"20\" customWidth=\"1\"/></cols><sheetData>"
+        ),
+        MAIN_NAMESPACE,
+        table.columns.len().max(1)
+    );
+
+    let header: Vec<Cell> = table.columns.iter().map(|column| Cell::Text(column.clone())).collect();
+    for (index, row) in std::iter::once(&header).chain(&table.rows).enumerate() {
+        let row_number = index + 1;
+        xml.push_str(&format!("<row r=\"{}\">", row_number));
+        for (column, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", column_name(column), row_number);
+            let style = if index == 0 { HEADER_STYLE } else { 0 };
+            xml.push_str(&cell_xml(&reference, cell, style));
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+fn cell_xml(reference: &str, cell: &Cell, style: u32) -> String {
+    let style_attribute = |style: u32| if style == 0 { String::new() } else { format!(" s=\"{}\"", style) };
+    match cell {
+        Cell::Text(text) => format!(
+            "<c r=\"{}\"{} t=\"inlineStr\"><is><t xml:space=\"preserve\">{}</t></is></c>",
+            reference, style_attribute(style), escape_markup(text)
+        ),
+        Cell::Number(number) => format!("<c r=\"{}\"{}><v>{}</v></c>", reference, style_attribute(style), number.normalize()),
+        Cell::Money(amount) => format!(
+            "<c r=\"{}\"{}><v>{}</v></c>",
+            reference, style_attribute(MONEY_STYLE), DecimalUtils::round_currency(*amount).normalize()
+        ),
+        Cell::Date(date) => format!("<c r=\"{}\"{}><v>{}</v></c>", reference, style_attribute(DATE_STYLE), serial_date(*date)),
+        Cell::Empty => String::new(),
+    }
+}
+
+/// Spreadsheet column letters for a zero-based index: A, B, ..., Z, AA, ...
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// Days since the spreadsheet epoch, as Excel counts them after February 1900
+fn serial_date(date: NaiveDate) -> i64 {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30).expect("valid epoch");
+    (date - epoch).num_days()
+}
+
+/// Sheet names are at most 31 characters and may not contain `[]:*?/\`
+fn sheet_name(name: &str) -> String {
+    let name: String = name.chars().filter(|c| !"[]:*?/\\".contains(*c)).take(31).collect();
+    if name.is_empty() { "Sheet".to_string() } else { name }
+}
+
+/// Zip archive of uncompressed entries
+#[derive(Default)]
+struct Package {
+    bytes: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+}
+
+impl Package {
+    /// DOS date of 1980-01-01 at midnight, so output does not depend on the clock
+    const MODIFIED_DATE: u16 = (1 << 5) | 1;
+
+    fn add(&mut self, name: &str, contents: &str) {
+        let offset = self.bytes.len() as u32;
+        let crc = crc32fast::hash(contents.as_bytes());
+        let size = contents.len() as u32;
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&20u16.to_le_bytes()); // version needed to extract
+        header.extend_from_slice(&0u16.to_le_bytes()); // flags
+        header.extend_from_slice(&0u16.to_le_bytes()); // stored
+        header.extend_from_slice(&0u16.to_le_bytes()); // modified time
+        header.extend_from_slice(&Self::MODIFIED_DATE.to_le_bytes());
+        header.extend_from_slice(&crc.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes()); // compressed size
+        header.extend_from_slice(&size.to_le_bytes());
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+
+        self.bytes.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        self.bytes.extend_from_slice(&header);
+        self.bytes.extend_from_slice(name.as_bytes());
+        self.bytes.extend_from_slice(contents.as_bytes());
+
+        self.central_directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        self.central_directory.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        self.central_directory.extend_from_slice(&header);
+        self.central_directory.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.central_directory.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        self.central_directory.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+        self.central_directory.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        self.central_directory.extend_from_slice(&offset.to_le_bytes());
+        self.central_directory.extend_from_slice(name.as_bytes());
+        self.entries += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let offset = self.bytes.len() as u32;
+        let size = self.central_directory.len() as u32;
+        self.bytes.append(&mut self.central_directory);
+
+        self.bytes.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.bytes.extend_from_slice(&0u16.to_le_bytes()); // this disk
+        self.bytes.extend_from_slice(&0u16.to_le_bytes()); // disk with the central directory
+        self.bytes.extend_from_slice(&self.entries.to_le_bytes());
+        self.bytes.extend_from_slice(&self.entries.to_le_bytes());
+        self.bytes.extend_from_slice(&size.to_le_bytes());
+        self.bytes.extend_from_slice(&offset.to_le_bytes());
+        self.bytes.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_cell_references_and_dates() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(27), "AB");
+        assert_eq!(serial_date(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()), 45292);
+        assert_eq!(cell_xml("C2", &Cell::Money(dec!(1234.567)), 0), "<c r=\"C2\" s=\"3\"><v>1234.57</v></c>");
+    }
+
+    #[test]
+    fn test_writes_a_zip_package_of_every_part() {
+        let mut table = Table::new("Summary", &["Item", "Value"]);
+        table.rows.push(vec![Cell::Text("Monthly Payment".to_string()), Cell::Money(dec!(1199.10))]);
+        let workbook = write(&[table]);
+
+        assert!(workbook.starts_with(b"PK\x03\x04"));
+        let contents = String::from_utf8_lossy(&workbook);
+        for part in ["[Content_Types].xml", "xl/workbook.xml", "xl/styles.xml", "xl/worksheets/sheet1.xml"] {
+            assert!(contents.contains(part), "missing {}", part);
+        }
+        assert!(contents.contains("<sheet name=\"Summary\" sheetId=\"1\" r:id=\"rId1\"/>"));
+        // End of central directory lists all six parts
+        let end = &workbook[workbook.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 6);
+    }
+}
@@ -7,6 +7,8 @@ pub mod api;
 pub mod models;
 pub mod calculators;
 pub mod utils;
+pub mod config;
+pub mod export;
 
 // Re-export commonly used items
 pub use models::{
@@ -15,20 +17,67 @@ pub use models::{
     MortgageSummary,
     PaymentScheduleEntry,
     RepaymentType,
+    CustomRepaymentType,
     RepaymentTypeInfo,
+    EscrowInput,
+    MortgageInsuranceInput,
+    MortgageInsuranceType,
+    PrepaymentPenalty,
+    AffordabilityInput,
+    AffordabilityResult,
+    SolveRequest,
+    SolveResult,
+    RefinanceInput,
+    RefinanceAnalysis,
+    ComparisonRequest,
+    ComparisonResult,
+    RentVsBuyInput,
+    RentVsBuyAnalysis,
+    PayoffRequest,
+    PayoffQuote,
+    PaymentHistoryRequest,
+    PaymentHistory,
+    LoanModification,
+    PoolRequest,
+    PoolProjection,
+    BatchItemResult,
+    AnalyticsRequest,
+    LoanAnalytics,
+    ValuationRequest,
+    PresentValue,
+    InterestStatementRequest,
+    InterestStatement,
 };
 
 pub use calculators::{
     MortgageCalculator,
+    CalculationError,
     StandardCalculator,
     InterestOnlyCalculator,
     AcceleratedCalculator,
     BalloonCalculator,
     FloatingRateCalculator,
+    EscrowCalculator,
+    MortgageInsuranceCalculator,
+    AffordabilityCalculator,
+    GoalSeekSolver,
+    RefinanceCalculator,
+    ComparisonCalculator,
+    RentVsBuyCalculator,
+    PayoffCalculator,
+    PrepaymentPenaltyCalculator,
+    PaymentHistoryReconciler,
+    ModificationCalculator,
+    PoolCalculator,
+    BatchCalculator,
+    AnalyticsCalculator,
+    ValuationCalculator,
+    InterestStatementCalculator,
 };
 
 pub use api::{
     configure_routes,
+    configure_routes_with,
     ApiError,
 };
 
@@ -43,14 +92,13 @@ pub fn new_mortgage_input(
 ) -> MortgageInput {
     use chrono::Local;
     
-    MortgageInput {
+    MortgageInput::new(
         principal,
         annual_interest_rate,
         term_years,
-        repayment_type: RepaymentType::StandardPrincipalAndInterest,
-        start_date: Local::now().date_naive(),
-        balloon_payment_percentage: rust_decimal::Decimal::ZERO,
-    }
+        RepaymentType::StandardPrincipalAndInterest,
+        Local::now().date_naive(),
+    )
 }
 
 #[cfg(test)]
@@ -1,44 +1,85 @@
+use std::io::Write;
+use std::process;
+
 use actix_web::{App, HttpServer};
 use actix_cors::Cors;
-use env_logger;
 use log::info;
 
+use mortgagekit_rs::config::{CliArgs, LogFormat, ServerConfig, USAGE};
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    // Initialize environment logger with timestamp
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
-    
-    let host = "0.0.0.0";
-    let port = 8080;
-    
+    let cli = CliArgs::parse(std::env::args().skip(1)).unwrap_or_else(|err| exit_with(&err));
+    if cli.help {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+
+    let config = ServerConfig::load(&cli, |name| std::env::var(name).ok())
+        .unwrap_or_else(|err| exit_with(&err));
+    if cli.print_config {
+        print!("{}", config.to_toml());
+        return Ok(());
+    }
+
+    init_logging(&config);
+
+    let (host, port) = (config.server.host.clone(), config.server.port);
     info!("Starting mortgagekit-rs server on http://{}:{}", host, port);
-    info!("Documentation available at http://{}:{}/api/docs", host, port);
+    if config.features.docs {
+        info!("Documentation available at http://{}:{}/api/docs", host, port);
+    }
 
-    HttpServer::new(|| {
-        // Configure CORS - in production, you'd want to restrict this
-        let cors = Cors::permissive();
+    let app_config = config.clone();
+    let mut server = HttpServer::new(move || {
+        let config = app_config.clone();
+        let cors = if config.cors.allows_any_origin() {
+            Cors::permissive()
+        } else {
+            config.cors.allowed_origins.iter()
+                .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+                .allow_any_method()
+                .allow_any_header()
+        };
 
         App::new()
             .wrap(cors)
             // Configure routes under /api/v1
-            .configure(mortgagekit_rs::api::configure_routes)
+            .configure(|cfg| mortgagekit_rs::api::configure_routes_with(cfg, &config))
             // Add middleware for logging, compression, etc.
             .wrap(actix_web::middleware::Logger::default())
             .wrap(actix_web::middleware::Compress::default())
-            // Add error handlers
-            .app_data(actix_web::web::JsonConfig::default()
-                .limit(4096) // Limit payload size to 4kb
-                .error_handler(|err, _| {
-                    let error_message = format!("JSON Error: {}", err);
-                    actix_web::error::InternalError::from_response(
-                        err,
-                        actix_web::HttpResponse::BadRequest()
-                            .json(error_message)
-                    ).into()
-                })
-            )
-    })
-    .bind((host, port))?
-    .run()
-    .await
+    });
+    if let Some(workers) = config.server.workers {
+        server = server.workers(workers);
+    }
+
+    server
+        .bind((host, port))?
+        .run()
+        .await
+}
+
+/// Initialize the logger at the configured level; `RUST_LOG` still refines it
+fn init_logging(config: &ServerConfig) {
+    let mut builder = env_logger::Builder::from_env(
+        env_logger::Env::new().default_filter_or(config.log.level.as_str())
+    );
+    if config.log.format == LogFormat::Json {
+        builder.format(|buf, record| {
+            writeln!(buf, "{}", serde_json::json!({
+                "timestamp": buf.timestamp().to_string(),
+                "level": record.level().to_string(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            }))
+        });
+    }
+    builder.init();
+}
+
+fn exit_with(err: &mortgagekit_rs::config::ConfigError) -> ! {
+    eprintln!("error: {}", err);
+    eprintln!("Run with --help for usage.");
+    process::exit(2);
 }
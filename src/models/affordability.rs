@@ -0,0 +1,142 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+/// Borrower finances and loan terms used to size the largest affordable loan
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AffordabilityInput {
+    /// Gross annual income of all borrowers
+    #[validate(custom = "validate_positive")]
+    pub gross_annual_income: Decimal,
+
+    /// Recurring monthly debt payments outside housing (cards, auto, student loans)
+    #[serde(default)]
+    #[validate(custom = "validate_non_negative")]
+    pub monthly_debts: Decimal,
+
+    /// Maximum housing payment as a share of gross monthly income
+    #[serde(default = "default_front_end_dti")]
+    #[validate(custom = "validate_ratio")]
+    pub front_end_dti_limit: Decimal,
+
+    /// Maximum total debt payments as a share of gross monthly income
+    #[serde(default = "default_back_end_dti")]
+    #[validate(custom = "validate_ratio")]
+    pub back_end_dti_limit: Decimal,
+
+    /// Annual rate as a fraction, e.g. 0.06 for 6%
+    #[validate(custom = "validate_rate")]
+    pub annual_interest_rate: Decimal,
+
+    #[validate(range(min = 1, max = 50))]
+    pub term_years: u32,
+
+    /// Annual property tax as a share of the purchase price
+    #[serde(default)]
+    #[validate(custom = "validate_ratio")]
+    pub property_tax_rate: Decimal,
+
+    /// Annual homeowners insurance premium
+    #[serde(default)]
+    #[validate(custom = "validate_non_negative")]
+    pub annual_insurance: Decimal,
+
+    /// Monthly HOA dues
+    #[serde(default)]
+    #[validate(custom = "validate_non_negative")]
+    pub monthly_hoa: Decimal,
+
+    /// Cash available for the down payment
+    #[serde(default)]
+    #[validate(custom = "validate_non_negative")]
+    pub down_payment: Decimal,
+}
+
+fn default_front_end_dti() -> Decimal {
+    dec!(0.28)
+}
+
+fn default_back_end_dti() -> Decimal {
+    dec!(0.36)
+}
+
+fn validate_positive(amount: &Decimal) -> Result<(), ValidationError> {
+    if *amount > dec!(0) && *amount <= dec!(1_000_000_000) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_amount"))
+    }
+}
+
+fn validate_non_negative(amount: &Decimal) -> Result<(), ValidationError> {
+    if *amount >= dec!(0) && *amount <= dec!(1_000_000_000) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_amount"))
+    }
+}
+
+fn validate_ratio(ratio: &Decimal) -> Result<(), ValidationError> {
+    if *ratio >= dec!(0) && *ratio <= dec!(1) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_ratio"))
+    }
+}
+
+fn validate_rate(rate: &Decimal) -> Result<(), ValidationError> {
+    if *rate >= dec!(0) && *rate <= dec!(1) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_rate"))
+    }
+}
+
+/// Debt-to-income ratio that caps the housing payment
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum LimitingRatio {
+    /// Housing payment alone hits the front-end limit
+    FrontEnd,
+
+    /// Housing payment plus other debts hits the back-end limit
+    BackEnd,
+}
+
+/// Maximum purchase price and loan amount for a borrower
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AffordabilityResult {
+    /// Highest purchase price the borrower qualifies for
+    pub max_purchase_price: Decimal,
+
+    /// Loan amount at the maximum purchase price
+    pub max_loan_amount: Decimal,
+
+    /// Largest allowed monthly housing payment (PITI plus HOA)
+    pub max_housing_payment: Decimal,
+
+    /// Monthly principal and interest at the maximum loan amount
+    pub monthly_principal_and_interest: Decimal,
+
+    /// Monthly property tax at the maximum purchase price
+    pub monthly_property_tax: Decimal,
+
+    /// Monthly homeowners insurance
+    pub monthly_insurance: Decimal,
+
+    /// Monthly HOA dues
+    pub monthly_hoa: Decimal,
+
+    /// Housing payment as a share of gross monthly income
+    pub front_end_ratio: Decimal,
+
+    /// Total debt payments as a share of gross monthly income
+    pub back_end_ratio: Decimal,
+
+    /// Which DTI limit determined the result
+    pub limiting_ratio: LimitingRatio,
+}
@@ -0,0 +1,72 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use super::input::MortgageInput;
+
+/// Loan to value and the price or yield it trades at
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyticsRequest {
+    #[validate]
+    pub input: MortgageInput,
+
+    #[validate(custom = "validate_pricing")]
+    pub pricing: LoanPricing,
+}
+
+/// Market level the loan's cash flows are valued at
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LoanPricing {
+    /// Price as a percentage of the original principal, e.g. 101.5
+    Price { price: Decimal },
+
+    /// Annual yield, compounded once per payment period
+    #[serde(rename_all = "camelCase")]
+    Yield { annual_yield: Decimal },
+}
+
+fn validate_pricing(pricing: &LoanPricing) -> Result<(), ValidationError> {
+    let valid = match pricing {
+        LoanPricing::Price { price } => *price > dec!(0) && *price <= dec!(1000),
+        LoanPricing::Yield { annual_yield } => *annual_yield > dec!(-0.5) && *annual_yield <= dec!(1),
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_pricing"))
+    }
+}
+
+/// Price sensitivity measures of a loan's cash flows
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LoanAnalytics {
+    /// Price as a percentage of the original principal
+    pub price: Decimal,
+
+    /// Present value of the cash flows
+    pub dollar_price: Decimal,
+
+    /// Annual yield compounded once per payment period
+    pub yield_to_maturity: Decimal,
+
+    /// Present-value-weighted average time to the cash flows, in years
+    pub macaulay_duration: Decimal,
+
+    /// Percentage price change per unit change in annual yield
+    pub modified_duration: Decimal,
+
+    /// In years squared
+    pub convexity: Decimal,
+
+    /// Average time to the return of principal, in years
+    pub weighted_average_life: Decimal,
+
+    /// Dollar price change for a one basis point fall in yield
+    pub dv01: Decimal,
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::schedule::MortgageSchedule;
+use super::summary::MortgageSummary;
+
+/// Results returned for each loan in a batch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchOutput {
+    #[default]
+    Summary,
+    Schedule,
+    Both,
+}
+
+/// Query options for a batch calculation
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query, rename_all = "camelCase")]
+pub struct BatchOptions {
+    /// Summary, schedule or both for each loan; summary by default
+    #[serde(default)]
+    pub output: BatchOutput,
+}
+
+/// Outcome for one loan in a batch, streamed back as a line of NDJSON
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchItemResult {
+    /// Position of the loan in the request, from zero
+    pub index: usize,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<MortgageSummary>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<MortgageSchedule>,
+
+    /// Why the loan could not be read or calculated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -0,0 +1,154 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use super::input::MortgageInput;
+use super::repayment_type::RepaymentType;
+use super::summary::MortgageSummary;
+
+/// Loans to compare side by side, either in full or as overrides of a base loan
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_scenarios"))]
+pub struct ComparisonRequest {
+    /// Loan that scenarios without a full input are derived from
+    #[serde(default)]
+    #[validate]
+    pub base: Option<MortgageInput>,
+
+    #[validate(length(min = 1, max = 10))]
+    pub scenarios: Vec<ComparisonScenario>,
+}
+
+/// One option in a comparison
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonScenario {
+    /// Label shown for the scenario; defaults to its position
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Complete loan terms; takes precedence over the base loan
+    #[serde(default)]
+    pub input: Option<MortgageInput>,
+
+    /// Changes applied to the base loan
+    #[serde(default)]
+    pub overrides: ScenarioOverrides,
+}
+
+/// Fields of the base loan a scenario may replace
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioOverrides {
+    pub principal: Option<Decimal>,
+    pub annual_interest_rate: Option<Decimal>,
+    pub term_years: Option<u32>,
+    pub repayment_type: Option<RepaymentType>,
+    pub balloon_payment_percentage: Option<Decimal>,
+    pub extra_payment: Option<Decimal>,
+}
+
+fn validate_scenarios(request: &ComparisonRequest) -> Result<(), ValidationError> {
+    if request.base.is_none() && request.scenarios.iter().any(|scenario| scenario.input.is_none()) {
+        Err(ValidationError::new("base_required_for_overrides"))
+    } else {
+        Ok(())
+    }
+}
+
+impl ComparisonRequest {
+    /// Resolves every scenario into a named, complete loan input
+    ///
+    /// Scenarios without an input are skipped when there is no base loan;
+    /// validation rejects such requests beforehand.
+    pub fn resolve(&self) -> Vec<(String, MortgageInput)> {
+        self.scenarios.iter()
+            .enumerate()
+            .filter_map(|(index, scenario)| {
+                let input = match (&scenario.input, &self.base) {
+                    (Some(input), _) => input.clone(),
+                    (None, Some(base)) => scenario.overrides.apply(base),
+                    (None, None) => return None,
+                };
+                let name = scenario.name.clone()
+                    .unwrap_or_else(|| format!("Scenario {}", index + 1));
+                Some((name, input))
+            })
+            .collect()
+    }
+}
+
+impl ScenarioOverrides {
+    /// Returns a copy of `base` with the overridden fields replaced
+    pub fn apply(&self, base: &MortgageInput) -> MortgageInput {
+        let mut input = base.clone();
+        if let Some(principal) = self.principal {
+            input.principal = principal;
+        }
+        if let Some(rate) = self.annual_interest_rate {
+            input.annual_interest_rate = rate;
+        }
+        if let Some(term_years) = self.term_years {
+            input.term_years = term_years;
+        }
+        if let Some(repayment_type) = self.repayment_type {
+            input.repayment_type = repayment_type;
+        }
+        if let Some(percentage) = self.balloon_payment_percentage {
+            input.balloon_payment_percentage = percentage;
+        }
+        if let Some(extra_payment) = self.extra_payment {
+            input.extra_payment = extra_payment;
+        }
+        input
+    }
+}
+
+/// Side-by-side results for every scenario
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonResult {
+    /// Summaries in request order
+    pub scenarios: Vec<ScenarioResult>,
+
+    /// `differences[i][j]` holds scenario `j` minus scenario `i`
+    pub differences: Vec<Vec<ScenarioDifference>>,
+
+    /// Month-by-month cumulative payments and interest, aligned across scenarios
+    pub cost_curves: Vec<CostCurvePoint>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioResult {
+    pub name: String,
+    pub input: MortgageInput,
+    pub summary: MortgageSummary,
+}
+
+/// Difference between two scenario summaries
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioDifference {
+    pub monthly_payment: Decimal,
+    pub total_monthly_payment: Decimal,
+    pub total_payments: Decimal,
+    pub total_interest: Decimal,
+    pub number_of_payments: i64,
+}
+
+/// Cumulative cost of each scenario at the end of a loan month
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CostCurvePoint {
+    /// Loan month (1-based), counted in 30-day periods from each scenario's start date
+    pub month: u32,
+
+    /// Cumulative payments per scenario, in scenario order
+    pub cumulative_payments: Vec<Decimal>,
+
+    /// Cumulative interest per scenario, in scenario order
+    pub cumulative_interest: Vec<Decimal>,
+}
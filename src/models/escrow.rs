@@ -0,0 +1,127 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+/// Escrowed property charges collected alongside the P&I payment
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EscrowInput {
+    /// Annual property tax bill in the first loan year
+    #[serde(default)]
+    #[validate(custom = "validate_amount")]
+    pub annual_property_tax: Decimal,
+
+    /// Annual homeowners insurance premium in the first loan year
+    #[serde(default)]
+    #[validate(custom = "validate_amount")]
+    pub annual_insurance: Decimal,
+
+    /// Annual HOA dues in the first loan year
+    #[serde(default)]
+    #[validate(custom = "validate_amount")]
+    pub annual_hoa: Decimal,
+
+    /// Yearly growth rate applied to property tax (e.g. 0.03 for 3%)
+    #[serde(default)]
+    #[validate(custom = "validate_growth_rate")]
+    pub property_tax_growth_rate: Decimal,
+
+    /// Yearly growth rate applied to the insurance premium
+    #[serde(default)]
+    #[validate(custom = "validate_growth_rate")]
+    pub insurance_growth_rate: Decimal,
+
+    /// Yearly growth rate applied to HOA dues
+    #[serde(default)]
+    #[validate(custom = "validate_growth_rate")]
+    pub hoa_growth_rate: Decimal,
+
+    /// Calendar months (1-12) in which property tax is disbursed; empty means monthly
+    #[serde(default)]
+    #[validate(custom = "validate_months")]
+    pub property_tax_disbursement_months: Vec<u32>,
+
+    /// Calendar months (1-12) in which insurance is disbursed; empty means monthly
+    #[serde(default)]
+    #[validate(custom = "validate_months")]
+    pub insurance_disbursement_months: Vec<u32>,
+
+    /// Calendar months (1-12) in which HOA dues are disbursed; empty means monthly
+    #[serde(default)]
+    #[validate(custom = "validate_months")]
+    pub hoa_disbursement_months: Vec<u32>,
+
+    /// Cushion held in the account, in months of disbursements (RESPA caps this at 2)
+    #[serde(default = "default_cushion_months")]
+    #[validate(range(max = 2))]
+    pub cushion_months: u32,
+}
+
+fn default_cushion_months() -> u32 {
+    2
+}
+
+fn validate_amount(amount: &Decimal) -> Result<(), ValidationError> {
+    if *amount >= dec!(0) && *amount <= dec!(100_000_000) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_amount"))
+    }
+}
+
+fn validate_growth_rate(rate: &Decimal) -> Result<(), ValidationError> {
+    if *rate >= dec!(-1) && *rate <= dec!(1) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_growth_rate"))
+    }
+}
+
+fn validate_months(months: &[u32]) -> Result<(), ValidationError> {
+    if months.iter().all(|month| (1..=12).contains(month)) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_month"))
+    }
+}
+
+/// Annual RESPA-style escrow account analysis
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EscrowAnalysis {
+    /// Escrow year covered by this analysis (1-based)
+    pub escrow_year: u32,
+
+    /// Date of the first payment in the escrow year
+    pub analysis_date: NaiveDate,
+
+    /// Escrow balance before the first deposit of the year
+    pub beginning_balance: Decimal,
+
+    /// Disbursements projected for the coming twelve months
+    pub projected_disbursements: Decimal,
+
+    /// Cushion the account must not fall below
+    pub required_cushion: Decimal,
+
+    /// Lowest projected balance when collecting only the base deposit
+    pub lowest_projected_balance: Decimal,
+
+    /// Amount by which the lowest balance falls short of the cushion
+    pub shortage: Decimal,
+
+    /// Amount by which the lowest balance exceeds the cushion
+    pub surplus: Decimal,
+
+    /// Surplus refunded to the borrower at analysis time
+    pub refund: Decimal,
+
+    /// Escrow deposit collected with each payment during the year
+    pub periodic_deposit: Decimal,
+
+    /// Monthly equivalent of the periodic deposit
+    pub monthly_deposit: Decimal,
+}
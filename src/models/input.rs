@@ -1,13 +1,29 @@
+use std::ops::RangeInclusive;
+
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize};
-use validator::{Validate, ValidationError};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError, ValidationErrors};
 
 use super::repayment_type::RepaymentType;
+use super::escrow::EscrowInput;
+use super::mortgage_insurance::MortgageInsuranceInput;
+use super::prepayment_penalty::{validate_prepayment_penalty, PrepaymentPenalty};
+use super::modification::{validate_modifications, LoanModification};
+use crate::utils::DecimalUtils;
+
+/// Years a loan may start in
+const START_YEARS: RangeInclusive<i32> = 1900..=2100;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+/// Largest `(1 + monthly rate)^payments` the payment calculation can carry
+/// while leaving headroom for the multiplications that follow it
+const MAX_PAYMENT_FACTOR: Decimal = dec!(1_000_000_000_000_000_000_000_000);
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
 #[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_mortgage_insurance", skip_on_field_errors = false))]
 pub struct MortgageInput {
     #[validate(custom = "validate_principal")]
     pub principal: Decimal,
@@ -24,6 +40,131 @@ pub struct MortgageInput {
     #[serde(default)]
     #[validate(custom = "validate_percentage")]
     pub balloon_payment_percentage: Decimal,
+
+    /// Additional principal paid with every scheduled payment
+    #[serde(default)]
+    #[validate(custom = "validate_extra_payment")]
+    pub extra_payment: Decimal,
+
+    /// Property tax, insurance and HOA collected into escrow
+    #[serde(default)]
+    #[validate]
+    pub escrow: Option<EscrowInput>,
+
+    /// Appraised value of the property at origination
+    #[serde(default)]
+    #[validate(custom = "validate_principal")]
+    pub property_value: Option<Decimal>,
+
+    /// PMI or FHA mortgage insurance terms
+    #[serde(default)]
+    #[validate]
+    pub mortgage_insurance: Option<MortgageInsuranceInput>,
+
+    /// Penalty charged on principal repaid ahead of schedule
+    #[serde(default)]
+    #[validate(custom = "validate_prepayment_penalty")]
+    pub prepayment_penalty: Option<PrepaymentPenalty>,
+
+    /// Forbearance and modification events applied mid-life
+    #[serde(default)]
+    #[validate(custom = "validate_modifications")]
+    pub modifications: Vec<LoanModification>,
+}
+
+impl MortgageInput {
+    /// Loan without a balloon, extra payments, escrow, insurance, prepayment penalty or modifications
+    pub fn new(
+        principal: Decimal,
+        annual_interest_rate: Decimal,
+        term_years: u32,
+        repayment_type: RepaymentType,
+        start_date: NaiveDate,
+    ) -> Self {
+        Self {
+            principal,
+            annual_interest_rate,
+            term_years,
+            repayment_type,
+            start_date,
+            balloon_payment_percentage: Decimal::ZERO,
+            extra_payment: Decimal::ZERO,
+            escrow: None,
+            property_value: None,
+            mortgage_insurance: None,
+            prepayment_penalty: None,
+            modifications: Vec::new(),
+        }
+    }
+
+    /// Returns the original loan-to-value ratio when a property value is known
+    pub fn original_ltv(&self) -> Option<Decimal> {
+        self.property_value
+            .filter(|value| !value.is_zero())
+            .map(|value| self.principal / value)
+    }
+
+    /// Validates the loan as a loan of `repayment_type`
+    ///
+    /// Runs the per-field rules and then the rules spanning several fields: a balloon
+    /// percentage strictly between 0 and 100 for balloon loans and none otherwise, a start
+    /// date between 1900 and 2100, and a rate and term whose payment factor stays
+    /// representable. `repayment_type` may differ from the input's own, e.g. when an
+    /// override changes it.
+    pub fn validate_for(&self, repayment_type: RepaymentType) -> Result<(), ValidationErrors> {
+        let mut errors = self.validate().err().unwrap_or_default();
+
+        let balloon = self.balloon_payment_percentage;
+        let balloon_error = if repayment_type.requires_balloon_percentage() {
+            if balloon <= Decimal::ZERO {
+                Some(("balloon_percentage_required", format!("is required for {} loans", repayment_type)))
+            } else if balloon >= dec!(100) {
+                Some(("invalid_balloon_percentage", "must be below 100".to_string()))
+            } else {
+                None
+            }
+        } else if !balloon.is_zero() {
+            Some(("balloon_percentage_not_allowed", format!("is not allowed for {} loans", repayment_type)))
+        } else {
+            None
+        };
+        if let Some((code, message)) = balloon_error {
+            errors.add("balloon_payment_percentage", business_rule(code, message));
+        }
+
+        if !START_YEARS.contains(&self.start_date.year()) {
+            errors.add("start_date", business_rule(
+                "start_date_out_of_range",
+                format!("must fall between {} and {}", START_YEARS.start(), START_YEARS.end()),
+            ));
+        }
+
+        // Interest-only payments never compound the rate over the term
+        let rate_or_term_invalid = ["annual_interest_rate", "term_years"].iter()
+            .any(|field| errors.errors().contains_key(field));
+        if repayment_type != RepaymentType::InterestOnly && !rate_or_term_invalid {
+            let monthly_rate = self.annual_interest_rate / dec!(12);
+            let factor = DecimalUtils::checked_power(dec!(1) + monthly_rate, i64::from(self.term_years) * 12);
+            if factor.filter(|factor| *factor <= MAX_PAYMENT_FACTOR).is_none() {
+                errors.add("annual_interest_rate", business_rule(
+                    "payment_factor_overflow",
+                    format!("is too high to amortize over {} years", self.term_years),
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn business_rule(code: &'static str, message: String) -> ValidationError {
+    let mut error = ValidationError::new(code);
+    error.message = Some(message.into());
+    error
 }
 
 fn validate_principal(principal: &Decimal) -> Result<(), ValidationError> {
@@ -42,6 +183,14 @@ fn validate_rate(rate: &Decimal) -> Result<(), ValidationError> {
     }
 }
 
+fn validate_extra_payment(amount: &Decimal) -> Result<(), ValidationError> {
+    if *amount >= dec!(0) && *amount <= dec!(1_000_000_000) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_extra_payment"))
+    }
+}
+
 fn validate_percentage(percentage: &Decimal) -> Result<(), ValidationError> {
     if *percentage >= dec!(0) && *percentage <= dec!(100) {
         Ok(())
@@ -49,3 +198,85 @@ fn validate_percentage(percentage: &Decimal) -> Result<(), ValidationError> {
         Err(ValidationError::new("invalid_percentage"))
     }
 }
+
+fn validate_mortgage_insurance(input: &MortgageInput) -> Result<(), ValidationError> {
+    if input.mortgage_insurance.is_some() && input.property_value.is_none() {
+        Err(ValidationError::new("property_value_required"))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(repayment_type: RepaymentType) -> MortgageInput {
+        MortgageInput::new(
+            dec!(300000),
+            dec!(0.05),
+            30,
+            repayment_type,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        )
+    }
+
+    fn codes(result: Result<(), ValidationErrors>, field: &str) -> Vec<String> {
+        let errors = result.err().unwrap_or_default();
+        errors.field_errors().get(field)
+            .map(|errors| errors.iter().map(|error| error.code.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_balloon_percentage_rules() {
+        let mut balloon = input(RepaymentType::BalloonPayment);
+        assert_eq!(codes(balloon.validate_for(RepaymentType::BalloonPayment), "balloon_payment_percentage"),
+            ["balloon_percentage_required"]);
+
+        balloon.balloon_payment_percentage = dec!(100);
+        assert!(balloon.validate().is_ok());
+        assert_eq!(codes(balloon.validate_for(RepaymentType::BalloonPayment), "balloon_payment_percentage"),
+            ["invalid_balloon_percentage"]);
+
+        balloon.balloon_payment_percentage = dec!(20);
+        assert!(balloon.validate_for(RepaymentType::BalloonPayment).is_ok());
+        assert_eq!(codes(balloon.validate_for(RepaymentType::StandardPrincipalAndInterest), "balloon_payment_percentage"),
+            ["balloon_percentage_not_allowed"]);
+    }
+
+    #[test]
+    fn test_start_date_window() {
+        let mut loan = input(RepaymentType::StandardPrincipalAndInterest);
+        loan.start_date = NaiveDate::from_ymd_opt(1850, 6, 1).unwrap();
+        assert_eq!(codes(loan.validate_for(loan.repayment_type), "start_date"), ["start_date_out_of_range"]);
+
+        loan.start_date = NaiveDate::from_ymd_opt(2100, 12, 31).unwrap();
+        assert!(loan.validate_for(loan.repayment_type).is_ok());
+    }
+
+    #[test]
+    fn test_payment_factor_overflow() {
+        let mut loan = input(RepaymentType::AcceleratedBiweekly);
+        loan.annual_interest_rate = dec!(50);
+        loan.term_years = 40;
+        assert_eq!(codes(loan.validate_for(loan.repayment_type), "annual_interest_rate"), ["payment_factor_overflow"]);
+
+        // Interest-only loans never compound the rate
+        assert!(loan.validate_for(RepaymentType::InterestOnly).is_ok());
+
+        loan.annual_interest_rate = dec!(0.25);
+        assert!(loan.validate_for(loan.repayment_type).is_ok());
+    }
+
+    #[test]
+    fn test_field_errors_are_kept() {
+        let mut loan = input(RepaymentType::StandardPrincipalAndInterest);
+        loan.principal = dec!(-1);
+        loan.balloon_payment_percentage = dec!(10);
+        let errors = loan.validate_for(loan.repayment_type).unwrap_err();
+
+        assert!(errors.field_errors().contains_key("principal"));
+        assert!(errors.field_errors().contains_key("balloon_payment_percentage"));
+    }
+}
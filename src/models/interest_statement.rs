@@ -0,0 +1,98 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use super::input::MortgageInput;
+use super::payment_history::{default_day_count, LateFeePolicy, ReceivedPayment};
+use super::payoff::DayCountConvention;
+
+/// Loan and tax year to report mortgage interest for
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_tax_year"))]
+pub struct InterestStatementRequest {
+    #[validate]
+    pub input: MortgageInput,
+
+    #[validate(range(min = 1900, max = 2200))]
+    pub tax_year: i32,
+
+    /// Points paid on the purchase of a principal residence, reported for the year the loan starts
+    #[serde(default)]
+    #[validate(custom = "validate_points")]
+    pub points_paid: Decimal,
+
+    /// Payments received; reported from the schedule when absent
+    #[validate(length(max = 1200))]
+    #[validate]
+    pub payments: Option<Vec<ReceivedPayment>>,
+
+    #[serde(default)]
+    #[validate]
+    pub late_fee: LateFeePolicy,
+
+    /// Convention for accruing interest between actual payment dates
+    #[serde(default = "default_day_count")]
+    pub day_count: DayCountConvention,
+}
+
+fn validate_points(points: &Decimal) -> Result<(), ValidationError> {
+    if *points >= dec!(0) && *points <= dec!(1_000_000_000) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_points"))
+    }
+}
+
+/// Statements cover years the loan was outstanding in
+fn validate_tax_year(request: &InterestStatementRequest) -> Result<(), ValidationError> {
+    if request.tax_year >= request.input.start_date.year() {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_tax_year"))
+    }
+}
+
+/// Where the statement's figures come from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum StatementSource {
+    /// Installments due in the year, as scheduled
+    Schedule,
+
+    /// Payments received in the year, as applied by the servicer
+    PaymentHistory,
+}
+
+/// Year-end mortgage interest statement, in the shape of IRS Form 1098
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InterestStatement {
+    pub tax_year: i32,
+
+    pub source: StatementSource,
+
+    /// Box 1: interest received from the borrower in the year
+    pub mortgage_interest: Decimal,
+
+    /// Box 2: principal outstanding on January 1, or at origination for loans starting in the year
+    pub outstanding_principal: Decimal,
+
+    /// Box 3
+    pub origination_date: NaiveDate,
+
+    /// Box 5: periodic mortgage insurance premiums paid in the year; upfront premiums are excluded
+    pub mortgage_insurance_premiums: Decimal,
+
+    /// Box 6: points paid on purchase, in the year the loan starts
+    pub points_paid: Decimal,
+
+    /// Installments paid in the year
+    pub number_of_payments: u32,
+
+    /// Principal outstanding after the year's last payment
+    pub ending_principal: Decimal,
+}
@@ -3,9 +3,60 @@ mod schedule;
 mod summary;
 mod repayment_type;
 mod repayment_info;
+mod escrow;
+mod mortgage_insurance;
+mod affordability;
+mod solve;
+mod refinance;
+mod comparison;
+mod rent_vs_buy;
+mod payoff;
+mod prepayment_penalty;
+mod payment_history;
+mod modification;
+mod pool;
+mod batch;
+mod analytics;
+mod valuation;
+mod schedule_query;
+mod interest_statement;
 
 pub use input::MortgageInput;
 pub use schedule::{MortgageSchedule, PaymentScheduleEntry};
 pub use summary::MortgageSummary;
-pub use repayment_type::RepaymentType;
+pub use repayment_type::{CustomRepaymentType, RepaymentType};
 pub use repayment_info::RepaymentTypeInfo;
+pub use escrow::{EscrowInput, EscrowAnalysis};
+pub use mortgage_insurance::{MortgageInsuranceInput, MortgageInsuranceType};
+pub use affordability::{AffordabilityInput, AffordabilityResult, LimitingRatio};
+pub use solve::{SolveRequest, SolveResult, SolveTarget, SolveVariable};
+pub use refinance::{RefinanceAnalysis, RefinanceInput, RefinanceOffer, RefinanceOfferResult};
+pub use comparison::{
+    ComparisonRequest,
+    ComparisonResult,
+    ComparisonScenario,
+    CostCurvePoint,
+    ScenarioDifference,
+    ScenarioOverrides,
+    ScenarioResult,
+};
+pub use rent_vs_buy::{RentVsBuyAnalysis, RentVsBuyInput, RentVsBuyYear};
+pub use payoff::{DayCountConvention, PayoffQuote, PayoffRequest};
+pub use prepayment_penalty::PrepaymentPenalty;
+pub use payment_history::{
+    DelinquencyStatus,
+    InstallmentStatus,
+    LateFeePolicy,
+    PaymentApplication,
+    PaymentHistory,
+    PaymentHistoryRequest,
+    ReceivedPayment,
+    ReconciledInstallment,
+};
+pub use modification::{ForbearanceInterest, LoanModification};
+pub use pool::{DefaultAssumptions, PoolCashFlow, PoolProjection, PoolRequest, PrepaymentSpeed};
+pub use batch::{BatchItemResult, BatchOptions, BatchOutput};
+pub use analytics::{AnalyticsRequest, LoanAnalytics, LoanPricing};
+pub use valuation::{DiscountCurve, ParRatePoint, PresentValue, ValuationRequest, ZeroRatePoint};
+pub use schedule_query::{ScheduleAggregation, ScheduleQuery, ScheduleYear, DEFAULT_PAGE_SIZE};
+pub use interest_statement::{InterestStatement, InterestStatementRequest, StatementSource};
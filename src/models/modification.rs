@@ -0,0 +1,86 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::ValidationError;
+
+/// What happens to interest accrued while payments are suspended
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ForbearanceInterest {
+    /// Added to the interest-bearing balance when payments resume
+    #[default]
+    Capitalized,
+
+    /// Held in the non-interest-bearing deferred balance due at maturity
+    Deferred,
+}
+
+/// Change to the loan's terms taking effect at a given payment
+///
+/// Every event re-amortizes the interest-bearing balance into level payments
+/// over the payments left, as a loan modification does.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LoanModification {
+    /// Payments suspended for a number of months; the remaining payments follow afterwards
+    #[serde(rename_all = "camelCase")]
+    Forbearance {
+        start_payment: u32,
+        months: u32,
+        #[serde(default)]
+        interest: ForbearanceInterest,
+    },
+
+    /// Months added to the remaining term
+    #[serde(rename_all = "camelCase")]
+    TermExtension {
+        start_payment: u32,
+        additional_months: u32,
+    },
+
+    /// New annual rate for the remaining term
+    #[serde(rename_all = "camelCase")]
+    RateReduction {
+        start_payment: u32,
+        annual_interest_rate: Decimal,
+    },
+
+    /// Principal moved to the non-interest-bearing deferred balance due at maturity
+    #[serde(rename_all = "camelCase")]
+    PrincipalForbearance {
+        start_payment: u32,
+        amount: Decimal,
+    },
+}
+
+impl LoanModification {
+    /// Payment number at which the event takes effect
+    pub fn start_payment(&self) -> u32 {
+        match self {
+            Self::Forbearance { start_payment, .. }
+            | Self::TermExtension { start_payment, .. }
+            | Self::RateReduction { start_payment, .. }
+            | Self::PrincipalForbearance { start_payment, .. } => *start_payment,
+        }
+    }
+}
+
+pub(crate) fn validate_modifications(modifications: &[LoanModification]) -> Result<(), ValidationError> {
+    let valid = modifications.len() <= 20 && modifications.iter().all(|modification| {
+        modification.start_payment() >= 1 && match modification {
+            LoanModification::Forbearance { months, .. } => (1..=60).contains(months),
+            LoanModification::TermExtension { additional_months, .. } => (1..=480).contains(additional_months),
+            LoanModification::RateReduction { annual_interest_rate, .. } =>
+                *annual_interest_rate >= dec!(0) && *annual_interest_rate <= dec!(1),
+            LoanModification::PrincipalForbearance { amount, .. } =>
+                *amount > dec!(0) && *amount <= dec!(1_000_000_000),
+        }
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_modification"))
+    }
+}
@@ -0,0 +1,45 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+/// Kinds of mortgage insurance
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum MortgageInsuranceType {
+    /// Borrower-paid private mortgage insurance on a conventional loan
+    PrivateMortgageInsurance,
+
+    /// FHA upfront and annual mortgage insurance premium
+    Fha,
+}
+
+/// Mortgage insurance terms attached to a loan
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MortgageInsuranceInput {
+    pub insurance_type: MortgageInsuranceType,
+
+    /// Borrower credit score used to pick the PMI rate tier
+    #[serde(default)]
+    #[validate(range(min = 300, max = 850))]
+    pub credit_score: Option<u32>,
+
+    /// Annual premium rate overriding the built-in rate tables
+    #[serde(default)]
+    #[validate(custom = "validate_premium_rate")]
+    pub annual_rate: Option<Decimal>,
+
+    /// Whether the borrower requests PMI cancellation at 80% of original value
+    #[serde(default)]
+    pub borrower_requested_cancellation: bool,
+}
+
+fn validate_premium_rate(rate: &Decimal) -> Result<(), ValidationError> {
+    if *rate >= dec!(0) && *rate <= dec!(0.05) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_premium_rate"))
+    }
+}
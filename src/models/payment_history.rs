@@ -0,0 +1,215 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use super::input::MortgageInput;
+use super::payoff::DayCountConvention;
+
+/// Contractual loan terms and the payments actually received on it
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentHistoryRequest {
+    #[validate]
+    pub input: MortgageInput,
+
+    /// Payments received, in any order
+    #[validate(length(max = 1200))]
+    #[validate]
+    pub payments: Vec<ReceivedPayment>,
+
+    /// Date the history is reconciled through
+    pub as_of_date: NaiveDate,
+
+    #[serde(default)]
+    #[validate]
+    pub late_fee: LateFeePolicy,
+
+    /// Convention for accruing interest between actual payment dates
+    #[serde(default = "default_day_count")]
+    pub day_count: DayCountConvention,
+}
+
+/// A payment received from the borrower
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivedPayment {
+    pub received_date: NaiveDate,
+
+    #[validate(custom = "validate_positive")]
+    pub amount: Decimal,
+}
+
+/// When late fees are assessed and how large they are
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LateFeePolicy {
+    /// Days after the due date before an unpaid installment is charged a late fee
+    #[validate(range(max = 60))]
+    pub grace_period_days: u32,
+
+    /// Late fee as a percentage of the installment's principal and interest
+    #[validate(custom = "validate_percentage")]
+    pub percentage: Decimal,
+}
+
+impl Default for LateFeePolicy {
+    fn default() -> Self {
+        Self {
+            grace_period_days: 15,
+            percentage: dec!(5),
+        }
+    }
+}
+
+/// Payments due every 30 days accrue exactly the scheduled interest on actual/360
+pub(crate) fn default_day_count() -> DayCountConvention {
+    DayCountConvention::Actual360
+}
+
+fn validate_positive(amount: &Decimal) -> Result<(), ValidationError> {
+    if *amount > dec!(0) && *amount <= dec!(1_000_000_000) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_amount"))
+    }
+}
+
+fn validate_percentage(percentage: &Decimal) -> Result<(), ValidationError> {
+    if *percentage >= dec!(0) && *percentage <= dec!(100) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_percentage"))
+    }
+}
+
+/// Standing of a single contractual installment
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallmentStatus {
+    /// Satisfied within the grace period
+    Paid,
+
+    /// Satisfied after the grace period
+    PaidLate,
+
+    /// Due and not yet satisfied
+    PastDue,
+
+    /// Satisfied ahead of its due date
+    PaidAhead,
+}
+
+/// Delinquency bucket by days past due of the oldest unpaid installment
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum DelinquencyStatus {
+    Current,
+    ThirtyDays,
+    SixtyDays,
+    NinetyDays,
+    OneHundredTwentyDaysPlus,
+}
+
+impl DelinquencyStatus {
+    pub fn from_days_past_due(days: i64) -> Self {
+        match days {
+            i64::MIN..=29 => Self::Current,
+            30..=59 => Self::ThirtyDays,
+            60..=89 => Self::SixtyDays,
+            90..=119 => Self::NinetyDays,
+            _ => Self::OneHundredTwentyDaysPlus,
+        }
+    }
+}
+
+/// One contractual installment compared with how it was actually paid
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciledInstallment {
+    pub payment_number: u32,
+    pub due_date: NaiveDate,
+
+    /// Principal, interest, escrow and mortgage insurance due
+    pub amount_due: Decimal,
+
+    pub scheduled_principal: Decimal,
+    pub scheduled_interest: Decimal,
+    pub scheduled_balance: Decimal,
+
+    pub status: InstallmentStatus,
+
+    /// Date funds in suspense first covered the installment
+    pub paid_date: Option<NaiveDate>,
+
+    /// Days between the due date and the paid date, or the as-of date if unpaid
+    pub days_late: i64,
+
+    /// Interest accrued on actual dates and paid with this installment
+    pub actual_interest: Option<Decimal>,
+    pub actual_principal: Option<Decimal>,
+    pub actual_balance: Option<Decimal>,
+
+    /// Late fee assessed on this installment
+    pub late_fee: Decimal,
+}
+
+/// How one received payment was applied
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentApplication {
+    pub received_date: NaiveDate,
+    pub amount: Decimal,
+
+    /// Installments satisfied by this payment together with earlier suspense funds
+    pub installments_paid: Vec<u32>,
+
+    pub interest: Decimal,
+    pub principal: Decimal,
+
+    /// Escrow and mortgage insurance portion of the installments paid
+    pub escrow: Decimal,
+
+    pub late_fees: Decimal,
+
+    /// Funds left over after the installments due, applied to principal
+    pub principal_curtailment: Decimal,
+
+    /// Unapplied funds held after this payment
+    pub suspense_balance: Decimal,
+}
+
+/// Loan history re-derived from actual payments
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentHistory {
+    pub as_of_date: NaiveDate,
+
+    /// Installments due by the as-of date, and any paid ahead of it
+    pub installments: Vec<ReconciledInstallment>,
+
+    pub applications: Vec<PaymentApplication>,
+
+    /// Principal owed after the payments actually applied
+    pub actual_balance: Decimal,
+
+    /// Principal owed had every installment been paid as scheduled
+    pub scheduled_balance: Decimal,
+
+    /// Actual balance minus scheduled balance
+    pub balance_variance: Decimal,
+
+    /// Interest accrued on the actual balance and not yet paid
+    pub accrued_interest: Decimal,
+
+    pub suspense_balance: Decimal,
+    pub late_fees_assessed: Decimal,
+    pub late_fees_outstanding: Decimal,
+
+    pub installments_past_due: u32,
+    pub amount_past_due: Decimal,
+    pub days_past_due: i64,
+    pub delinquency_status: DelinquencyStatus,
+}
@@ -0,0 +1,95 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use super::input::MortgageInput;
+use crate::utils::DateUtils;
+
+/// Convention for counting accrual days and the length of the interest year
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum DayCountConvention {
+    /// 30-day months over a 360-day year
+    #[default]
+    #[serde(rename = "30/360")]
+    Thirty360,
+
+    /// Actual days over a 360-day year
+    #[serde(rename = "actual/360")]
+    Actual360,
+
+    /// Actual days over a 365-day year
+    #[serde(rename = "actual/365")]
+    Actual365,
+}
+
+impl DayCountConvention {
+    /// Days of interest accrued from `start_date` up to `end_date`
+    pub fn days(&self, start_date: NaiveDate, end_date: NaiveDate) -> i64 {
+        match self {
+            Self::Thirty360 => DateUtils::days_30_360(start_date, end_date),
+            Self::Actual360 | Self::Actual365 => (end_date - start_date).num_days(),
+        }
+    }
+
+    /// Days in the interest year
+    pub fn year_basis(&self) -> Decimal {
+        match self {
+            Self::Thirty360 | Self::Actual360 => dec!(360),
+            Self::Actual365 => dec!(365),
+        }
+    }
+}
+
+/// Loan and date for which a payoff statement is requested
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoffRequest {
+    #[validate]
+    pub input: MortgageInput,
+
+    /// Date the loan is to be paid off
+    pub payoff_date: NaiveDate,
+
+    #[serde(default)]
+    pub day_count: DayCountConvention,
+}
+
+/// Amount needed to retire the loan on the payoff date
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoffQuote {
+    pub payoff_date: NaiveDate,
+
+    /// Number of the last installment due on or before the payoff date
+    pub last_paid_payment_number: u32,
+
+    /// Date interest has been paid through
+    pub interest_paid_through: NaiveDate,
+
+    /// Principal still owed after the last paid installment
+    pub outstanding_principal: Decimal,
+
+    /// Annual rate accruing on the outstanding principal
+    pub annual_interest_rate: Decimal,
+
+    pub day_count: DayCountConvention,
+
+    /// Days of interest accrued since the last paid installment
+    pub days_accrued: i64,
+
+    /// Interest accruing per day
+    pub per_diem: Decimal,
+
+    /// Interest accrued up to the payoff date
+    pub accrued_interest: Decimal,
+
+    /// Penalty charged for repaying the loan early
+    pub prepayment_penalty: Decimal,
+
+    /// Principal, accrued interest and penalty
+    pub total_payoff_amount: Decimal,
+}
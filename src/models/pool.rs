@@ -0,0 +1,146 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use super::input::MortgageInput;
+use crate::utils::DecimalUtils;
+
+/// Loans pooled together and the behavior assumed for them
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolRequest {
+    #[validate(length(min = 1, max = 500))]
+    #[validate]
+    pub loans: Vec<MortgageInput>,
+
+    /// Voluntary prepayment speed; loans pay as scheduled when omitted
+    #[serde(default)]
+    #[validate(custom = "validate_prepayment_speed")]
+    pub prepayment: Option<PrepaymentSpeed>,
+
+    /// Default assumptions; loans always perform when omitted
+    #[serde(default)]
+    #[validate]
+    pub defaults: Option<DefaultAssumptions>,
+}
+
+/// Voluntary prepayment speed, in percent
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PrepaymentSpeed {
+    /// Constant prepayment rate: annual share of the balance prepaid
+    Cpr { rate: Decimal },
+
+    /// Single monthly mortality: monthly share of the balance prepaid
+    Smm { rate: Decimal },
+
+    /// Multiple of the PSA benchmark, which ramps from 0.2% CPR in month one
+    /// to 6% CPR in month thirty; 100 is the benchmark itself
+    Psa { speed: Decimal },
+}
+
+impl PrepaymentSpeed {
+    /// Annual prepayment rate as a fraction for a loan in its `loan_month` month
+    pub fn cpr(&self, loan_month: u32) -> Decimal {
+        match self {
+            Self::Cpr { rate } => rate / dec!(100),
+            Self::Smm { rate } => dec!(1) - DecimalUtils::power(dec!(1) - rate / dec!(100), 12),
+            Self::Psa { speed } => {
+                let ramp = Decimal::from(loan_month.clamp(1, 30)) / dec!(30);
+                (dec!(0.06) * ramp * speed / dec!(100)).min(dec!(1))
+            }
+        }
+    }
+}
+
+/// Defaults, losses and the wait before recoveries come in
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultAssumptions {
+    /// Constant default rate: annual share of the performing balance defaulting, in percent
+    #[validate(custom = "validate_percentage")]
+    pub cdr: Decimal,
+
+    /// Share of defaulted principal lost, in percent
+    #[validate(custom = "validate_percentage")]
+    pub loss_severity: Decimal,
+
+    /// Months between a default and the recovery on it
+    #[serde(default = "default_recovery_lag_months")]
+    #[validate(range(max = 120))]
+    pub recovery_lag_months: u32,
+}
+
+fn default_recovery_lag_months() -> u32 {
+    12
+}
+
+fn validate_percentage(percentage: &Decimal) -> Result<(), ValidationError> {
+    if *percentage >= dec!(0) && *percentage <= dec!(100) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_percentage"))
+    }
+}
+
+fn validate_prepayment_speed(speed: &PrepaymentSpeed) -> Result<(), ValidationError> {
+    let valid = match speed {
+        PrepaymentSpeed::Cpr { rate } | PrepaymentSpeed::Smm { rate } => validate_percentage(rate).is_ok(),
+        PrepaymentSpeed::Psa { speed } => *speed >= dec!(0) && *speed <= dec!(5000),
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_prepayment_speed"))
+    }
+}
+
+/// Pool cash flows falling in one month
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolCashFlow {
+    /// Month counted from the earliest loan start, from one
+    pub month: u32,
+
+    /// Performing balance before this month's payments, including loans originated this month
+    pub beginning_balance: Decimal,
+
+    pub scheduled_principal: Decimal,
+    pub interest: Decimal,
+    pub prepayments: Decimal,
+
+    /// Performing principal that defaulted this month
+    pub defaults: Decimal,
+
+    /// Proceeds on principal that defaulted one recovery lag earlier
+    pub recoveries: Decimal,
+
+    /// Principal written off on defaults recovered this month
+    pub losses: Decimal,
+
+    pub ending_balance: Decimal,
+
+    /// Scheduled principal, interest, prepayments and recoveries paid to the pool
+    pub total_cash_flow: Decimal,
+}
+
+/// Aggregated cash flows for a pool of loans
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolProjection {
+    pub loan_count: usize,
+    pub original_balance: Decimal,
+
+    pub total_scheduled_principal: Decimal,
+    pub total_interest: Decimal,
+    pub total_prepayments: Decimal,
+    pub total_defaults: Decimal,
+    pub total_recoveries: Decimal,
+    pub total_losses: Decimal,
+    pub total_cash_flow: Decimal,
+
+    pub cash_flows: Vec<PoolCashFlow>,
+}
@@ -0,0 +1,60 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::ValidationError;
+
+/// Charge a lender levies when principal is repaid ahead of schedule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PrepaymentPenalty {
+    /// Percentage of the prepaid principal for each loan year, e.g. 5-4-3-2-1;
+    /// prepayments after the last listed year are free
+    #[serde(rename_all = "camelCase")]
+    StepDown { percentages: Vec<Decimal> },
+
+    /// Present value of the lender's lost interest spread over the treasury rate,
+    /// subject to a minimum percentage of the prepaid principal
+    #[serde(rename_all = "camelCase")]
+    YieldMaintenance {
+        treasury_rate: Decimal,
+        #[serde(default = "default_minimum_percentage")]
+        minimum_percentage: Decimal,
+    },
+
+    /// Cost of a treasury portfolio replicating the remaining payments on the
+    /// prepaid principal, plus fixed fees when the whole loan is defeased
+    #[serde(rename_all = "camelCase")]
+    Defeasance {
+        treasury_rate: Decimal,
+        #[serde(default)]
+        fees: Decimal,
+    },
+}
+
+fn default_minimum_percentage() -> Decimal {
+    dec!(1)
+}
+
+pub(crate) fn validate_prepayment_penalty(penalty: &PrepaymentPenalty) -> Result<(), ValidationError> {
+    let valid = match penalty {
+        PrepaymentPenalty::StepDown { percentages } => {
+            (1..=30).contains(&percentages.len())
+                && percentages.iter().all(|percentage| *percentage >= dec!(0) && *percentage <= dec!(10))
+        }
+        PrepaymentPenalty::YieldMaintenance { treasury_rate, minimum_percentage } => {
+            *treasury_rate >= dec!(0) && *treasury_rate <= dec!(1)
+                && *minimum_percentage >= dec!(0) && *minimum_percentage <= dec!(10)
+        }
+        PrepaymentPenalty::Defeasance { treasury_rate, fees } => {
+            *treasury_rate >= dec!(0) && *treasury_rate <= dec!(1)
+                && *fees >= dec!(0) && *fees <= dec!(1_000_000_000)
+        }
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_prepayment_penalty"))
+    }
+}
@@ -0,0 +1,171 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use super::input::MortgageInput;
+use super::repayment_type::RepaymentType;
+use super::summary::MortgageSummary;
+
+/// Existing loan and the refinance offers to compare against it
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_position"))]
+pub struct RefinanceInput {
+    /// Original terms of the loan being refinanced
+    #[validate]
+    pub existing_loan: MortgageInput,
+
+    /// Number of payments already made on the existing loan
+    #[serde(default)]
+    pub current_payment_number: Option<u32>,
+
+    /// Outstanding balance of the existing loan, if known instead of the payment number
+    #[serde(default)]
+    #[validate(custom = "validate_amount")]
+    pub current_balance: Option<Decimal>,
+
+    #[validate(length(min = 1, max = 10))]
+    #[validate]
+    pub offers: Vec<RefinanceOffer>,
+
+    /// Annual rate used to discount monthly savings
+    #[serde(default = "default_discount_rate")]
+    #[validate(custom = "validate_rate")]
+    pub discount_rate: Decimal,
+}
+
+/// Terms of a single refinance offer
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RefinanceOffer {
+    /// Label identifying the offer
+    pub name: String,
+
+    #[validate(custom = "validate_rate")]
+    pub annual_interest_rate: Decimal,
+
+    #[validate(range(min = 1, max = 50))]
+    pub term_years: u32,
+
+    #[serde(default = "default_repayment_type")]
+    pub repayment_type: RepaymentType,
+
+    /// Lender and third-party fees due at closing
+    #[serde(default)]
+    #[validate(custom = "validate_amount")]
+    pub closing_costs: Decimal,
+
+    /// Discount points as a percentage of the new loan amount
+    #[serde(default)]
+    #[validate(custom = "validate_points")]
+    pub points: Decimal,
+
+    /// Whether closing costs and points are rolled into the new loan
+    #[serde(default)]
+    pub finance_closing_costs: bool,
+}
+
+fn default_discount_rate() -> Decimal {
+    dec!(0.05)
+}
+
+fn default_repayment_type() -> RepaymentType {
+    RepaymentType::StandardPrincipalAndInterest
+}
+
+fn validate_amount(amount: &Decimal) -> Result<(), ValidationError> {
+    if *amount >= dec!(0) && *amount <= dec!(1_000_000_000) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_amount"))
+    }
+}
+
+fn validate_rate(rate: &Decimal) -> Result<(), ValidationError> {
+    if *rate >= dec!(0) && *rate <= dec!(1) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_rate"))
+    }
+}
+
+fn validate_points(points: &Decimal) -> Result<(), ValidationError> {
+    if *points >= dec!(0) && *points <= dec!(10) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_points"))
+    }
+}
+
+fn validate_position(input: &RefinanceInput) -> Result<(), ValidationError> {
+    if input.current_payment_number.is_none() && input.current_balance.is_none() {
+        Err(ValidationError::new("payment_number_or_balance_required"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Projection of the existing loan and each offer
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RefinanceAnalysis {
+    /// Payments already made on the existing loan
+    pub current_payment_number: u32,
+
+    /// Balance refinanced by every offer
+    pub current_balance: Decimal,
+
+    /// Regular payment on the existing loan
+    pub current_monthly_payment: Decimal,
+
+    /// Payments left on the existing loan
+    pub remaining_payments: u32,
+
+    /// Interest left to pay if the existing loan is kept
+    pub remaining_interest: Decimal,
+
+    /// Penalty for repaying the existing loan now
+    pub prepayment_penalty: Decimal,
+
+    pub offers: Vec<RefinanceOfferResult>,
+}
+
+/// Savings from taking one refinance offer
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RefinanceOfferResult {
+    pub name: String,
+
+    /// Amount borrowed under the new loan
+    pub new_loan_amount: Decimal,
+
+    /// Regular payment on the new loan
+    pub new_monthly_payment: Decimal,
+
+    /// Existing payment minus new payment
+    pub monthly_savings: Decimal,
+
+    /// Closing costs, points and the existing loan's prepayment penalty
+    pub total_closing_costs: Decimal,
+
+    /// First month in which cumulative savings cover the closing costs
+    pub break_even_month: Option<u32>,
+
+    /// Payment date of the break-even month
+    pub break_even_date: Option<NaiveDate>,
+
+    /// Interest paid over the life of the new loan
+    pub new_total_interest: Decimal,
+
+    /// Remaining interest on the existing loan minus interest on the new loan
+    pub lifetime_interest_savings: Decimal,
+
+    /// Present value of monthly savings net of upfront costs
+    pub npv_of_savings: Decimal,
+
+    /// Summary of the new loan
+    pub summary: MortgageSummary,
+}
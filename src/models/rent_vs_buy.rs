@@ -0,0 +1,228 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use super::mortgage_insurance::MortgageInsuranceInput;
+use super::repayment_type::RepaymentType;
+
+/// Purchase, financing and rental assumptions for a rent-versus-buy projection
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_down_payment"))]
+pub struct RentVsBuyInput {
+    /// Purchase price of the home
+    #[validate(custom = "validate_positive")]
+    pub home_price: Decimal,
+
+    /// Cash paid towards the purchase price; the rest is borrowed
+    #[validate(custom = "validate_non_negative")]
+    pub down_payment: Decimal,
+
+    #[validate(custom = "validate_rate")]
+    pub annual_interest_rate: Decimal,
+
+    #[validate(range(min = 1, max = 50))]
+    pub term_years: u32,
+
+    #[serde(default = "default_repayment_type")]
+    pub repayment_type: RepaymentType,
+
+    pub start_date: NaiveDate,
+
+    /// Mortgage insurance on the loan, priced from the home price
+    #[serde(default)]
+    #[validate]
+    pub mortgage_insurance: Option<MortgageInsuranceInput>,
+
+    /// Buyer closing costs paid in cash at purchase
+    #[serde(default)]
+    #[validate(custom = "validate_non_negative")]
+    pub closing_costs: Decimal,
+
+    /// Selling costs as a share of the sale price, deducted from home equity
+    #[serde(default = "default_selling_cost_rate")]
+    #[validate(custom = "validate_rate")]
+    pub selling_cost_rate: Decimal,
+
+    /// Annual property tax as a share of the home's value
+    #[serde(default)]
+    #[validate(custom = "validate_rate")]
+    pub property_tax_rate: Decimal,
+
+    /// Annual homeowners insurance premium in the first year
+    #[serde(default)]
+    #[validate(custom = "validate_non_negative")]
+    pub annual_insurance: Decimal,
+
+    /// Monthly HOA dues in the first year
+    #[serde(default)]
+    #[validate(custom = "validate_non_negative")]
+    pub monthly_hoa: Decimal,
+
+    /// Annual maintenance as a share of the home's value
+    #[serde(default = "default_maintenance_rate")]
+    #[validate(custom = "validate_rate")]
+    pub maintenance_rate: Decimal,
+
+    /// Annual growth of the home's value
+    #[serde(default = "default_growth_rate")]
+    #[validate(custom = "validate_growth_rate")]
+    pub home_appreciation_rate: Decimal,
+
+    /// Annual growth of insurance premiums and HOA dues
+    #[serde(default = "default_growth_rate")]
+    #[validate(custom = "validate_growth_rate")]
+    pub cost_inflation_rate: Decimal,
+
+    /// Marginal income tax rate applied to itemized deductions
+    #[serde(default)]
+    #[validate(custom = "validate_rate")]
+    pub marginal_tax_rate: Decimal,
+
+    /// Standard deduction forgone by itemizing mortgage interest and property tax
+    #[serde(default)]
+    #[validate(custom = "validate_non_negative")]
+    pub standard_deduction: Decimal,
+
+    /// Monthly rent in the first year
+    #[validate(custom = "validate_non_negative")]
+    pub monthly_rent: Decimal,
+
+    /// Annual growth of rent
+    #[serde(default = "default_growth_rate")]
+    #[validate(custom = "validate_growth_rate")]
+    pub rent_growth_rate: Decimal,
+
+    /// Annual renters insurance premium
+    #[serde(default)]
+    #[validate(custom = "validate_non_negative")]
+    pub annual_renters_insurance: Decimal,
+
+    /// Annual return on invested savings
+    #[serde(default = "default_investment_return_rate")]
+    #[validate(custom = "validate_growth_rate")]
+    pub investment_return_rate: Decimal,
+
+    /// Number of years to project
+    #[validate(range(min = 1, max = 50))]
+    pub horizon_years: u32,
+}
+
+fn default_repayment_type() -> RepaymentType {
+    RepaymentType::StandardPrincipalAndInterest
+}
+
+fn default_selling_cost_rate() -> Decimal {
+    dec!(0.06)
+}
+
+fn default_maintenance_rate() -> Decimal {
+    dec!(0.01)
+}
+
+fn default_growth_rate() -> Decimal {
+    dec!(0.03)
+}
+
+fn default_investment_return_rate() -> Decimal {
+    dec!(0.05)
+}
+
+fn validate_positive(amount: &Decimal) -> Result<(), ValidationError> {
+    if *amount > dec!(0) && *amount <= dec!(1_000_000_000) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_amount"))
+    }
+}
+
+fn validate_non_negative(amount: &Decimal) -> Result<(), ValidationError> {
+    if *amount >= dec!(0) && *amount <= dec!(1_000_000_000) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_amount"))
+    }
+}
+
+fn validate_rate(rate: &Decimal) -> Result<(), ValidationError> {
+    if *rate >= dec!(0) && *rate <= dec!(1) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_rate"))
+    }
+}
+
+fn validate_growth_rate(rate: &Decimal) -> Result<(), ValidationError> {
+    if *rate >= dec!(-0.5) && *rate <= dec!(0.5) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_growth_rate"))
+    }
+}
+
+fn validate_down_payment(input: &RentVsBuyInput) -> Result<(), ValidationError> {
+    if input.down_payment < input.home_price {
+        Ok(())
+    } else {
+        Err(ValidationError::new("down_payment_exceeds_price"))
+    }
+}
+
+/// Owner and renter positions at the end of one projection year
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RentVsBuyYear {
+    pub year: u32,
+
+    /// Projected market value of the home
+    pub home_value: Decimal,
+
+    /// Mortgage principal still owed
+    pub loan_balance: Decimal,
+
+    /// Owner outlays for the year net of tax savings
+    pub ownership_cost: Decimal,
+
+    /// Renter outlays for the year
+    pub rental_cost: Decimal,
+
+    /// Income tax saved by itemizing interest and property tax
+    pub tax_savings: Decimal,
+
+    /// Home value net of selling costs, minus the loan balance
+    pub owner_equity: Decimal,
+
+    /// Savings the owner invested in years renting was dearer
+    pub owner_investments: Decimal,
+
+    /// Equity plus investments if the home were sold at year end
+    pub owner_net_worth: Decimal,
+
+    /// Invested upfront cash plus savings from years owning was dearer
+    pub renter_net_worth: Decimal,
+
+    /// Owner net worth minus renter net worth
+    pub buy_advantage: Decimal,
+}
+
+/// Year-by-year comparison of buying against renting and investing the difference
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RentVsBuyAnalysis {
+    /// Amount borrowed to buy the home
+    pub loan_amount: Decimal,
+
+    /// Down payment, closing costs and upfront mortgage insurance
+    pub upfront_cash: Decimal,
+
+    /// Regular principal and interest payment on the loan
+    pub monthly_payment: Decimal,
+
+    /// Year from which buying leaves the higher net worth through the horizon
+    pub crossover_year: Option<u32>,
+
+    pub years: Vec<RentVsBuyYear>,
+}
@@ -1,39 +1,60 @@
-use serde::{Deserialize, Serialize};
+use serde::{Serialize, Serializer};
+use utoipa::openapi::{ObjectBuilder, RefOr, Schema, SchemaType};
+use utoipa::ToSchema;
 
 /// Available types of mortgage repayment
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RepaymentType {
     /// Standard principal and interest payments
     StandardPrincipalAndInterest,
-    
+
     /// Interest-only payments with principal due at end
     InterestOnly,
-    
+
     /// Accelerated biweekly payments
     AcceleratedBiweekly,
-    
+
     /// Regular payments with balloon payment at end
     BalloonPayment,
-    
+
     /// Variable rate mortgage
     FloatingRate,
+
+    /// Product priced by a calculator registered with `register_calculator`
+    Custom(&'static CustomRepaymentType),
+}
+
+/// Terms of a custom product, declared once as a `static` or `const` alongside its calculator
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct CustomRepaymentType {
+    /// Code the product is serialized as
+    pub code: &'static str,
+
+    /// Display name listed by `/repayment-types`
+    pub name: &'static str,
+
+    /// Scheduled payments per year, used to annualize escrow, insurance and analytics
+    pub payments_per_year: u32,
+
+    /// Whether inputs must carry a balloon payment percentage
+    pub requires_balloon_percentage: bool,
+}
+
+impl CustomRepaymentType {
+    /// Monthly product without a balloon
+    pub const fn new(code: &'static str, name: &'static str) -> Self {
+        Self { code, name, payments_per_year: 12, requires_balloon_percentage: false }
+    }
 }
 
 impl std::fmt::Display for RepaymentType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::StandardPrincipalAndInterest => write!(f, "Standard Principal and Interest"),
-            Self::InterestOnly => write!(f, "Interest Only"),
-            Self::AcceleratedBiweekly => write!(f, "Accelerated Biweekly"),
-            Self::BalloonPayment => write!(f, "Balloon Payment"),
-            Self::FloatingRate => write!(f, "Floating Rate"),
-        }
+        write!(f, "{}", self.name())
     }
 }
 
 impl RepaymentType {
-    /// Returns all available repayment types
+    /// Returns all built-in repayment types; `calculators::repayment_types()` adds registered products
     pub fn all() -> Vec<RepaymentType> {
         vec![
             RepaymentType::StandardPrincipalAndInterest,
@@ -44,9 +65,73 @@ impl RepaymentType {
         ]
     }
 
+    /// Code the repayment type is serialized as
+    pub fn code(&self) -> &'static str {
+        match self {
+            RepaymentType::StandardPrincipalAndInterest => "standardPrincipalAndInterest",
+            RepaymentType::InterestOnly => "interestOnly",
+            RepaymentType::AcceleratedBiweekly => "acceleratedBiweekly",
+            RepaymentType::BalloonPayment => "balloonPayment",
+            RepaymentType::FloatingRate => "floatingRate",
+            RepaymentType::Custom(custom) => custom.code,
+        }
+    }
+
+    /// Display name listed by `/repayment-types`
+    pub fn name(&self) -> &'static str {
+        match self {
+            RepaymentType::StandardPrincipalAndInterest => "Standard Principal and Interest",
+            RepaymentType::InterestOnly => "Interest Only",
+            RepaymentType::AcceleratedBiweekly => "Accelerated Biweekly",
+            RepaymentType::BalloonPayment => "Balloon Payment",
+            RepaymentType::FloatingRate => "Floating Rate",
+            RepaymentType::Custom(custom) => custom.name,
+        }
+    }
+
+    /// Looks up a built-in repayment type by its code; custom codes resolve through the calculator registry
+    pub fn builtin_from_code(code: &str) -> Option<RepaymentType> {
+        Self::all().into_iter().find(|repayment_type| repayment_type.code() == code)
+    }
+
     /// Returns whether this repayment type requires a balloon payment percentage
     pub fn requires_balloon_percentage(&self) -> bool {
-        matches!(self, RepaymentType::BalloonPayment)
+        match self {
+            RepaymentType::Custom(custom) => custom.requires_balloon_percentage,
+            _ => matches!(self, RepaymentType::BalloonPayment),
+        }
+    }
+
+    /// Returns the number of scheduled payments per year
+    pub fn payments_per_year(&self) -> u32 {
+        match self {
+            RepaymentType::AcceleratedBiweekly => 26,
+            RepaymentType::Custom(custom) => custom.payments_per_year,
+            _ => 12,
+        }
+    }
+}
+
+impl Serialize for RepaymentType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'s> ToSchema<'s> for RepaymentType {
+    fn schema() -> (&'s str, RefOr<Schema>) {
+        let builtin: Vec<_> = RepaymentType::all().iter().map(RepaymentType::code).collect();
+        (
+            "RepaymentType",
+            ObjectBuilder::new()
+                .schema_type(SchemaType::String)
+                .description(Some(format!(
+                    "Available types of mortgage repayment: one of {} or the code of a registered custom product",
+                    builtin.join(", ")
+                )))
+                .example(Some(builtin[0].into()))
+                .into(),
+        )
     }
 }
 
@@ -72,4 +157,23 @@ mod tests {
         assert!(RepaymentType::BalloonPayment.requires_balloon_percentage());
         assert!(!RepaymentType::StandardPrincipalAndInterest.requires_balloon_percentage());
     }
+
+    #[test]
+    fn test_codes_and_custom_terms() {
+        for repayment_type in RepaymentType::all() {
+            assert_eq!(serde_json::to_string(&repayment_type).unwrap(), format!("\"{}\"", repayment_type.code()));
+            assert_eq!(RepaymentType::builtin_from_code(repayment_type.code()), Some(repayment_type));
+        }
+        assert_eq!(RepaymentType::builtin_from_code("graduated"), None);
+
+        static GRADUATED: CustomRepaymentType = CustomRepaymentType {
+            payments_per_year: 26,
+            ..CustomRepaymentType::new("graduated", "Graduated Payment")
+        };
+        let graduated = RepaymentType::Custom(&GRADUATED);
+        assert_eq!(graduated.code(), "graduated");
+        assert_eq!(graduated.to_string(), "Graduated Payment");
+        assert_eq!(graduated.payments_per_year(), 26);
+        assert!(!graduated.requires_balloon_percentage());
+    }
 }
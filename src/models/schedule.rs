@@ -1,9 +1,13 @@
 use rust_decimal::Decimal;
 use chrono::NaiveDate;
 use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::escrow::EscrowAnalysis;
+use super::modification::LoanModification;
 
 /// Represents a single payment in the mortgage schedule
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PaymentScheduleEntry {
     /// Date when the payment is due
@@ -26,10 +30,28 @@ pub struct PaymentScheduleEntry {
     
     /// Current interest rate (for variable rate mortgages)
     pub current_rate: Option<Decimal>,
+
+    /// Escrow deposit collected with this payment
+    pub escrow_deposit: Option<Decimal>,
+
+    /// Escrow account balance after this payment and any disbursements
+    pub escrow_balance: Option<Decimal>,
+
+    /// Mortgage insurance premium collected with this payment
+    pub mortgage_insurance: Option<Decimal>,
+
+    /// Penalty charged on the extra principal in this payment
+    pub prepayment_penalty: Option<Decimal>,
+
+    /// Non-interest-bearing balance due at maturity, after this payment
+    pub deferred_balance: Option<Decimal>,
+
+    /// Modification events taking effect at this payment
+    pub modifications: Vec<LoanModification>,
 }
 
 /// Complete mortgage amortization schedule
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MortgageSchedule {
     /// Regular payment amount
@@ -43,6 +65,24 @@ pub struct MortgageSchedule {
     
     /// Complete schedule of all payments
     pub schedule: Vec<PaymentScheduleEntry>,
+
+    /// Escrow deposit collected at closing
+    pub initial_escrow_deposit: Option<Decimal>,
+
+    /// Annual escrow account analyses
+    pub escrow_analysis: Vec<EscrowAnalysis>,
+
+    /// Monthly mortgage insurance premium in the first year
+    pub monthly_mortgage_insurance: Option<Decimal>,
+
+    /// Upfront mortgage insurance premium (FHA)
+    pub upfront_mortgage_insurance: Option<Decimal>,
+
+    /// Date of the first payment without mortgage insurance
+    pub mortgage_insurance_end_date: Option<NaiveDate>,
+
+    /// Largest non-interest-bearing balance deferred to maturity by modifications
+    pub deferred_balance: Option<Decimal>,
 }
 
 impl MortgageSchedule {
@@ -57,6 +97,12 @@ impl MortgageSchedule {
             total_payments,
             total_interest,
             schedule: Vec::new(),
+            initial_escrow_deposit: None,
+            escrow_analysis: Vec::new(),
+            monthly_mortgage_insurance: None,
+            upfront_mortgage_insurance: None,
+            mortgage_insurance_end_date: None,
+            deferred_balance: None,
         }
     }
 
@@ -69,6 +115,36 @@ impl MortgageSchedule {
     pub fn payment_count(&self) -> usize {
         self.schedule.len()
     }
+
+    /// Returns the date of the final payment
+    pub fn payoff_date(&self) -> Option<NaiveDate> {
+        self.schedule.last().map(|entry| entry.payment_date)
+    }
+
+    /// Returns the last installment due on or before `date`
+    pub fn last_payment_on_or_before(&self, date: NaiveDate) -> Option<&PaymentScheduleEntry> {
+        self.schedule.iter()
+            .take_while(|entry| entry.payment_date <= date)
+            .last()
+    }
+
+    /// Returns the monthly escrow deposit for the first escrow year
+    pub fn monthly_escrow_payment(&self) -> Option<Decimal> {
+        self.escrow_analysis.first().map(|analysis| analysis.monthly_deposit)
+    }
+
+    /// Returns the total mortgage insurance collected over the schedule
+    pub fn total_mortgage_insurance(&self) -> Option<Decimal> {
+        self.monthly_mortgage_insurance?;
+        Some(self.schedule.iter().filter_map(|entry| entry.mortgage_insurance).sum())
+    }
+
+    /// Returns the total prepayment penalty charged on extra principal
+    pub fn total_prepayment_penalty(&self) -> Option<Decimal> {
+        let mut penalties = self.schedule.iter().filter_map(|entry| entry.prepayment_penalty).peekable();
+        penalties.peek()?;
+        Some(penalties.sum())
+    }
 }
 
 #[cfg(test)]
@@ -106,6 +182,12 @@ mod tests {
             interest_component: dec!(200),
             remaining_principal: dec!(299200),
             current_rate: None,
+            escrow_deposit: None,
+            escrow_balance: None,
+            mortgage_insurance: None,
+            prepayment_penalty: None,
+            deferred_balance: None,
+            modifications: Vec::new(),
         };
 
         schedule.add_payment(payment);
@@ -0,0 +1,91 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
+
+/// Rows returned per page when only `page` is given
+pub const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Query options selecting part of a schedule, or rolling it up by year
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query, rename_all = "camelCase")]
+#[validate(schema(function = "validate_date_range"))]
+pub struct ScheduleQuery {
+    /// Page to return, from 1; pages hold `pageSize` rows
+    #[validate(range(min = 1))]
+    pub page: Option<u32>,
+
+    /// Rows per page, 100 when only `page` is given
+    #[validate(range(min = 1, max = 1000))]
+    pub page_size: Option<u32>,
+
+    /// Only payments due on or after this date
+    pub from: Option<NaiveDate>,
+
+    /// Only payments due on or before this date
+    pub to: Option<NaiveDate>,
+
+    /// Return yearly totals instead of individual payments
+    pub aggregate: Option<ScheduleAggregation>,
+}
+
+/// Years payments are grouped into
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ScheduleAggregation {
+    /// January to December of the payment date
+    CalendarYear,
+
+    /// Each run of a year's worth of payments from the first
+    LoanYear,
+}
+
+/// Totals of the payments falling in one year
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleYear {
+    /// Calendar year, or loan year counted from 1
+    pub year: i32,
+
+    pub first_payment_date: NaiveDate,
+
+    pub last_payment_date: NaiveDate,
+
+    pub number_of_payments: u32,
+
+    /// Principal and interest paid in the year
+    pub total_payment: Decimal,
+
+    pub total_principal: Decimal,
+
+    pub total_interest: Decimal,
+
+    /// Remaining principal after the year's last payment
+    pub ending_balance: Decimal,
+}
+
+fn validate_date_range(query: &ScheduleQuery) -> Result<(), ValidationError> {
+    match (query.from, query.to) {
+        (Some(from), Some(to)) if from > to => Err(ValidationError::new("invalid_date_range")),
+        _ => Ok(()),
+    }
+}
+
+impl ScheduleQuery {
+    /// Whether a payment due on `date` is inside the requested range
+    pub fn includes(&self, date: NaiveDate) -> bool {
+        self.from.filter(|from| date < *from).is_none() && self.to.filter(|to| date > *to).is_none()
+    }
+
+    /// Rows to skip and keep, when a page was requested
+    pub fn page_bounds(&self) -> Option<(usize, usize)> {
+        if self.page.is_none() && self.page_size.is_none() {
+            return None;
+        }
+        let page_size = self.page_size.unwrap_or(DEFAULT_PAGE_SIZE) as usize;
+        let page = self.page.unwrap_or(1).max(1) as usize;
+        Some(((page - 1).saturating_mul(page_size), page_size))
+    }
+}
@@ -0,0 +1,90 @@
+use rust_decimal::Decimal;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use super::input::MortgageInput;
+use super::summary::MortgageSummary;
+
+/// Schedule outcome the solver should hit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SolveTarget {
+    /// Regular monthly outlay, including any extra principal
+    MonthlyPayment { value: Decimal },
+
+    /// Total interest paid over the life of the loan
+    TotalInterest { value: Decimal },
+
+    /// Date of the final payment; the solver lands on or before it
+    PayoffDate { date: NaiveDate },
+
+    /// Lump sum due with the final payment of a balloon loan
+    BalloonAmount { value: Decimal },
+}
+
+impl std::fmt::Display for SolveTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MonthlyPayment { value } => write!(f, "monthly payment of {}", value),
+            Self::TotalInterest { value } => write!(f, "total interest of {}", value),
+            Self::PayoffDate { date } => write!(f, "payoff by {}", date),
+            Self::BalloonAmount { value } => write!(f, "balloon amount of {}", value),
+        }
+    }
+}
+
+/// Input the solver is allowed to change
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SolveVariable {
+    Principal,
+    AnnualInterestRate,
+    TermYears,
+    ExtraPayment,
+}
+
+impl std::fmt::Display for SolveVariable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Principal => write!(f, "principal"),
+            Self::AnnualInterestRate => write!(f, "annual interest rate"),
+            Self::TermYears => write!(f, "term"),
+            Self::ExtraPayment => write!(f, "extra payment"),
+        }
+    }
+}
+
+/// Goal-seek request: a base loan, a target and the variable to solve for
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SolveRequest {
+    #[validate]
+    pub input: MortgageInput,
+    pub target: SolveTarget,
+    pub variable: SolveVariable,
+}
+
+/// Solved variable and the loan it produces
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SolveResult {
+    /// Variable that was solved for
+    pub variable: SolveVariable,
+
+    /// Value of the variable that best meets the target
+    pub solved_value: Decimal,
+
+    /// Outcome actually produced by the solved loan
+    pub achieved: SolveTarget,
+
+    /// Number of calculator evaluations performed
+    pub iterations: u32,
+
+    /// Base input with the solved value applied
+    pub input: MortgageInput,
+
+    /// Summary of the solved loan
+    pub summary: MortgageSummary,
+}
@@ -1,9 +1,12 @@
 use rust_decimal::Decimal;
+use chrono::NaiveDate;
 use serde::Serialize;
+use utoipa::ToSchema;
 use super::repayment_type::RepaymentType;
+use super::schedule::MortgageSchedule;
 
 /// Summary of mortgage calculation results
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MortgageSummary {
     /// Type of repayment used
@@ -32,6 +35,33 @@ pub struct MortgageSummary {
     
     /// Interest rate range for variable rate mortgages
     pub rate_range: Option<(Decimal, Decimal)>,
+
+    /// Monthly escrow deposit for taxes, insurance and HOA (first escrow year)
+    pub monthly_escrow_payment: Option<Decimal>,
+
+    /// Escrow deposit collected at closing
+    pub initial_escrow_deposit: Option<Decimal>,
+
+    /// Monthly mortgage insurance premium in the first year
+    pub monthly_mortgage_insurance: Option<Decimal>,
+
+    /// Upfront mortgage insurance premium (FHA)
+    pub upfront_mortgage_insurance: Option<Decimal>,
+
+    /// Total mortgage insurance paid over the loan term
+    pub total_mortgage_insurance: Option<Decimal>,
+
+    /// Date mortgage insurance drops off the payment
+    pub mortgage_insurance_end_date: Option<NaiveDate>,
+
+    /// Monthly payment including escrow and mortgage insurance
+    pub total_monthly_payment: Decimal,
+
+    /// Prepayment penalties charged on extra principal over the loan term
+    pub total_prepayment_penalty: Option<Decimal>,
+
+    /// Largest non-interest-bearing balance deferred to maturity by modifications
+    pub deferred_balance: Option<Decimal>,
 }
 
 impl MortgageSummary {
@@ -55,6 +85,15 @@ impl MortgageSummary {
             number_of_payments,
             balloon_payment: None,
             rate_range: None,
+            monthly_escrow_payment: None,
+            initial_escrow_deposit: None,
+            monthly_mortgage_insurance: None,
+            upfront_mortgage_insurance: None,
+            total_mortgage_insurance: None,
+            mortgage_insurance_end_date: None,
+            total_monthly_payment: monthly_payment,
+            total_prepayment_penalty: None,
+            deferred_balance: None,
         }
     }
 
@@ -69,6 +108,40 @@ impl MortgageSummary {
         self.rate_range = Some((min_rate, max_rate));
         self
     }
+
+    /// Adds escrow information from a calculated schedule
+    pub fn with_escrow_from(mut self, schedule: &MortgageSchedule) -> Self {
+        if let Some(monthly_escrow) = schedule.monthly_escrow_payment() {
+            self.monthly_escrow_payment = Some(monthly_escrow);
+            self.initial_escrow_deposit = schedule.initial_escrow_deposit;
+            self.total_monthly_payment += monthly_escrow;
+        }
+        self
+    }
+
+    /// Adds mortgage insurance information from a calculated schedule
+    pub fn with_mortgage_insurance_from(mut self, schedule: &MortgageSchedule) -> Self {
+        if let Some(monthly_premium) = schedule.monthly_mortgage_insurance {
+            self.monthly_mortgage_insurance = Some(monthly_premium);
+            self.upfront_mortgage_insurance = schedule.upfront_mortgage_insurance;
+            self.total_mortgage_insurance = schedule.total_mortgage_insurance();
+            self.mortgage_insurance_end_date = schedule.mortgage_insurance_end_date;
+            self.total_monthly_payment += monthly_premium;
+        }
+        self
+    }
+
+    /// Adds prepayment penalties charged in a calculated schedule
+    pub fn with_prepayment_penalty_from(mut self, schedule: &MortgageSchedule) -> Self {
+        self.total_prepayment_penalty = schedule.total_prepayment_penalty();
+        self
+    }
+
+    /// Adds the balance deferred by modifications in a calculated schedule
+    pub fn with_modifications_from(mut self, schedule: &MortgageSchedule) -> Self {
+        self.deferred_balance = schedule.deferred_balance;
+        self
+    }
 }
 
 #[cfg(test)]
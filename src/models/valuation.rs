@@ -0,0 +1,106 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use super::input::MortgageInput;
+
+/// Loan whose payments are discounted and the curve to discount them on
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ValuationRequest {
+    #[validate]
+    pub input: MortgageInput,
+
+    #[validate(custom = "validate_discount_curve")]
+    pub curve: DiscountCurve,
+}
+
+/// Rates the payment stream is discounted at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DiscountCurve {
+    /// One annual rate, compounded once per payment period
+    Flat { rate: Decimal },
+
+    /// Par yields by maturity, bootstrapped into a zero curve
+    #[serde(rename_all = "camelCase")]
+    ParRates {
+        points: Vec<ParRatePoint>,
+
+        /// Coupons per year on the par instruments
+        #[serde(default = "default_coupon_frequency")]
+        coupon_frequency: u32,
+    },
+}
+
+/// Par yield of an instrument maturing after `term_years`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ParRatePoint {
+    pub term_years: Decimal,
+    pub rate: Decimal,
+}
+
+/// Treasury-style semiannual coupons
+fn default_coupon_frequency() -> u32 {
+    2
+}
+
+fn validate_rate(rate: Decimal) -> bool {
+    rate >= dec!(-0.05) && rate <= dec!(1)
+}
+
+fn validate_discount_curve(curve: &DiscountCurve) -> Result<(), ValidationError> {
+    let valid = match curve {
+        DiscountCurve::Flat { rate } => validate_rate(*rate),
+        DiscountCurve::ParRates { points, coupon_frequency } => {
+            (1..=50).contains(&points.len())
+                && [1, 2, 4, 12].contains(coupon_frequency)
+                && points.iter().all(|point| {
+                    point.term_years > dec!(0) && point.term_years <= dec!(50) && validate_rate(point.rate)
+                })
+                && points.windows(2).all(|pair| pair[0].term_years < pair[1].term_years)
+        }
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new("invalid_discount_curve"))
+    }
+}
+
+/// Bootstrapped point on the zero curve
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ZeroRatePoint {
+    pub term_years: Decimal,
+    pub discount_factor: Decimal,
+
+    /// Continuously compounded zero rate
+    pub zero_rate: Decimal,
+}
+
+/// Present value of a loan's principal and interest payments
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PresentValue {
+    pub principal: Decimal,
+    pub total_principal: Decimal,
+    pub total_interest: Decimal,
+
+    pub pv_principal: Decimal,
+    pub pv_interest: Decimal,
+    pub total_pv: Decimal,
+
+    /// Total present value minus the principal lent; positive when the loan is worth more than par
+    pub npv: Decimal,
+
+    /// Total present value as a percentage of the principal
+    pub price: Decimal,
+
+    /// Zero curve bootstrapped from par rates; empty for a flat rate
+    pub zero_curve: Vec<ZeroRatePoint>,
+}
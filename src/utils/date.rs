@@ -46,6 +46,28 @@ impl DateUtils {
         }
     }
 
+    /// Returns the number of whole years elapsed between two dates
+    pub fn years_between(start_date: NaiveDate, end_date: NaiveDate) -> u32 {
+        if end_date <= start_date {
+            return 0;
+        }
+        let mut years = end_date.year() - start_date.year();
+        if (end_date.month(), end_date.day()) < (start_date.month(), start_date.day()) {
+            years -= 1;
+        }
+        years.max(0) as u32
+    }
+
+    /// Counts days between two dates on a 30/360 basis (US bond basis)
+    pub fn days_30_360(start_date: NaiveDate, end_date: NaiveDate) -> i64 {
+        let start_day = start_date.day().min(30);
+        let end_day = if end_date.day() == 31 && start_day == 30 { 30 } else { end_date.day() };
+
+        i64::from(end_date.year() - start_date.year()) * 360
+            + (i64::from(end_date.month()) - i64::from(start_date.month())) * 30
+            + (i64::from(end_day) - i64::from(start_day))
+    }
+
     /// Validates if a date is a valid payment date
     pub fn is_valid_payment_date(date: NaiveDate) -> bool {
         let current_date = chrono::Local::now().date_naive();
@@ -33,6 +33,11 @@ impl DecimalUtils {
         result
     }
 
+    /// Raises a decimal to a non-negative integer power, or `None` on overflow
+    pub fn checked_power(base: Decimal, exp: i64) -> Option<Decimal> {
+        (0..exp).try_fold(dec!(1), |result, _| result.checked_mul(base))
+    }
+
     /// Checks if a decimal is within an inclusive range
     pub fn is_within_range(value: Decimal, range: RangeInclusive<Decimal>) -> bool {
         range.contains(&value)
@@ -40,8 +45,23 @@ impl DecimalUtils {
 
     /// Calculates the monthly payment factor for a given rate and term
     pub fn monthly_payment_factor(monthly_rate: Decimal, num_payments: u32) -> Decimal {
+        if monthly_rate.is_zero() {
+            return dec!(1) / Decimal::from(num_payments);
+        }
         let base = dec!(1) + monthly_rate;
         let factor = Self::power(base, num_payments as i64);
         (monthly_rate * factor) / (factor - dec!(1))
     }
+
+    /// Like [`monthly_payment_factor`](Self::monthly_payment_factor), or `None` where it overflows
+    pub fn checked_payment_factor(periodic_rate: Decimal, num_payments: u32) -> Option<Decimal> {
+        if num_payments == 0 {
+            return None;
+        }
+        if periodic_rate.is_zero() {
+            return Some(dec!(1) / Decimal::from(num_payments));
+        }
+        let factor = Self::checked_power(dec!(1) + periodic_rate, i64::from(num_payments))?;
+        periodic_rate.checked_mul(factor)?.checked_div(factor - dec!(1))
+    }
 }
@@ -0,0 +1,374 @@
+//! Randomized properties of the schedule and analysis calculators
+//!
+//! Each property draws loans from a seeded generator so failures reproduce;
+//! the failing input is printed with the assertion.
+
+use chrono::{Datelike, NaiveDate};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use validator::Validate;
+use mortgagekit_rs::{
+    calculators::{calculate_schedule, calculate_summary, schedule_entries},
+    models::{
+        DefaultAssumptions,
+        EscrowInput,
+        ForbearanceInterest,
+        LoanModification,
+        PrepaymentPenalty,
+        PrepaymentSpeed,
+        RefinanceOffer,
+    },
+    AffordabilityCalculator,
+    AffordabilityInput,
+    MortgageInput,
+    MortgageInsuranceInput,
+    MortgageInsuranceType,
+    PoolCalculator,
+    PoolRequest,
+    RefinanceCalculator,
+    RefinanceInput,
+    RentVsBuyCalculator,
+    RentVsBuyInput,
+    RepaymentType,
+};
+
+const CASES: usize = 400;
+
+fn repayment_type(rng: &mut StdRng) -> RepaymentType {
+    let types = RepaymentType::all();
+    types[rng.gen_range(0..types.len())]
+}
+
+/// Any decimal from zero up to `10^max_digits`, with up to 10 fractional digits
+fn amount(rng: &mut StdRng, max_digits: u32) -> Decimal {
+    let digits = rng.gen_range(0..=max_digits.min(18));
+    let mantissa = rng.gen_range(0..=10_i64.pow(digits));
+    let scale = rng.gen_range(0..=10);
+    Decimal::new(mantissa, scale) * Decimal::from(10_i64.pow(scale.min(digits)))
+}
+
+/// Decimal anywhere in the representable range, extremes included
+fn any_decimal(rng: &mut StdRng) -> Decimal {
+    let value = match rng.gen_range(0..10) {
+        0 => Decimal::MAX,
+        1 => Decimal::ZERO,
+        2 => Decimal::new(1, 28),
+        _ => amount(rng, 18) * Decimal::from(10_i64.pow(rng.gen_range(0..=10))),
+    };
+    if rng.gen_bool(0.2) { -value } else { value }
+}
+
+fn any_date(rng: &mut StdRng) -> NaiveDate {
+    match rng.gen_range(0..5) {
+        0 => NaiveDate::MAX,
+        1 => NaiveDate::MIN,
+        _ => NaiveDate::from_ymd_opt(rng.gen_range(-5000..=250_000), rng.gen_range(1..=12), 1).unwrap(),
+    }
+}
+
+fn escrow(rng: &mut StdRng, growth: impl Fn(&mut StdRng) -> Decimal, amount: impl Fn(&mut StdRng) -> Decimal) -> EscrowInput {
+    EscrowInput {
+        annual_property_tax: amount(rng),
+        annual_insurance: amount(rng),
+        annual_hoa: amount(rng),
+        property_tax_growth_rate: growth(rng),
+        insurance_growth_rate: growth(rng),
+        hoa_growth_rate: growth(rng),
+        property_tax_disbursement_months: vec![rng.gen_range(1..=12)],
+        insurance_disbursement_months: Vec::new(),
+        hoa_disbursement_months: Vec::new(),
+        cushion_months: rng.gen_range(0..=2),
+    }
+}
+
+/// Fraction from zero to one, both ends included
+fn rate(rng: &mut StdRng) -> Decimal {
+    match rng.gen_range(0..10) {
+        0 => Decimal::ZERO,
+        1 => Decimal::ONE,
+        _ => amount(rng, 0).min(Decimal::ONE),
+    }
+}
+
+/// Growth or return rate from -50% to 50% a year
+fn growth_rate(rng: &mut StdRng) -> Decimal {
+    Decimal::new(rng.gen_range(-500..=500), 3)
+}
+
+/// Amount from zero to the validated maximum of one billion
+fn bounded_amount(rng: &mut StdRng) -> Decimal {
+    amount(rng, 9).min(dec!(1_000_000_000))
+}
+
+fn mortgage_insurance(rng: &mut StdRng) -> MortgageInsuranceInput {
+    MortgageInsuranceInput {
+        insurance_type: if rng.gen_bool(0.5) { MortgageInsuranceType::PrivateMortgageInsurance } else { MortgageInsuranceType::Fha },
+        credit_score: rng.gen_bool(0.7).then(|| rng.gen_range(300..=850)),
+        annual_rate: rng.gen_bool(0.3).then(|| Decimal::new(rng.gen_range(0..=500), 4)),
+        borrower_requested_cancellation: rng.gen_bool(0.2),
+    }
+}
+
+fn modification(rng: &mut StdRng, start_payment: u32, scale: Decimal) -> LoanModification {
+    match rng.gen_range(0..4) {
+        0 => LoanModification::Forbearance {
+            start_payment,
+            months: rng.gen_range(1..=60),
+            interest: if rng.gen_bool(0.5) { ForbearanceInterest::Capitalized } else { ForbearanceInterest::Deferred },
+        },
+        1 => LoanModification::TermExtension { start_payment, additional_months: rng.gen_range(1..=480) },
+        2 => LoanModification::RateReduction { start_payment, annual_interest_rate: amount(rng, 0).min(dec!(1)) },
+        _ => LoanModification::PrincipalForbearance { start_payment, amount: scale * Decimal::new(rng.gen_range(1..100), 2) },
+    }
+}
+
+/// Loan with every field drawn from the whole of its type
+fn any_loan(rng: &mut StdRng) -> MortgageInput {
+    let principal = any_decimal(rng);
+    MortgageInput {
+        principal,
+        annual_interest_rate: any_decimal(rng),
+        term_years: if rng.gen_bool(0.1) { u32::MAX } else { rng.gen_range(0..=120) },
+        repayment_type: repayment_type(rng),
+        start_date: any_date(rng),
+        balloon_payment_percentage: any_decimal(rng),
+        extra_payment: any_decimal(rng),
+        escrow: rng.gen_bool(0.3).then(|| escrow(rng, any_decimal, any_decimal)),
+        property_value: rng.gen_bool(0.2).then(|| any_decimal(rng)),
+        mortgage_insurance: rng.gen_bool(0.2).then(|| MortgageInsuranceInput {
+            insurance_type: MortgageInsuranceType::PrivateMortgageInsurance,
+            credit_score: Some(rng.gen()),
+            annual_rate: Some(any_decimal(rng)),
+            borrower_requested_cancellation: rng.gen(),
+        }),
+        prepayment_penalty: rng.gen_bool(0.2).then(|| PrepaymentPenalty::Defeasance {
+            treasury_rate: any_decimal(rng),
+            fees: Decimal::ZERO,
+        }),
+        modifications: (0..rng.gen_range(0..3))
+            .map(|_| {
+                let start_payment = rng.gen_range(0..=700);
+                modification(rng, start_payment, principal.abs().min(dec!(1_000_000_000)))
+            })
+            .collect(),
+    }
+}
+
+/// Loan drawn across the per-field validated ranges, extremes included
+fn validated_ranges_loan(rng: &mut StdRng) -> MortgageInput {
+    let principal = amount(rng, 9).clamp(dec!(0.01), dec!(1_000_000_000));
+    // Mostly above 80% loan-to-value, where insurance premiums are charged
+    let property_value = rng.gen_bool(0.3)
+        .then(|| (principal * Decimal::new(rng.gen_range(100..=150), 2)).min(dec!(1_000_000_000)));
+    MortgageInput {
+        principal,
+        annual_interest_rate: if rng.gen_bool(0.7) { amount(rng, 0).min(dec!(1)) } else { amount(rng, 2).min(dec!(100)) },
+        term_years: rng.gen_range(1..=50),
+        repayment_type: repayment_type(rng),
+        start_date: if rng.gen_bool(0.9) {
+            NaiveDate::from_ymd_opt(rng.gen_range(1900..=2100), rng.gen_range(1..=12), rng.gen_range(1..=28)).unwrap()
+        } else {
+            any_date(rng)
+        },
+        balloon_payment_percentage: Decimal::from(rng.gen_range(0..=100)),
+        extra_payment: if rng.gen_bool(0.5) { Decimal::ZERO } else { amount(rng, 9).min(dec!(1_000_000_000)) },
+        escrow: rng.gen_bool(0.3).then(|| escrow(
+            rng,
+            |rng| Decimal::new(rng.gen_range(-100..=100), 2),
+            |rng| amount(rng, 8).min(dec!(100_000_000)),
+        )),
+        property_value,
+        mortgage_insurance: property_value.filter(|_| rng.gen_bool(0.7)).map(|_| mortgage_insurance(rng)),
+        prepayment_penalty: rng.gen_bool(0.2).then(|| PrepaymentPenalty::YieldMaintenance {
+            treasury_rate: Decimal::new(rng.gen_range(0..=100), 2),
+            minimum_percentage: dec!(1),
+        }),
+        modifications: (0..rng.gen_range(0..3))
+            .map(|_| {
+                let start_payment = rng.gen_range(1..=700);
+                modification(rng, start_payment, principal)
+            })
+            .collect(),
+    }
+}
+
+/// Loan passing `MortgageInput::validate_for`, without modifications
+fn valid_loan(rng: &mut StdRng) -> MortgageInput {
+    loop {
+        let mut input = validated_ranges_loan(rng);
+        input.modifications.clear();
+        if input.repayment_type == RepaymentType::BalloonPayment {
+            input.balloon_payment_percentage = input.balloon_payment_percentage.clamp(dec!(1), dec!(99));
+        } else {
+            input.balloon_payment_percentage = Decimal::ZERO;
+        }
+        if input.validate_for(input.repayment_type).is_ok() {
+            return input;
+        }
+    }
+}
+
+#[test]
+fn test_calculators_never_panic() {
+    let mut rng = StdRng::seed_from_u64(0x6d6f7274);
+    for case in 0..CASES {
+        let input = if case % 2 == 0 { any_loan(&mut rng) } else { validated_ranges_loan(&mut rng) };
+        // Completing without a panic is the property; wild inputs are expected to error
+        let schedule = calculate_schedule(&input);
+        let summary = calculate_summary(&input);
+        assert_eq!(schedule.is_ok(), summary.is_ok(), "{:?}", input);
+    }
+}
+
+#[test]
+fn test_validated_loans_calculate() {
+    let mut rng = StdRng::seed_from_u64(0x6b6974);
+    for _ in 0..CASES {
+        let input = valid_loan(&mut rng);
+        let schedule = calculate_schedule(&input)
+            .unwrap_or_else(|error| panic!("{} for {:?}", error, input));
+        let last = schedule.schedule.last().expect("a validated loan has payments");
+        assert!(last.remaining_principal.abs() < dec!(0.01), "{:?}", input);
+        assert!(last.payment_date.year() <= input.start_date.year() + input.term_years as i32, "{:?}", input);
+        assert!(schedule.schedule.iter().all(|entry| entry.interest_component >= Decimal::ZERO), "{:?}", input);
+    }
+}
+
+#[test]
+fn test_summaries_and_streams_match_schedules() {
+    let mut rng = StdRng::seed_from_u64(0x73756d);
+    for case in 0..CASES {
+        let mut input = valid_loan(&mut rng);
+        // Floating rates are redrawn on every calculation
+        if input.repayment_type == RepaymentType::FloatingRate {
+            continue;
+        }
+        if case % 2 == 0 {
+            input.escrow = None;
+            input.prepayment_penalty = None;
+            input.extra_payment = if case % 4 == 0 { Decimal::ZERO } else { input.extra_payment };
+        }
+        let schedule = calculate_schedule(&input).unwrap();
+        let summary = calculate_summary(&input).unwrap();
+        let tolerance = dec!(0.000001);
+
+        assert_eq!(summary.number_of_payments as usize, schedule.payment_count(), "{:?}", input);
+        assert_eq!(summary.monthly_payment, schedule.monthly_payment, "{:?}", input);
+        assert!((summary.total_payments - schedule.total_payments).abs() < tolerance, "{:?}", input);
+        assert!((summary.total_interest - schedule.total_interest).abs() < tolerance, "{:?}", input);
+
+        let streamed: Vec<_> = schedule_entries(&input).unwrap().collect();
+        assert_eq!(
+            serde_json::to_value(&streamed).unwrap(),
+            serde_json::to_value(&schedule.schedule).unwrap(),
+            "{:?}", input
+        );
+    }
+}
+
+fn valid_affordability(rng: &mut StdRng) -> AffordabilityInput {
+    AffordabilityInput {
+        gross_annual_income: bounded_amount(rng).max(dec!(0.01)),
+        monthly_debts: bounded_amount(rng),
+        front_end_dti_limit: rate(rng),
+        back_end_dti_limit: rate(rng),
+        annual_interest_rate: rate(rng),
+        term_years: rng.gen_range(1..=50),
+        property_tax_rate: rate(rng),
+        annual_insurance: bounded_amount(rng),
+        monthly_hoa: bounded_amount(rng),
+        down_payment: bounded_amount(rng),
+    }
+}
+
+fn valid_rent_vs_buy(rng: &mut StdRng) -> RentVsBuyInput {
+    let home_price = bounded_amount(rng).max(dec!(0.01));
+    RentVsBuyInput {
+        home_price,
+        down_payment: home_price * Decimal::new(rng.gen_range(0..100), 2),
+        annual_interest_rate: rate(rng),
+        term_years: rng.gen_range(1..=50),
+        repayment_type: repayment_type(rng),
+        start_date: NaiveDate::from_ymd_opt(rng.gen_range(1900..=2100), rng.gen_range(1..=12), 1).unwrap(),
+        mortgage_insurance: rng.gen_bool(0.3).then(|| mortgage_insurance(rng)),
+        closing_costs: bounded_amount(rng),
+        selling_cost_rate: rate(rng),
+        property_tax_rate: rate(rng),
+        annual_insurance: bounded_amount(rng),
+        monthly_hoa: bounded_amount(rng),
+        maintenance_rate: rate(rng),
+        home_appreciation_rate: growth_rate(rng),
+        cost_inflation_rate: growth_rate(rng),
+        marginal_tax_rate: rate(rng),
+        standard_deduction: bounded_amount(rng),
+        monthly_rent: bounded_amount(rng),
+        rent_growth_rate: growth_rate(rng),
+        annual_renters_insurance: bounded_amount(rng),
+        investment_return_rate: growth_rate(rng),
+        horizon_years: rng.gen_range(1..=50),
+    }
+}
+
+fn valid_refinance(rng: &mut StdRng) -> RefinanceInput {
+    let existing_loan = valid_loan(rng);
+    let from_balance = rng.gen_bool(0.3);
+    RefinanceInput {
+        current_payment_number: (!from_balance).then(|| rng.gen_range(0..=existing_loan.term_years * 12)),
+        current_balance: from_balance.then(|| existing_loan.principal * Decimal::new(rng.gen_range(0..=100), 2)),
+        existing_loan,
+        offers: (0..rng.gen_range(1..=3))
+            .map(|index| RefinanceOffer {
+                name: format!("Offer {}", index),
+                annual_interest_rate: rate(rng),
+                term_years: rng.gen_range(1..=50),
+                repayment_type: repayment_type(rng),
+                closing_costs: bounded_amount(rng),
+                points: Decimal::from(rng.gen_range(0..=10)),
+                finance_closing_costs: rng.gen(),
+            })
+            .collect(),
+        discount_rate: rate(rng),
+    }
+}
+
+fn valid_pool(rng: &mut StdRng) -> PoolRequest {
+    PoolRequest {
+        loans: (0..rng.gen_range(1..=4)).map(|_| valid_loan(rng)).collect(),
+        prepayment: match rng.gen_range(0..4) {
+            0 => None,
+            1 => Some(PrepaymentSpeed::Cpr { rate: Decimal::from(rng.gen_range(0..=100)) }),
+            2 => Some(PrepaymentSpeed::Smm { rate: Decimal::from(rng.gen_range(0..=100)) }),
+            _ => Some(PrepaymentSpeed::Psa { speed: Decimal::from(rng.gen_range(0..=5000)) }),
+        },
+        defaults: rng.gen_bool(0.5).then(|| DefaultAssumptions {
+            cdr: Decimal::from(rng.gen_range(0..=100)),
+            loss_severity: Decimal::from(rng.gen_range(0..=100)),
+            recovery_lag_months: rng.gen_range(0..=120),
+        }),
+    }
+}
+
+#[test]
+fn test_validated_analyses_never_panic() {
+    let mut rng = StdRng::seed_from_u64(0x616e61);
+    for _ in 0..CASES / 4 {
+        let affordability = valid_affordability(&mut rng);
+        assert!(affordability.validate().is_ok(), "{:?}", affordability);
+        // Completing without a panic is the property; extreme terms may still overflow
+        let _ = AffordabilityCalculator::calculate(&affordability);
+
+        let rent_vs_buy = valid_rent_vs_buy(&mut rng);
+        assert!(rent_vs_buy.validate().is_ok(), "{:?}", rent_vs_buy);
+        let _ = RentVsBuyCalculator::analyze(&rent_vs_buy);
+
+        let refinance = valid_refinance(&mut rng);
+        assert!(refinance.validate().is_ok(), "{:?}", refinance);
+        let _ = RefinanceCalculator::analyze(&refinance);
+
+        let pool = valid_pool(&mut rng);
+        assert!(pool.validate().is_ok(), "{:?}", pool);
+        let _ = PoolCalculator::project(&pool);
+    }
+}
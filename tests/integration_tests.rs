@@ -2,8 +2,9 @@ use actix_web::{test, App};
 use rust_decimal_macros::dec;
 use chrono::NaiveDate;
 use mortgagekit_rs::{
-    models::{MortgageInput, RepaymentType},
-    api::configure_routes,
+    models::{CustomRepaymentType, MortgageInput, RepaymentType, RepaymentTypeInfo},
+    api::{configure_routes, configure_routes_with},
+    config::ServerConfig,
 };
 
 #[actix_web::test]
@@ -14,14 +15,13 @@ async fn test_full_mortgage_calculation() {
     ).await;
 
     // Create test input
-    let input = MortgageInput {
-        principal: dec!(300000),
-        annual_interest_rate: dec!(0.05),
-        term_years: 30,
-        repayment_type: RepaymentType::StandardPrincipalAndInterest,
-        start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        balloon_payment_percentage: dec!(0),
-    };
+    let input = MortgageInput::new(
+        dec!(300000),
+        dec!(0.05),
+        30,
+        RepaymentType::StandardPrincipalAndInterest,
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+    );
 
     // Test full schedule calculation
     let req = test::TestRequest::post()
@@ -43,14 +43,13 @@ async fn test_all_repayment_types() {
         App::new().configure(configure_routes)
     ).await;
 
-    let base_input = MortgageInput {
-        principal: dec!(300000),
-        annual_interest_rate: dec!(0.05),
-        term_years: 30,
-        start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        balloon_payment_percentage: dec!(0),
-        repayment_type: RepaymentType::StandardPrincipalAndInterest,
-    };
+    let base_input = MortgageInput::new(
+        dec!(300000),
+        dec!(0.05),
+        30,
+        RepaymentType::StandardPrincipalAndInterest,
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+    );
 
     // Test each repayment type
     for repayment_type in [
@@ -84,14 +83,13 @@ async fn test_invalid_inputs() {
     ).await;
 
     // Test negative principal
-    let invalid_input = MortgageInput {
-        principal: dec!(-100000),
-        annual_interest_rate: dec!(0.05),
-        term_years: 30,
-        repayment_type: RepaymentType::StandardPrincipalAndInterest,
-        start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        balloon_payment_percentage: dec!(0),
-    };
+    let invalid_input = MortgageInput::new(
+        dec!(-100000),
+        dec!(0.05),
+        30,
+        RepaymentType::StandardPrincipalAndInterest,
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+    );
 
     let req = test::TestRequest::post()
         .uri("/api/v1/calculate")
@@ -102,14 +100,13 @@ async fn test_invalid_inputs() {
     assert!(resp.status().is_client_error());
 
     // Test invalid interest rate
-    let invalid_input = MortgageInput {
-        principal: dec!(100000),
-        annual_interest_rate: dec!(101), // Over 100%
-        term_years: 30,
-        repayment_type: RepaymentType::StandardPrincipalAndInterest,
-        start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        balloon_payment_percentage: dec!(0),
-    };
+    let invalid_input = MortgageInput::new(
+        dec!(100000),
+        dec!(101), // Over 100%
+        30,
+        RepaymentType::StandardPrincipalAndInterest,
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+    );
 
     let req = test::TestRequest::post()
         .uri("/api/v1/calculate")
@@ -150,3 +147,807 @@ async fn test_health_check() {
     let resp = test::call_service(&app, req).await;
     assert!(resp.status().is_success());
 }
+
+#[actix_web::test]
+async fn test_affordability_endpoint() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/affordability")
+        .set_json(serde_json::json!({
+            "grossAnnualIncome": 120000,
+            "monthlyDebts": 500,
+            "annualInterestRate": 0.06,
+            "termYears": 30,
+            "propertyTaxRate": 0.012,
+            "annualInsurance": 1200,
+            "downPayment": 60000
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let result: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(result["limitingRatio"], "frontEnd");
+    assert!(result["maxLoanAmount"].is_string());
+
+    // Rates are fractions, so 100 is 10,000% and rejected rather than priced
+    let req = test::TestRequest::post()
+        .uri("/api/v1/affordability")
+        .set_json(serde_json::json!({
+            "grossAnnualIncome": 120000,
+            "annualInterestRate": 100,
+            "termYears": 50
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["errors"][0]["field"], "annualInterestRate");
+    assert_eq!(body["errors"][0]["code"], "invalid_rate");
+}
+
+#[actix_web::test]
+async fn test_solve_endpoint() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/solve")
+        .set_json(serde_json::json!({
+            "input": {
+                "principal": 300000,
+                "annualInterestRate": 0.05,
+                "termYears": 30,
+                "repaymentType": "standardPrincipalAndInterest",
+                "startDate": "2024-01-01"
+            },
+            "target": { "type": "monthlyPayment", "value": 2000 },
+            "variable": "principal"
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let result: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(result["variable"], "principal");
+    assert_eq!(result["achieved"]["type"], "monthlyPayment");
+
+    // A balloon target on a standard loan cannot be solved
+    let req = test::TestRequest::post()
+        .uri("/api/v1/solve")
+        .set_json(serde_json::json!({
+            "input": {
+                "principal": 300000,
+                "annualInterestRate": 0.05,
+                "termYears": 30,
+                "repaymentType": "standardPrincipalAndInterest",
+                "startDate": "2024-01-01"
+            },
+            "target": { "type": "balloonAmount", "value": 50000 },
+            "variable": "principal"
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+async fn test_compare_endpoint() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/compare")
+        .set_json(serde_json::json!({
+            "base": {
+                "principal": 300000,
+                "annualInterestRate": 0.06,
+                "termYears": 30,
+                "repaymentType": "standardPrincipalAndInterest",
+                "startDate": "2024-01-01"
+            },
+            "scenarios": [
+                { "name": "30 year" },
+                { "name": "15 year", "overrides": { "termYears": 15, "annualInterestRate": 0.055 } }
+            ]
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let result: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(result["scenarios"][1]["name"], "15 year");
+    assert_eq!(result["scenarios"][1]["input"]["termYears"], 15);
+    assert_eq!(result["differences"][0][1]["numberOfPayments"], -180);
+    assert_eq!(result["costCurves"].as_array().unwrap().len(), 360);
+
+    // Overrides need a base loan to apply to
+    let req = test::TestRequest::post()
+        .uri("/api/v1/compare")
+        .set_json(serde_json::json!({
+            "scenarios": [{ "overrides": { "termYears": 15 } }]
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_rent_vs_buy_endpoint() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/rent-vs-buy")
+        .set_json(serde_json::json!({
+            "homePrice": 400000,
+            "downPayment": 80000,
+            "annualInterestRate": 0.065,
+            "termYears": 30,
+            "startDate": "2024-01-01",
+            "propertyTaxRate": 0.012,
+            "annualInsurance": 1500,
+            "monthlyRent": 2500,
+            "horizonYears": 10
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let result: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(result["years"].as_array().unwrap().len(), 10);
+    assert_eq!(result["loanAmount"], "320000");
+}
+
+#[actix_web::test]
+async fn test_payoff_endpoint() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/payoff")
+        .set_json(serde_json::json!({
+            "input": {
+                "principal": 360000,
+                "annualInterestRate": 0.06,
+                "termYears": 30,
+                "repaymentType": "standardPrincipalAndInterest",
+                "startDate": "2024-01-01"
+            },
+            "payoffDate": "2024-01-16",
+            "dayCount": "actual/365"
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let result: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(result["lastPaidPaymentNumber"], 1);
+    assert_eq!(result["daysAccrued"], 15);
+    assert_eq!(result["dayCount"], "actual/365");
+}
+
+#[actix_web::test]
+async fn test_payment_history_endpoint() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/payment-history")
+        .set_json(serde_json::json!({
+            "input": {
+                "principal": 200000,
+                "annualInterestRate": 0.06,
+                "termYears": 30,
+                "repaymentType": "standardPrincipalAndInterest",
+                "startDate": "2024-01-01"
+            },
+            "payments": [
+                { "receivedDate": "2024-01-01", "amount": 1199.10 },
+                { "receivedDate": "2024-02-10", "amount": 600 }
+            ],
+            "asOfDate": "2024-03-15"
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let result: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(result["installmentsPastDue"], 2);
+    assert_eq!(result["suspenseBalance"], "600");
+    assert_eq!(result["installments"][1]["status"], "pastDue");
+}
+
+#[actix_web::test]
+async fn test_loan_modification_summary() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate/summary")
+        .set_json(serde_json::json!({
+            "principal": 300000,
+            "annualInterestRate": 0.07,
+            "termYears": 30,
+            "repaymentType": "standardPrincipalAndInterest",
+            "startDate": "2024-01-01",
+            "modifications": [
+                { "type": "principalForbearance", "startPayment": 25, "amount": 40000 },
+                { "type": "termExtension", "startPayment": 25, "additionalMonths": 60 }
+            ]
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let summary: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(summary["deferredBalance"], "40000");
+    assert_eq!(summary["numberOfPayments"], 420);
+}
+
+#[actix_web::test]
+async fn test_pool_endpoint() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let loan = serde_json::json!({
+        "principal": 150000,
+        "annualInterestRate": 0.065,
+        "termYears": 30,
+        "repaymentType": "standardPrincipalAndInterest",
+        "startDate": "2024-01-01"
+    });
+    let req = test::TestRequest::post()
+        .uri("/api/v1/pool")
+        .set_json(serde_json::json!({
+            "loans": [loan.clone(), loan],
+            "prepayment": { "type": "psa", "speed": 150 },
+            "defaults": { "cdr": 1, "lossSeverity": 35 }
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let result: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(result["loanCount"], 2);
+    assert_eq!(result["originalBalance"], "300000");
+    assert_eq!(result["cashFlows"][0]["recoveries"], "0");
+    assert_ne!(result["cashFlows"][12]["recoveries"], "0");
+}
+
+#[actix_web::test]
+async fn test_batch_endpoint_streams_ndjson() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let loan = r#"{"principal":250000,"annualInterestRate":0.05,"termYears":30,"repaymentType":"standardPrincipalAndInterest","startDate":"2024-01-01"}"#;
+    let invalid = r#"{"principal":-5,"annualInterestRate":0.05,"termYears":30,"repaymentType":"standardPrincipalAndInterest","startDate":"2024-01-01"}"#;
+    let req = test::TestRequest::post()
+        .uri("/api/v1/batch?output=both")
+        .insert_header(("content-type", "application/x-ndjson"))
+        .set_payload(format!("{loan}\n{invalid}\n{{not json\n{loan}\n"))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    assert_eq!(resp.headers().get("content-type").unwrap(), "application/x-ndjson");
+
+    let body = test::read_body(resp).await;
+    let lines: Vec<serde_json::Value> = std::str::from_utf8(&body).unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[3]["index"], 3);
+    assert!(lines[0]["summary"].is_object() && lines[0]["schedule"].is_object());
+    assert!(lines[1]["error"].is_string());
+    assert!(lines[2]["error"].is_string());
+}
+
+#[actix_web::test]
+async fn test_batch_endpoint_rejects_malformed_array() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/batch")
+        .set_payload("[{\"principal\": 1")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_analytics_endpoint() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/analytics")
+        .set_json(serde_json::json!({
+            "input": {
+                "principal": 200000,
+                "annualInterestRate": 0.06,
+                "termYears": 30,
+                "repaymentType": "standardPrincipalAndInterest",
+                "startDate": "2024-01-01"
+            },
+            "pricing": { "type": "yield", "annualYield": 0.06 }
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let result: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(result["dollarPrice"], "200000.00");
+    assert!(result["dv01"].is_string());
+    assert!(result["weightedAverageLife"].is_string());
+}
+
+#[actix_web::test]
+async fn test_present_value_endpoint() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/present-value")
+        .set_json(serde_json::json!({
+            "input": {
+                "principal": 300000,
+                "annualInterestRate": 0.06,
+                "termYears": 30,
+                "repaymentType": "standardPrincipalAndInterest",
+                "startDate": "2024-01-01"
+            },
+            "curve": {
+                "type": "parRates",
+                "points": [
+                    { "termYears": 1, "rate": 0.045 },
+                    { "termYears": 10, "rate": 0.05 },
+                    { "termYears": 30, "rate": 0.055 }
+                ]
+            }
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let result: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(result["zeroCurve"].as_array().unwrap().len(), 60);
+    assert!(result["npv"].as_str().unwrap().parse::<f64>().unwrap() > 0.0);
+}
+
+#[actix_web::test]
+async fn test_openapi_docs() {
+    let mut config = ServerConfig::default();
+    config.docs.swagger_ui_url = "/assets/swagger-ui".to_string();
+    let app = test::init_service(
+        App::new().configure(|cfg| configure_routes_with(cfg, &config))
+    ).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/docs/openapi.json")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let spec: serde_json::Value = test::read_body_json(resp).await;
+    for path in [
+        "/api/v1/calculate", "/api/v1/calculate/summary", "/api/v1/batch", "/api/v1/affordability", "/api/v1/solve",
+        "/api/v1/refinance", "/api/v1/compare", "/api/v1/rent-vs-buy", "/api/v1/payoff", "/api/v1/payment-history",
+        "/api/v1/interest-statement", "/api/v1/pool", "/api/v1/analytics", "/api/v1/present-value",
+    ] {
+        assert!(spec["paths"][path]["post"].is_object(), "missing {path}");
+    }
+    assert!(spec["paths"]["/api/v1/repayment-types"]["get"].is_object());
+    for schema in [
+        "MortgageInput", "MortgageSchedule", "MortgageSummary", "RepaymentTypeInfo", "ProblemDetails",
+        "AffordabilityResult", "RefinanceInput", "PoolProjection", "PresentValue", "InterestStatement",
+    ] {
+        assert!(spec["components"]["schemas"][schema].is_object(), "missing {schema}");
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/api/docs")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    assert_eq!(resp.headers().get("content-type").unwrap(), "text/html; charset=utf-8");
+    let page = test::read_body(resp).await;
+    assert!(std::str::from_utf8(&page).unwrap().contains(r#"src="/assets/swagger-ui/swagger-ui-bundle.js""#));
+}
+
+#[actix_web::test]
+async fn test_disabled_features_are_not_served() {
+    let mut config = ServerConfig::default();
+    config.features.docs = false;
+    config.features.batch = false;
+    let app = test::init_service(
+        App::new().configure(|cfg| configure_routes_with(cfg, &config))
+    ).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/docs/openapi.json")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/batch")
+        .set_payload("[]")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/health")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+}
+
+#[actix_web::test]
+async fn test_errors_are_problem_details() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate")
+        .set_json(serde_json::json!({
+            "principal": "-100000",
+            "annualInterestRate": "0.05",
+            "termYears": 30,
+            "repaymentType": "standardPrincipalAndInterest",
+            "startDate": "2024-01-01",
+            "balloonPaymentPercentage": "0",
+            "extraPayment": "0"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    assert_eq!(resp.headers().get("content-type").unwrap(), "application/problem+json");
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "validation_error");
+    assert_eq!(body["errors"][0]["field"], "principal");
+    assert_eq!(body["errors"][0]["code"], "invalid_principal");
+    assert_eq!(body["errors"][0]["rejectedValue"], "-100000");
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate")
+        .insert_header(("content-type", "application/json"))
+        .set_payload("{\"principal\": ")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["type"], "about:blank");
+    assert_eq!(body["status"], 400);
+    assert_eq!(body["code"], "malformed_request");
+}
+
+#[actix_web::test]
+async fn test_cross_field_validation() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let input = MortgageInput {
+        balloon_payment_percentage: dec!(20),
+        ..MortgageInput::new(
+            dec!(300000),
+            dec!(0.05),
+            30,
+            RepaymentType::StandardPrincipalAndInterest,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        )
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate/summary")
+        .set_json(&input)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["errors"][0]["field"], "balloonPaymentPercentage");
+    assert_eq!(body["errors"][0]["code"], "balloon_percentage_not_allowed");
+
+    // The same loan nested in another request is checked under its own path
+    let req = test::TestRequest::post()
+        .uri("/api/v1/present-value")
+        .set_json(serde_json::json!({ "input": input, "curve": { "type": "flat", "rate": "0.05" } }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["errors"][0]["field"], "input.balloonPaymentPercentage");
+}
+
+static ONE_PERCENT_PREPAYMENT: CustomRepaymentType =
+    CustomRepaymentType::new("onePercentPrepayment", "One Percent Prepayment");
+
+/// Standard amortization with 1% of the original principal prepaid every month
+struct OnePercentPrepayment;
+
+impl mortgagekit_rs::MortgageCalculator for OnePercentPrepayment {
+    fn repayment_type(&self) -> RepaymentType {
+        RepaymentType::Custom(&ONE_PERCENT_PREPAYMENT)
+    }
+
+    fn description(&self) -> &str {
+        "Standard payments plus 1% of the original principal every month."
+    }
+
+    fn calculate_schedule(&self, input: &MortgageInput) -> Result<mortgagekit_rs::MortgageSchedule, mortgagekit_rs::CalculationError> {
+        let mut input = input.clone();
+        input.repayment_type = RepaymentType::StandardPrincipalAndInterest;
+        input.extra_payment = input.principal / dec!(100);
+        mortgagekit_rs::calculators::calculate_schedule(&input)
+    }
+}
+
+#[actix_web::test]
+async fn test_registered_calculator_is_served() {
+    mortgagekit_rs::calculators::register_calculator(OnePercentPrepayment);
+    assert!(RepaymentTypeInfo::all().iter()
+        .any(|info| info.repayment_type == RepaymentType::Custom(&ONE_PERCENT_PREPAYMENT)));
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/repayment-types")
+        .to_request();
+    let types: Vec<serde_json::Value> = test::read_body_json(test::call_service(&app, req).await).await;
+    assert!(types.iter().any(|info| info["repaymentType"] == "onePercentPrepayment"
+        && info["name"] == "One Percent Prepayment"));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate/summary")
+        .set_json(serde_json::json!({
+            "principal": "100000",
+            "annualInterestRate": "0.05",
+            "termYears": 30,
+            "repaymentType": "onePercentPrepayment",
+            "startDate": "2024-01-01",
+            "balloonPaymentPercentage": "0",
+            "extraPayment": "0"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let summary: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(summary["repaymentType"], "onePercentPrepayment");
+    assert!(summary["numberOfPayments"].as_u64().unwrap() < 360);
+}
+
+#[actix_web::test]
+async fn test_schedule_pages_ranges_and_years() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+    let input = serde_json::json!({
+        "principal": "200000",
+        "annualInterestRate": "0.06",
+        "termYears": 30,
+        "repaymentType": "standardPrincipalAndInterest",
+        "startDate": "2024-01-15",
+        "balloonPaymentPercentage": "0",
+        "extraPayment": "0"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate?page=3&pageSize=25")
+        .set_json(&input)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "360");
+    let schedule: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(schedule["schedule"].as_array().unwrap().len(), 25);
+    assert_eq!(schedule["schedule"][0]["paymentNumber"], 51);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate?from=2030-01-01&to=2030-12-31&aggregate=calendarYear")
+        .set_json(&input)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    let years: Vec<serde_json::Value> = test::read_body_json(resp).await;
+    assert_eq!(years.len(), 1);
+    assert_eq!(years[0]["year"], 2030);
+    assert!(years[0]["numberOfPayments"].as_u64().unwrap() >= 12);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate?aggregate=loanYear")
+        .set_json(&input)
+        .to_request();
+    let years: Vec<serde_json::Value> = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(years.len(), 30);
+    assert_eq!(years[29]["endingBalance"].as_str().map(|balance| balance.parse::<f64>().unwrap().abs() < 0.01), Some(true));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate?from=2030-01-01&to=2029-01-01&pageSize=0")
+        .set_json(&input)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let codes: Vec<&str> = body["errors"].as_array().unwrap().iter()
+        .map(|error| error["code"].as_str().unwrap())
+        .collect();
+    assert!(codes.contains(&"invalid_date_range"));
+    assert!(codes.contains(&"range"));
+}
+
+#[actix_web::test]
+async fn test_schedule_and_summary_exports() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+    let input = serde_json::json!({
+        "principal": "200000",
+        "annualInterestRate": "0.06",
+        "termYears": 30,
+        "repaymentType": "standardPrincipalAndInterest",
+        "startDate": "2024-01-15",
+        "balloonPaymentPercentage": "0",
+        "extraPayment": "0"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate?from=2025-01-01&to=2025-12-31")
+        .insert_header(("Accept", "text/csv"))
+        .set_json(&input)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    assert_eq!(resp.headers().get("Content-Type").unwrap(), "text/csv; charset=utf-8");
+    assert_eq!(resp.headers().get("Content-Disposition").unwrap(), "attachment; filename=\"schedule.csv\"");
+    let csv = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert!(lines[0].starts_with("Payment,Date,Payment Amount,Principal,Interest"));
+    assert!(lines.len() >= 13);
+    assert!(lines[1..].iter().all(|line| line.split(',').nth(1).unwrap().starts_with("2025-")));
+    assert!(lines[1].contains(",1199.10,"));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate?aggregate=loanYear")
+        .insert_header(("Accept", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"))
+        .set_json(&input)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "30");
+    let workbook = test::read_body(resp).await;
+    assert!(workbook.starts_with(b"PK\x03\x04"));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate/summary")
+        .insert_header(("Accept", "text/html"))
+        .set_json(&input)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    assert_eq!(resp.headers().get("Content-Disposition").unwrap(), "inline; filename=\"summary.html\"");
+    let html = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(html.contains("<h1>Standard Principal and Interest Loan Summary</h1>"));
+    assert!(html.contains("@media print"));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate/summary")
+        .insert_header(("Accept", "image/png"))
+        .set_json(&input)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_ACCEPTABLE);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "not_acceptable");
+
+    // Floating rates differ on every calculation, yet the summary must total the years it is exported with
+    let mut floating = input.clone();
+    floating["repaymentType"] = "floatingRate".into();
+    let req = test::TestRequest::post()
+        .uri("/api/v1/calculate?aggregate=loanYear")
+        .insert_header(("Accept", "text/html"))
+        .set_json(&floating)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    let html = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    let rows: Vec<Vec<f64>> = html.split("<tr>").skip(1)
+        .map(|row| row.split("<td").skip(1)
+            .filter_map(|cell| cell.split('>').nth(1)?.split('<').next()?.parse().ok())
+            .collect())
+        .collect();
+    let summary_interest = html.split("<td>Total Interest</td><td class=\"number\">").nth(1).unwrap()
+        .split('<').next().unwrap()
+        .parse::<f64>().unwrap();
+    // Year rows carry year, payment count and five amounts; interest is the fourth amount
+    let yearly_interest: f64 = rows.iter().filter(|row| row.len() == 6).map(|row| row[4]).sum();
+    assert!((summary_interest - yearly_interest).abs() < 1.0, "{} vs {}", summary_interest, yearly_interest);
+}
+
+#[actix_web::test]
+async fn test_interest_statement_endpoint() {
+    let app = test::init_service(
+        App::new().configure(configure_routes)
+    ).await;
+    let input = serde_json::json!({
+        "principal": 200000,
+        "annualInterestRate": 0.06,
+        "termYears": 30,
+        "repaymentType": "standardPrincipalAndInterest",
+        "startDate": "2024-01-01"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/interest-statement")
+        .set_json(serde_json::json!({ "input": input, "taxYear": 2024, "pointsPaid": 2000 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    let statement: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(statement["source"], "schedule");
+    assert_eq!(statement["outstandingPrincipal"], "200000");
+    assert_eq!(statement["pointsPaid"], "2000");
+    assert_eq!(statement["originationDate"], "2024-01-01");
+    assert!(statement["mortgageInterest"].as_str().unwrap().parse::<f64>().unwrap() > 11000.0);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/interest-statement")
+        .set_json(serde_json::json!({
+            "input": input,
+            "taxYear": 2024,
+            "payments": [
+                { "receivedDate": "2024-01-01", "amount": 1199.10 },
+                { "receivedDate": "2024-01-31", "amount": 1199.10 }
+            ]
+        }))
+        .to_request();
+    let statement: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(statement["source"], "paymentHistory");
+    assert_eq!(statement["numberOfPayments"], 2);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/interest-statement")
+        .set_json(serde_json::json!({ "input": input, "taxYear": 2023 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["errors"][0]["code"], "invalid_tax_year");
+}